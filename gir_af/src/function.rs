@@ -2,6 +2,8 @@ use gir_core::primitives::*;
 use gir_core::graph::*;
 use gir_core::backend::*;
 use gir_core::errors::*;
+use gir_core::ops::SliceRange;
+use gir_core::passes::{Pass, EinsumLowering};
 
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -10,6 +12,14 @@ use std::ops::Neg;
 
 use arrayfire as af;
 
+fn eval_sym_int(sym_int: &SymInt, deduced: &HashMap<String, i64>) -> Result<i64> {
+    match sym_int.eval(deduced) {
+        Ok(v) => Ok(v),
+        Err(_) => Err(ErrorKind::Msg(format!("Could not evaluate the symbolic integer {} \
+        with the deduced values {:?}.", sym_int.to_code(&|x: String| x), deduced)).into())
+    }
+}
+
 #[derive(Clone)]
 pub struct AfFunction {
     pub initialized: bool,
@@ -23,20 +33,25 @@ pub struct AfFunction {
 }
 
 impl AfFunction {
-    pub fn internal_eval(&mut self, inputs: &[&af::Array]) {
+    pub fn internal_eval(&mut self, inputs: &[&af::Array]) -> Result<()> {
         for (&id, input) in self.gf.inputs.iter().zip(inputs) {
             self.expr_map.insert(id, (*input).clone());
         }
         for (name, &id) in self.gf.parameters.iter() {
-            self.expr_map.insert(id, self.parameters.borrow().get(name).unwrap().clone());
+            match self.parameters.borrow().get(name) {
+                Some(v) => self.expr_map.insert(id, v.clone()),
+                None => return Err(ErrorKind::Msg(format!("The parameter {} has not been \
+                initialized.", name)).into())
+            };
 //            let v = self.expr_map.get(&id).unwrap();
 //            println!("Id: {}", id);
 //            af_print!("Value:",v);
         }
         let order = self.gf.graph.order.clone();
         for &id in &order {
-            self.compute_node(id);
+            self.compute_node(id)?;
         }
+        Ok(())
     }
 }
 
@@ -47,6 +62,12 @@ impl CompiledFunction<af::Array, af::Array> for AfFunction {
             return Err(ErrorKind::Msg(format!("Incorrect number of inputs. \
             Expected: {}, actual: {}.", self.gf.inputs.len(), inputs.len())).into());
         }
+        // ArrayFire has no native tensor contractions, which are lowered on the first call
+        if !self.initialized {
+            EinsumLowering::default().run(&mut self.gf)
+                .chain_err(|| "Could not lower the tensor contractions of the function.")?;
+            self.initialized = true;
+        }
         let input_shapes: Vec<[usize;4]> = inputs.iter().map(|x| {
             let mut dims = [1, 1, 1, 1];
             for (i, &d) in x.dims().get().iter().enumerate() {
@@ -62,7 +83,7 @@ impl CompiledFunction<af::Array, af::Array> for AfFunction {
             },
             None => {}
         }
-        self.internal_eval(inputs);
+        self.internal_eval(inputs)?;
         let mut result = Vec::new();
         for i in &self.gf.outputs {
             result.push(self.expr_map.remove(i).unwrap());
@@ -78,7 +99,7 @@ impl CompiledFunction<af::Array, af::Array> for AfFunction {
 }
 
 impl AfFunction {
-    fn compute_node(&mut self, id: usize) {
+    fn compute_node(&mut self, id: usize) -> Result<()> {
         let ref node = self.gf.graph.nodes[id];
         let expr_map = &mut self.expr_map;
         let op_meta = node.op.get_meta();
@@ -90,6 +111,22 @@ impl AfFunction {
                 let result = af::constant(value as f32, af::Dim4::new(&[1, 1, 1, 1]));
                 expr_map.insert(node.id, result);
             },
+            "Basis" => {
+                let (index, _, _) = *node.op.get_args().unwrap()
+                    .downcast::<(usize, Shape, FundamentalType)>().unwrap();
+                let mut dims = [1u64; 4];
+                for (i, d) in [&node.shape.0, &node.shape.1, &node.shape.2, &node.shape.3].iter().enumerate() {
+                    dims[i] = eval_sym_int(d, &self.last_deduced)? as u64;
+                }
+                let mut values = vec![0.0f32; (dims[0] * dims[1] * dims[2] * dims[3]) as usize];
+                if index >= values.len() {
+                    return Err(ErrorKind::Msg(format!("The basis index {} is out of bounds \
+                    for the shape {:?}.", index, dims)).into())
+                }
+                values[index] = 1.0;
+                let result = af::Array::new(&values, af::Dim4::new(&[dims[0], dims[1], dims[2], dims[3]]));
+                expr_map.insert(node.id, result);
+            },
             "Add" => {
                 let result = match node.ancestors.len() {
                     2 => af::add(expr_map.get(&node.ancestors[0]).unwrap(),
@@ -112,12 +149,26 @@ impl AfFunction {
                 //            println!("{:?} vs {:?}", expr_map.get(&node.ancestors[0]).unwrap().dims(),
                 //                     expr_map.get(&node.ancestors[1]).unwrap().dims());
                 //            println!("{:?}", node.ancestors);
-                let result = match node.ancestors.len() {
-                    2 => af::matmul(expr_map.get(&node.ancestors[0]).unwrap(),
-                                    expr_map.get(&node.ancestors[1]).unwrap(),
-                                    af::MatProp::NONE, af::MatProp::NONE),
-                    _ => unimplemented!()
+                // Longer chains are ordered by the MatrixChainOrdering pass,
+                // otherwise they are multiplied from left to right
+                let batch = [node.shape.2.eval(&self.last_deduced).unwrap() as u64,
+                    node.shape.3.eval(&self.last_deduced).unwrap() as u64];
+                // Batch axes of size one are tiled to the batch shape of the result
+                let tiled = |x: &af::Array| {
+                    let dims = x.dims();
+                    if dims[2] == batch[0] && dims[3] == batch[1] {
+                        x.clone()
+                    } else {
+                        af::tile(x, af::Dim4::new(&[1, 1, batch[0] / dims[2], batch[1] / dims[3]]))
+                    }
                 };
+                let mut result = af::matmul(&tiled(expr_map.get(&node.ancestors[0]).unwrap()),
+                                            &tiled(expr_map.get(&node.ancestors[1]).unwrap()),
+                                            af::MatProp::NONE, af::MatProp::NONE);
+                for a in node.ancestors.iter().skip(2) {
+                    result = af::matmul(&tiled(&result), &tiled(expr_map.get(a).unwrap()),
+                                        af::MatProp::NONE, af::MatProp::NONE);
+                }
                 expr_map.insert(node.id, result);
             },
             "Reorder" => {
@@ -134,8 +185,50 @@ impl AfFunction {
                 };
                 expr_map.insert(node.id, result);
             },
-            "Tanh" => {
-                let result = af::tanh(expr_map.get(&node.ancestors[0]).unwrap());
+            "Tanh" | "Exp" | "Log" | "Log1p" | "Expm1" | "Sqrt" | "Rsqrt" | "Square" |
+            "Sigmoid" | "Softplus" | "Relu" | "Abs" | "Sign" | "Sin" | "Cos" | "Tan" |
+            "Sinh" | "Cosh" | "Erf" => {
+                let result = {
+                    let parent = expr_map.get(&node.ancestors[0]).unwrap();
+                    match op_meta.name {
+                        "Tanh" => af::tanh(parent),
+                        "Exp" => af::exp(parent),
+                        "Log" => af::log(parent),
+                        "Log1p" => af::log1p(parent),
+                        "Expm1" => af::expm1(parent),
+                        "Sqrt" => af::sqrt(parent),
+                        "Rsqrt" => {
+                            let one = af::constant(1.0f32, af::Dim4::new(&[1, 1, 1, 1]));
+                            af::div(&one, &af::sqrt(parent), true)
+                        },
+                        "Square" => af::mul(parent, parent, false),
+                        "Sigmoid" => af::sigmoid(parent),
+                        "Softplus" => {
+                            // max(x, 0) + log(1 + exp(-|x|)) does not overflow for large x
+                            let zero = af::constant(0.0f32, af::Dim4::new(&[1, 1, 1, 1]));
+                            let positive = af::maxof(parent, &zero, true);
+                            af::add(&positive, &af::log1p(&af::exp(&af::abs(parent).neg())), false)
+                        },
+                        "Relu" => {
+                            let zero = af::constant(0.0f32, af::Dim4::new(&[1, 1, 1, 1]));
+                            af::maxof(parent, &zero, true)
+                        },
+                        "Abs" => af::abs(parent),
+                        "Sign" => {
+                            // af::sign only distinguishes negative values
+                            let zero = af::constant(0.0f32, af::Dim4::new(&[1, 1, 1, 1]));
+                            let positive = af::gt(parent, &zero, true).cast::<f32>();
+                            let negative = af::lt(parent, &zero, true).cast::<f32>();
+                            af::sub(&positive, &negative, false)
+                        },
+                        "Sin" => af::sin(parent),
+                        "Cos" => af::cos(parent),
+                        "Tan" => af::tan(parent),
+                        "Sinh" => af::sinh(parent),
+                        "Cosh" => af::cosh(parent),
+                        _ => af::erf(parent)
+                    }
+                };
                 expr_map.insert(node.id, result);
             },
             "Neg" => {
@@ -160,6 +253,145 @@ impl AfFunction {
                 }
                 expr_map.insert(node.id, result.unwrap());
             },
+            "Max" | "Min" | "Prod" | "Mean" | "Any" | "All" => {
+                let axes = *node.op.get_args().unwrap()
+                    .downcast::<[bool; 4]>().unwrap();
+                let result = {
+                    let parent = expr_map.get(&node.ancestors[0]).unwrap();
+                    match op_meta.name {
+                        "Max" => reduce_axes(parent, &axes, |x, i| af::max(x, i)),
+                        "Min" => reduce_axes(parent, &axes, |x, i| af::min(x, i)),
+                        "Prod" => reduce_axes(parent, &axes, |x, i| af::product(x, i)),
+                        "Mean" => reduce_axes(parent, &axes, |x, i| af::mean(x, i as i64)),
+                        "Any" => reduce_axes(parent, &axes, |x, i| af::any_true(x, i)),
+                        _ => reduce_axes(parent, &axes, |x, i| af::all_true(x, i))
+                    }
+                };
+                expr_map.insert(node.id, result);
+            },
+            "LogSumExp" | "Variance" => {
+                let axes = *node.op.get_args().unwrap()
+                    .downcast::<[bool; 4]>().unwrap();
+                let result = {
+                    let parent = expr_map.get(&node.ancestors[0]).unwrap();
+                    if op_meta.name == "LogSumExp" {
+                        let max = reduce_axes(parent, &axes, |x, i| af::max(x, i));
+                        let shifted = af::exp(&af::sub(parent, &max, true));
+                        let sum = reduce_axes(&shifted, &axes, |x, i| af::sum(x, i));
+                        af::add(&af::log(&sum), &max, false)
+                    } else {
+                        let mean = reduce_axes(parent, &axes, |x, i| af::mean(x, i as i64));
+                        let centered = af::sub(parent, &mean, true);
+                        let squares = af::mul(&centered, &centered, false);
+                        reduce_axes(&squares, &axes, |x, i| af::mean(x, i as i64))
+                    }
+                };
+                expr_map.insert(node.id, result);
+            },
+            "ArgMax" | "ArgMin" => {
+                let axes = *node.op.get_args().unwrap()
+                    .downcast::<[bool; 4]>().unwrap();
+                let result = {
+                    let parent = expr_map.get(&node.ancestors[0]).unwrap();
+                    // The reduced axes are moved to the front and flattened, so that
+                    // the index changes fastest along the first of them
+                    let dims = parent.dims();
+                    let reduced: Vec<u64> = (0..4).filter(|&i| axes[i as usize]).collect();
+                    let kept: Vec<u64> = (0..4).filter(|&i| !axes[i as usize]).collect();
+                    let mut flat = [1; 4];
+                    let mut result_dims = [1; 4];
+                    for &i in &reduced {
+                        flat[0] *= dims[i as usize];
+                    }
+                    for (k, &i) in kept.iter().take(3).enumerate() {
+                        flat[k + 1] = dims[i as usize];
+                        result_dims[i as usize] = dims[i as usize];
+                    }
+                    let order: Vec<u64> = reduced.iter().chain(kept.iter()).cloned().collect();
+                    if reduced.is_empty() {
+                        af::constant(0u32, dims)
+                    } else {
+                        let reordered = af::reorder(parent, af::Dim4::new(&[order[0], order[1],
+                            order[2], order[3]]));
+                        let flattened = af::moddims(&reordered, af::Dim4::new(&flat));
+                        let (_, index) = if op_meta.name == "ArgMax" {
+                            af::imax(&flattened, 0)
+                        } else {
+                            af::imin(&flattened, 0)
+                        };
+                        af::moddims(&index, af::Dim4::new(&result_dims))
+                    }
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Slice" | "SliceAdd" => {
+                let ranges = *node.op.get_args().unwrap()
+                    .downcast::<[Option<SliceRange>; 4]>().unwrap();
+                let seqs = slice_seqs(&ranges, &self.last_deduced)?;
+                let result = {
+                    let base = expr_map.get(&node.ancestors[0]).unwrap();
+                    match seqs {
+                        Some(ref seqs) if op_meta.name == "Slice" => af::index(base, seqs),
+                        Some(ref seqs) => {
+                            let update = expr_map.get(&node.ancestors[1]).unwrap();
+                            let sum = af::add(&af::index(base, seqs), update, false);
+                            af::assign_seq(base, seqs, &sum)
+                        },
+                        // ArrayFire sequences can not be empty, so the result is either
+                        // empty or the unchanged base
+                        None if op_meta.name == "Slice" => {
+                            let mut dims = [1u64; 4];
+                            for (i, d) in [&node.shape.0, &node.shape.1, &node.shape.2, &node.shape.3]
+                                .iter().enumerate() {
+                                dims[i] = ::std::cmp::max(eval_sym_int(d, &self.last_deduced)?, 0) as u64;
+                            }
+                            af::constant(0.0f32, af::Dim4::new(&dims))
+                        },
+                        None => base.clone()
+                    }
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Gather" => {
+                let axis = *node.op.get_args().unwrap()
+                    .downcast::<Axis>().unwrap();
+                let result = {
+                    let parent = expr_map.get(&node.ancestors[0]).unwrap();
+                    let indices = expr_map.get(&node.ancestors[1]).unwrap();
+                    // ArrayFire does not check the bounds of the indices
+                    host_indices(indices, parent.dims()[axis as usize])?;
+                    af::lookup(parent, &indices.cast::<u32>(), axis as i32)
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Scatter" | "ScatterAdd" => {
+                let axis = *node.op.get_args().unwrap()
+                    .downcast::<Axis>().unwrap();
+                let result = {
+                    let base = expr_map.get(&node.ancestors[0]).unwrap();
+                    let indices = expr_map.get(&node.ancestors[1]).unwrap();
+                    let updates = expr_map.get(&node.ancestors[2]).unwrap();
+                    let values = host_indices(indices, base.dims()[axis as usize])?;
+                    // ArrayFire has no scatter, as af::lookup only reads and assigning
+                    // with an index array keeps just one of repeated indices. Hence the
+                    // updates are written one subtensor at a time, such that repeated
+                    // indices accumulate for ScatterAdd
+                    let mut result = base.clone();
+                    for (i, &index) in values.iter().enumerate() {
+                        let mut source: [af::Seq<f64>; 4] = Default::default();
+                        let mut target: [af::Seq<f64>; 4] = Default::default();
+                        source[axis as usize] = af::Seq::new(i as f64, i as f64, 1.0);
+                        target[axis as usize] = af::Seq::new(index as f64, index as f64, 1.0);
+                        let mut value = af::index(updates, &source);
+                        if op_meta.name == "ScatterAdd" {
+                            value = af::add(&af::index(&result, &target), &value, false);
+                        }
+                        result = af::assign_seq(&result, &target, &value);
+                    }
+                    result
+                };
+                expr_map.insert(node.id, result);
+            },
             "TensorShape" => {
                 let axis = *node.op.get_args().unwrap()
                     .downcast::<Axis>().unwrap();
@@ -169,6 +401,62 @@ impl AfFunction {
                 };
                 expr_map.insert(node.id, result);
             },
+            "Pow" | "Maximum" | "Minimum" | "Atan2" => {
+                let result = {
+                    let arg0 = expr_map.get(&node.ancestors[0]).unwrap();
+                    let arg1 = expr_map.get(&node.ancestors[1]).unwrap();
+                    match op_meta.name {
+                        "Pow" => af::pow(arg0, arg1, true),
+                        "Maximum" => af::maxof(arg0, arg1, true),
+                        "Minimum" => af::minof(arg0, arg1, true),
+                        _ => af::atan2(arg0, arg1, true)
+                    }
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Gt" | "Ge" | "Lt" | "Le" | "Equal" | "Ne" | "And" | "Or" | "Xor" => {
+                let result = {
+                    let arg0 = expr_map.get(&node.ancestors[0]).unwrap();
+                    let arg1 = expr_map.get(&node.ancestors[1]).unwrap();
+                    match op_meta.name {
+                        "Gt" => af::gt(arg0, arg1, true),
+                        "Ge" => af::ge(arg0, arg1, true),
+                        "Lt" => af::lt(arg0, arg1, true),
+                        "Le" => af::le(arg0, arg1, true),
+                        "Equal" => af::eq(arg0, arg1, true),
+                        "Ne" | "Xor" => af::neq(arg0, arg1, true),
+                        "And" => af::and(arg0, arg1, true),
+                        _ => af::or(arg0, arg1, true)
+                    }
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Not" => {
+                let result = {
+                    let parent = expr_map.get(&node.ancestors[0]).unwrap();
+                    let zero = af::constant(0.0f32, af::Dim4::new(&[1, 1, 1, 1]));
+                    af::eq(parent, &zero, true)
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Select" => {
+                let result = {
+                    let cond = expr_map.get(&node.ancestors[0]).unwrap();
+                    let on_true = expr_map.get(&node.ancestors[1]).unwrap();
+                    let on_false = expr_map.get(&node.ancestors[2]).unwrap();
+                    af::select(on_true, cond, on_false)
+                };
+                expr_map.insert(node.id, result);
+            },
+            "Clamp" => {
+                let result = {
+                    let arg = expr_map.get(&node.ancestors[0]).unwrap();
+                    let low = expr_map.get(&node.ancestors[1]).unwrap();
+                    let high = expr_map.get(&node.ancestors[2]).unwrap();
+                    af::minof(&af::maxof(arg, low, true), high, true)
+                };
+                expr_map.insert(node.id, result);
+            },
             "Div" => {
                 let result = {
                     let parent = expr_map.get(&node.ancestors[0]).unwrap();
@@ -177,7 +465,7 @@ impl AfFunction {
                 };
                 expr_map.insert(node.id, result);
             },
-            "Broadcast" => {
+            "Broadcast" | "CustomGradient" | "Rematerialize" => {
                 let result = expr_map.get(&node.ancestors[0]).unwrap().clone();
                 expr_map.insert(node.id, result);
             },
@@ -200,9 +488,54 @@ impl AfFunction {
                 self.parameters.borrow_mut().insert(name, upd);
             },
             name => {
-                panic!("Operator {} not implemented.", name)
+                return Err(ErrorKind::Msg(format!("Operator {} not implemented \
+                in the ArrayFire backend.", name)).into())
             }
         }
 //        println!("{} - {:?}", id, expr_map.get(&id).map(|x| x.dims()));
+        Ok(())
     }
 }
+
+/// Applies the reduction `f` along each axis marked in `axes`
+fn reduce_axes<F: Fn(&af::Array, i32) -> af::Array>(x: &af::Array, axes: &[bool; 4], f: F) -> af::Array {
+    let mut result = x.clone();
+    for i in 0..4 {
+        if axes[i] {
+            result = f(&result, i as i32);
+        }
+    }
+    result
+}
+
+/// The arrayfire sequences for the slice ranges, which have an inclusive end,
+/// or `None` if any of the ranges is empty
+fn slice_seqs(ranges: &[Option<SliceRange>; 4], deduced: &HashMap<String, i64>)
+              -> Result<Option<[af::Seq<f64>; 4]>> {
+    let mut seqs: [af::Seq<f64>; 4] = Default::default();
+    for i in 0..4 {
+        if let Some(ref range) = ranges[i] {
+            let start = eval_sym_int(&range.start, deduced)?;
+            let stop = eval_sym_int(&range.stop, deduced)?;
+            if stop <= start {
+                return Ok(None)
+            }
+            seqs[i] = af::Seq::new(start as f64, (stop - 1) as f64, range.step as f64);
+        }
+    }
+    Ok(Some(seqs))
+}
+
+/// Copies the indices to the host, checking that they are within the bound
+fn host_indices(indices: &af::Array, bound: u64) -> Result<Vec<u32>> {
+    let mut values = vec![0.0f64; indices.elements() as usize];
+    indices.cast::<f64>().host(&mut values);
+    values.iter().map(|&v| {
+        if v < 0.0 || v as u64 >= bound {
+            Err(ErrorKind::Msg(format!("The index {} is out of bounds for an axis of \
+            size {}.", v, bound)).into())
+        } else {
+            Ok(v as u32)
+        }
+    }).collect()
+}
@@ -137,6 +137,68 @@ pub fn div<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr>
     wrapper.as_expr(result)
 }
 
+pub fn pow<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::pow(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn maximum<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::maximum(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn minimum<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::minimum(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn atan2<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::atan2(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn clamp<T1, T2, T3>(arg: T1, low: T2, high: T3) -> Result<Expr>
+    where T1: AsRef<Expr>, T2: AsRef<Expr>, T3: AsRef<Expr> {
+    let arg = arg.as_ref();
+    let low = low.as_ref();
+    let high = high.as_ref();
+    same_graph_3(arg, low, high)?;
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::clamp(g.deref_mut(), arg.id, low.id, high.id)?
+    };
+    wrapper.as_expr(result)
+}
+
 impl<T> Add<T> for Expr where T: AsRef<Expr> {
     type Output = Expr;
     fn add(self, rhs: T) -> Self::Output {
@@ -206,3 +268,28 @@ impl<'a, T> Div<T> for &'a Expr where T: AsRef<Expr> {
         self::div(self, rhs).unwrap()
     }
 }
+
+/// Method forms of the binary operators without a natural operator overload,
+/// named after their counterparts on `f64`. Like the overloads they panic on
+/// invalid arguments.
+impl Expr {
+    pub fn pow<T: AsRef<Expr>>(&self, exponent: T) -> Expr {
+        self::pow(self, exponent).unwrap()
+    }
+
+    pub fn max<T: AsRef<Expr>>(&self, other: T) -> Expr {
+        self::maximum(self, other).unwrap()
+    }
+
+    pub fn min<T: AsRef<Expr>>(&self, other: T) -> Expr {
+        self::minimum(self, other).unwrap()
+    }
+
+    pub fn atan2<T: AsRef<Expr>>(&self, other: T) -> Expr {
+        self::atan2(self, other).unwrap()
+    }
+
+    pub fn clamp<T1: AsRef<Expr>, T2: AsRef<Expr>>(&self, low: T1, high: T2) -> Expr {
+        self::clamp(self, low, high).unwrap()
+    }
+}
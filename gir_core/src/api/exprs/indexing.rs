@@ -0,0 +1,90 @@
+use primitives::*;
+use graph::*;
+use errors::*;
+use ops::SliceRange;
+use ops::interface::default::*;
+use super::super::ids;
+use std::ops::DerefMut;
+
+pub fn slice<T: AsRef<Expr>>(arg: T, ranges: [Option<SliceRange>; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::slice(g.deref_mut(), arg.id, ranges)?
+    };
+    wrapper.as_expr(result)
+}
+
+/// Slices only a single axis
+pub fn slice_axis<T, S1, S2>(arg: T, axis: Axis, start: S1, stop: S2, step: i64) -> Result<Expr>
+    where T: AsRef<Expr>, S1: Into<SymInt>, S2: Into<SymInt> {
+    let mut ranges = [None, None, None, None];
+    ranges[axis as usize] = Some(SliceRange::new(start.into(), stop.into(), step));
+    slice(arg, ranges)
+}
+
+pub fn slice_add<T1, T2>(base: T1, update: T2, ranges: [Option<SliceRange>; 4]) -> Result<Expr>
+    where T1: AsRef<Expr>, T2: AsRef<Expr> {
+    let base = base.as_ref();
+    let update = update.as_ref();
+    same_graph_2(base, update)?;
+    let ref wrapper = base.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::slice_add(g.deref_mut(), base.id, update.id, ranges)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn gather<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg: T1, indices: T2, axis: Axis) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let indices = indices.as_ref();
+    same_graph_2(arg, indices)?;
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::gather(g.deref_mut(), arg.id, indices.id, axis)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn index<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg: T1, index: T2, axis: Axis) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let index = index.as_ref();
+    same_graph_2(arg, index)?;
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::index(g.deref_mut(), arg.id, index.id, axis)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn scatter<T1, T2, T3>(base: T1, indices: T2, updates: T3, axis: Axis) -> Result<Expr>
+    where T1: AsRef<Expr>, T2: AsRef<Expr>, T3: AsRef<Expr> {
+    let base = base.as_ref();
+    let indices = indices.as_ref();
+    let updates = updates.as_ref();
+    same_graph_3(base, indices, updates)?;
+    let ref wrapper = base.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::scatter(g.deref_mut(), base.id, indices.id, updates.id, axis)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn scatter_add<T1, T2, T3>(base: T1, indices: T2, updates: T3, axis: Axis) -> Result<Expr>
+    where T1: AsRef<Expr>, T2: AsRef<Expr>, T3: AsRef<Expr> {
+    let base = base.as_ref();
+    let indices = indices.as_ref();
+    let updates = updates.as_ref();
+    same_graph_3(base, indices, updates)?;
+    let ref wrapper = base.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::scatter_add(g.deref_mut(), base.id, indices.id, updates.id, axis)?
+    };
+    wrapper.as_expr(result)
+}
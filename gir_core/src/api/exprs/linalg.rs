@@ -18,4 +18,37 @@ pub fn mat_mul<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<E
     wrapper.as_expr(result)
 }
 
+pub fn mat_mul_n<T: AsRef<Expr>>(args: &Vec<T>) -> Result<Expr> {
+    same_graph(args)?;
+    let ref wrapper = args[0].as_ref().wrapper;
+    let args = args.iter().map(|x| x.as_ref().id).collect();
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::mat_mul_n(g.deref_mut(), args)?
+    };
+    wrapper.as_expr(result)
+}
+
+/// Multiplies a chain of matrices in the order with the least number of operations
+pub fn mat_mul_chain<T: AsRef<Expr>>(args: &Vec<T>) -> Result<Expr> {
+    same_graph(args)?;
+    let ref wrapper = args[0].as_ref().wrapper;
+    let args = args.iter().map(|x| x.as_ref().id).collect();
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::mat_mul_chain(g.deref_mut(), args)?
+    };
+    wrapper.as_expr(result)
+}
 
+/// Contracts the tensors according to the subscript specification, e.g. `ij,jk->ik`
+pub fn einsum<T: AsRef<Expr>>(spec: &str, args: &Vec<T>) -> Result<Expr> {
+    same_graph(args)?;
+    let ref wrapper = args[0].as_ref().wrapper;
+    let args = args.iter().map(|x| x.as_ref().id).collect();
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::einsum(g.deref_mut(), spec, args)?
+    };
+    wrapper.as_expr(result)
+}
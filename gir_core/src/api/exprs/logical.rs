@@ -0,0 +1,194 @@
+use graph::*;
+use errors::*;
+use ops::interface::default::*;
+use super::super::ids;
+use std::ops::{BitAnd, BitOr, BitXor, DerefMut};
+use std::convert::AsRef;
+
+pub fn gt<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::gt(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn ge<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::ge(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn lt<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::lt(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn le<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::le(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn eq<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::eq(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn ne<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::ne(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn and<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::and(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn or<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::or(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn xor<T1: AsRef<Expr>, T2: AsRef<Expr>>(arg0: T1, arg1: T2) -> Result<Expr> {
+    let arg0 = arg0.as_ref();
+    let arg1 = arg1.as_ref();
+    same_graph_2(arg0, arg1)?;
+    let ref wrapper = arg0.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::xor(g.deref_mut(), arg0.id, arg1.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn not<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::not(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn select<T1, T2, T3>(cond: T1, on_true: T2, on_false: T3) -> Result<Expr>
+    where T1: AsRef<Expr>, T2: AsRef<Expr>, T3: AsRef<Expr> {
+    let cond = cond.as_ref();
+    let on_true = on_true.as_ref();
+    let on_false = on_false.as_ref();
+    same_graph_3(cond, on_true, on_false)?;
+    let ref wrapper = cond.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::select(g.deref_mut(), cond.id, on_true.id, on_false.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+impl<T> BitAnd<T> for Expr where T: AsRef<Expr> {
+    type Output = Expr;
+    fn bitand(self, rhs: T) -> Self::Output {
+        self::and(self, rhs).unwrap()
+    }
+}
+
+impl<'a, T> BitAnd<T> for &'a Expr where T: AsRef<Expr> {
+    type Output = Expr;
+    fn bitand(self, rhs: T) -> Self::Output {
+        self::and(self, rhs).unwrap()
+    }
+}
+
+impl<T> BitOr<T> for Expr where T: AsRef<Expr> {
+    type Output = Expr;
+    fn bitor(self, rhs: T) -> Self::Output {
+        self::or(self, rhs).unwrap()
+    }
+}
+
+impl<'a, T> BitOr<T> for &'a Expr where T: AsRef<Expr> {
+    type Output = Expr;
+    fn bitor(self, rhs: T) -> Self::Output {
+        self::or(self, rhs).unwrap()
+    }
+}
+
+impl<T> BitXor<T> for Expr where T: AsRef<Expr> {
+    type Output = Expr;
+    fn bitxor(self, rhs: T) -> Self::Output {
+        self::xor(self, rhs).unwrap()
+    }
+}
+
+impl<'a, T> BitXor<T> for &'a Expr where T: AsRef<Expr> {
+    type Output = Expr;
+    fn bitxor(self, rhs: T) -> Self::Output {
+        self::xor(self, rhs).unwrap()
+    }
+}
+
+impl ::std::ops::Not for Expr {
+    type Output = Expr;
+    fn not(self) -> Self::Output {
+        self::not(self).unwrap()
+    }
+}
+
+impl<'a> ::std::ops::Not for &'a Expr {
+    type Output = Expr;
+    fn not(self) -> Self::Output {
+        self::not(self).unwrap()
+    }
+}
@@ -3,11 +3,15 @@ pub mod special;
 pub mod shape;
 pub mod linalg;
 pub mod nonl;
+pub mod logical;
 pub mod reduction;
+pub mod indexing;
 
 pub use self::arithmetic::*;
 pub use self::special::*;
 pub use self::shape::*;
 pub use self::linalg::*;
 pub use self::nonl::*;
-pub use self::reduction::*;
\ No newline at end of file
+pub use self::logical::*;
+pub use self::reduction::*;
+pub use self::indexing::*;
\ No newline at end of file
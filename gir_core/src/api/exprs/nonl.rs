@@ -14,4 +14,182 @@ pub fn tanh<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
     wrapper.as_expr(result)
 }
 
+pub fn exp<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::exp(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn log<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::log(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn log1p<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::log1p(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn expm1<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::expm1(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn sqrt<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::sqrt(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn rsqrt<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::rsqrt(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn square<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::square(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn sigmoid<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::sigmoid(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn softplus<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::softplus(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
 
+pub fn relu<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::relu(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn abs<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::abs(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn sign<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::sign(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn sin<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::sin(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn cos<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::cos(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn tan<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::tan(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn sinh<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::sinh(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn cosh<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::cosh(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn erf<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::erf(g.deref_mut(), arg.id)?
+    };
+    wrapper.as_expr(result)
+}
@@ -37,3 +37,222 @@ pub fn sum_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
     wrapper.as_expr(result)
 }
 
+pub fn max<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    max_axes(arg, axes)
+}
+
+pub fn max_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    max_axes(arg, [true; 4])
+}
+
+pub fn max_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::max(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn min<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    min_axes(arg, axes)
+}
+
+pub fn min_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    min_axes(arg, [true; 4])
+}
+
+pub fn min_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::min(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn prod<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    prod_axes(arg, axes)
+}
+
+pub fn prod_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    prod_axes(arg, [true; 4])
+}
+
+pub fn prod_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::prod(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn mean<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    mean_axes(arg, axes)
+}
+
+pub fn mean_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    mean_axes(arg, [true; 4])
+}
+
+pub fn mean_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::mean(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn log_sum_exp<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    log_sum_exp_axes(arg, axes)
+}
+
+pub fn log_sum_exp_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    log_sum_exp_axes(arg, [true; 4])
+}
+
+pub fn log_sum_exp_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::log_sum_exp(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn variance<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    variance_axes(arg, axes)
+}
+
+pub fn variance_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    variance_axes(arg, [true; 4])
+}
+
+pub fn variance_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::variance(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn std<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    std_axes(arg, axes)
+}
+
+pub fn std_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    std_axes(arg, [true; 4])
+}
+
+pub fn std_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::std(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn arg_max<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    arg_max_axes(arg, axes)
+}
+
+pub fn arg_max_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    arg_max_axes(arg, [true; 4])
+}
+
+pub fn arg_max_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::arg_max(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn arg_min<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    arg_min_axes(arg, axes)
+}
+
+pub fn arg_min_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    arg_min_axes(arg, [true; 4])
+}
+
+pub fn arg_min_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::arg_min(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn any<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    any_axes(arg, axes)
+}
+
+pub fn any_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    any_axes(arg, [true; 4])
+}
+
+pub fn any_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::any(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
+
+pub fn all<T: AsRef<Expr>>(arg: T, axis: Axis) -> Result<Expr> {
+    let mut axes = [false; 4];
+    axes[axis as usize] = true;
+    all_axes(arg, axes)
+}
+
+pub fn all_all<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
+    all_axes(arg, [true; 4])
+}
+
+pub fn all_axes<T: AsRef<Expr>>(arg: T, axes: [bool; 4]) -> Result<Expr> {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::all(g.deref_mut(), arg.id, axes)?
+    };
+    wrapper.as_expr(result)
+}
@@ -4,6 +4,7 @@ use errors::*;
 use ops::interface::default::*;
 use super::super::ids;
 use std::ops::DerefMut;
+use std::rc::Rc;
 
 pub fn overwrite_update<T: AsRef<Expr>>(arg: T, upd: T) -> Result<bool> {
     let arg = arg.as_ref();
@@ -73,6 +74,30 @@ pub fn make_constant<T: AsRef<Expr>>(arg: T) -> Result<Expr> {
     wrapper.as_expr(result)
 }
 
+/// Wraps the expression, such that its reverse mode derivative is given by the rule.
+///
+/// The rule receives the graph, the id of the wrapped expression and the id of the
+/// derivative with respect to it. It must return pairs of expressions and their
+/// derivatives, where each expression is the wrapped one or one of its ancestors.
+pub fn custom_gradient<T: AsRef<Expr>, F>(arg: T, rule: F) -> Result<Expr>
+    where F: Fn(&mut Graph, usize, usize) -> Result<Vec<(usize, usize)>> + 'static {
+    let arg = arg.as_ref();
+    let ref wrapper = arg.wrapper;
+    let result = {
+        let mut g = wrapper.get_mut();
+        ids::custom_gradient(g.deref_mut(), arg.id, Rc::new(rule))?
+    };
+    wrapper.as_expr(result)
+}
+
+/// Marks the expression to be recomputed during the backward pass of `reverse_diff`,
+/// rather than being kept from the forward pass. To checkpoint whole scopes use
+/// the `checkpoint_scopes` of the graph properties instead.
+pub fn checkpoint<T: AsRef<Expr>>(arg: T) -> Result<()> {
+    let arg = arg.as_ref();
+    ids::checkpoint(arg.wrapper.get_mut().deref_mut(), arg.id)
+}
+
 pub fn reorder<T: AsRef<Expr>>(arg: T, order: [Axis; 4]) -> Result<Expr> {
     let arg = arg.as_ref();
     let ref wrapper = arg.wrapper;
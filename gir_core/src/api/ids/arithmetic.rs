@@ -30,5 +30,22 @@ pub fn div(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
 
 }
 
+pub fn pow(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Pow {}), vec![arg0, arg1])?)
+}
+
+pub fn maximum(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Maximum {}), vec![arg0, arg1])?)
+}
 
+pub fn minimum(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Minimum {}), vec![arg0, arg1])?)
+}
 
+pub fn atan2(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Atan2 {}), vec![arg0, arg1])?)
+}
+
+pub fn clamp(graph: &mut Graph, arg: usize, low: usize, high: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Clamp {}), vec![arg, low, high])?)
+}
@@ -0,0 +1,33 @@
+use primitives::*;
+use ops::*;
+use graph::*;
+use errors::*;
+
+pub fn slice(graph: &mut Graph, arg: usize, ranges: [Option<SliceRange>; 4]) -> Result<usize> {
+    graph.apply_op(Box::new(Slice {ranges: ranges}), vec![arg])
+}
+
+pub fn slice_add(graph: &mut Graph, base: usize, update: usize, ranges: [Option<SliceRange>; 4])
+                 -> Result<usize> {
+    graph.apply_op(Box::new(SliceAdd {ranges: ranges}), vec![base, update])
+}
+
+pub fn gather(graph: &mut Graph, arg: usize, indices: usize, axis: Axis) -> Result<usize> {
+    graph.apply_op(Box::new(Gather {axis: axis}), vec![arg, indices])
+}
+
+/// Takes the subtensor at a single integer index along the axis, which is kept
+/// with unit size.
+pub fn index(graph: &mut Graph, arg: usize, index: usize, axis: Axis) -> Result<usize> {
+    gather(graph, arg, index, axis)
+}
+
+pub fn scatter(graph: &mut Graph, base: usize, indices: usize, updates: usize, axis: Axis)
+               -> Result<usize> {
+    graph.apply_op(Box::new(Scatter {axis: axis}), vec![base, indices, updates])
+}
+
+pub fn scatter_add(graph: &mut Graph, base: usize, indices: usize, updates: usize, axis: Axis)
+                   -> Result<usize> {
+    graph.apply_op(Box::new(ScatterAdd {axis: axis}), vec![base, indices, updates])
+}
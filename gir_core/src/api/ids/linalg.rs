@@ -5,3 +5,39 @@ use errors::*;
 pub fn mat_mul(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
     Ok(graph.apply_op(Box::new(MatMul {}), vec![arg0, arg1])?)
 }
+
+pub fn mat_mul_n(graph: &mut Graph, args: Vec<usize>) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(MatMul {}), args)?)
+}
+
+pub fn einsum(graph: &mut Graph, spec: &str, args: Vec<usize>) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Einsum::parse(spec)?), args)?)
+}
+
+/// Multiplies a chain of matrices in the order with the least number of operations,
+/// using the size hints of the graph for any symbolic dimensions
+pub fn mat_mul_chain(graph: &mut Graph, args: Vec<usize>) -> Result<usize> {
+    if args.len() < 2 {
+        return Err(ErrorKind::InvalidArguments("MatMul".into(), args.clone(),
+            format!("Expecting at least 2 arguments, got {}.", args.len())).into())
+    }
+    let mut dims = Vec::with_capacity(args.len() + 1);
+    for &a in &args {
+        dims.push(graph.get_node(a)?.shape.0.clone());
+    }
+    dims.push(graph.get_node(*args.last().unwrap())?.shape.1.clone());
+    let splits = matrix_chain_order(&dims, &graph.props.size_hints);
+    mat_mul_split(graph, &args, &splits, 0, args.len() - 1)
+}
+
+fn mat_mul_split(graph: &mut Graph, args: &[usize], splits: &[Vec<usize>], i: usize, j: usize)
+                 -> Result<usize> {
+    if i == j {
+        Ok(args[i])
+    } else {
+        let k = splits[i][j];
+        let left = mat_mul_split(graph, args, splits, i, k)?;
+        let right = mat_mul_split(graph, args, splits, k + 1, j)?;
+        mat_mul(graph, left, right)
+    }
+}
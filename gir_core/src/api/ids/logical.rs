@@ -0,0 +1,47 @@
+use ops::*;
+use graph::*;
+use errors::*;
+
+pub fn gt(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Gt {}), vec![arg0, arg1])?)
+}
+
+pub fn ge(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Ge {}), vec![arg0, arg1])?)
+}
+
+pub fn lt(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Lt {}), vec![arg0, arg1])?)
+}
+
+pub fn le(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Le {}), vec![arg0, arg1])?)
+}
+
+pub fn eq(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Equal {}), vec![arg0, arg1])?)
+}
+
+pub fn ne(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Ne {}), vec![arg0, arg1])?)
+}
+
+pub fn and(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(And {}), vec![arg0, arg1])?)
+}
+
+pub fn or(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Or {}), vec![arg0, arg1])?)
+}
+
+pub fn xor(graph: &mut Graph, arg0: usize, arg1: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Xor {}), vec![arg0, arg1])?)
+}
+
+pub fn not(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Not {}), vec![arg])?)
+}
+
+pub fn select(graph: &mut Graph, cond: usize, on_true: usize, on_false: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Select {}), vec![cond, on_true, on_false])?)
+}
@@ -4,4 +4,76 @@ use errors::*;
 
 pub fn tanh(graph: &mut Graph, arg: usize) -> Result<usize> {
     Ok(graph.apply_op(Box::new(Tanh {}), vec![arg])?)
-}
\ No newline at end of file
+}
+
+pub fn exp(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Exp {}), vec![arg])?)
+}
+
+pub fn log(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Log {}), vec![arg])?)
+}
+
+pub fn log1p(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Log1p {}), vec![arg])?)
+}
+
+pub fn expm1(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Expm1 {}), vec![arg])?)
+}
+
+pub fn sqrt(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Sqrt {}), vec![arg])?)
+}
+
+pub fn rsqrt(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Rsqrt {}), vec![arg])?)
+}
+
+pub fn square(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Square {}), vec![arg])?)
+}
+
+pub fn sigmoid(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Sigmoid {}), vec![arg])?)
+}
+
+pub fn softplus(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Softplus {}), vec![arg])?)
+}
+
+pub fn relu(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Relu {}), vec![arg])?)
+}
+
+pub fn abs(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Abs {}), vec![arg])?)
+}
+
+pub fn sign(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Sign {}), vec![arg])?)
+}
+
+pub fn sin(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Sin {}), vec![arg])?)
+}
+
+pub fn cos(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Cos {}), vec![arg])?)
+}
+
+pub fn tan(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Tan {}), vec![arg])?)
+}
+
+pub fn sinh(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Sinh {}), vec![arg])?)
+}
+
+pub fn cosh(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Cosh {}), vec![arg])?)
+}
+
+pub fn erf(graph: &mut Graph, arg: usize) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(Erf {}), vec![arg])?)
+}
@@ -2,13 +2,92 @@ use primitives::*;
 use ops::*;
 use graph::*;
 use errors::*;
+use api::ids;
 
-pub fn sum(graph: &mut Graph, arg: usize, mut axes: [bool; 4]) -> Result<usize> {
-    // Eliminate any unit shapes
+/// Removes any axes of unit size from the reduction
+fn eliminate_unit_axes(graph: &Graph, arg: usize, mut axes: [bool; 4]) -> Result<[bool; 4]> {
     for &axis in Axis::iter() {
         if axes[axis as usize] && *graph.get_node(arg)?.shape.get(axis) == 1 {
             axes[axis as usize] = false;
         }
     }
+    Ok(axes)
+}
+
+/// Zeros of the given type with the shape of the argument, for reductions whose
+/// value over only unit axes is not the argument itself
+fn zeros_like(graph: &mut Graph, arg: usize, data_type: FundamentalType) -> Result<usize> {
+    let zero = graph.scalar(0.0, data_type);
+    ids::broadcast_to(graph, zero, arg)
+}
+
+pub fn sum(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
     graph.apply_op(Box::new(Sum {axes: axes}), vec![arg])
 }
+
+pub fn max(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(Max {axes: axes}), vec![arg])
+}
+
+pub fn min(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(Min {axes: axes}), vec![arg])
+}
+
+pub fn prod(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(Prod {axes: axes}), vec![arg])
+}
+
+pub fn mean(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(Mean {axes: axes}), vec![arg])
+}
+
+pub fn log_sum_exp(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(LogSumExp {axes: axes}), vec![arg])
+}
+
+pub fn variance(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    if axes == [false; 4] {
+        let data_type = graph.get_node(arg)?.data_type;
+        return zeros_like(graph, arg, data_type)
+    }
+    graph.apply_op(Box::new(Variance {axes: axes}), vec![arg])
+}
+
+/// The population standard deviation, as the square root of the `Variance`
+pub fn std(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let variance = variance(graph, arg, axes)?;
+    ids::sqrt(graph, variance)
+}
+
+pub fn arg_max(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    if axes == [false; 4] {
+        return zeros_like(graph, arg, FundamentalType::UnsignedInt)
+    }
+    graph.apply_op(Box::new(ArgMax {axes: axes}), vec![arg])
+}
+
+pub fn arg_min(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    if axes == [false; 4] {
+        return zeros_like(graph, arg, FundamentalType::UnsignedInt)
+    }
+    graph.apply_op(Box::new(ArgMin {axes: axes}), vec![arg])
+}
+
+pub fn any(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(Any {axes: axes}), vec![arg])
+}
+
+pub fn all(graph: &mut Graph, arg: usize, axes: [bool; 4]) -> Result<usize> {
+    let axes = eliminate_unit_axes(graph, arg, axes)?;
+    graph.apply_op(Box::new(All {axes: axes}), vec![arg])
+}
@@ -57,18 +57,24 @@ pub fn update(graph: &mut Graph, arg:usize, upd: usize) -> Result<usize> {
 }
 
 pub fn remove_update(graph: &mut Graph, arg:usize) -> Result<bool> {
-    let updates = graph.op_map.get("Update").unwrap();
-    for &u in updates {
-        if graph.nodes[u].ancestors[0] == arg {
-            let upd = graph.nodes[u].ancestors[1];
-            graph.nodes[arg].children.remove(&u);
-            graph.nodes[upd].children.remove(&u);
-            let op = Box::new(Cleared{});
-            graph.nodes[u] = op.apply_null();
-            return Ok(true)
-        }
+    let position = graph.op_map.get("Update").unwrap().iter()
+        .position(|&u| graph.nodes[u].ancestors[0] == arg);
+    if let Some(position) = position {
+        let u = graph.op_map.get_mut("Update").unwrap().remove(position);
+        let upd = graph.nodes[u].ancestors[1];
+        graph.nodes[arg].children.remove(&u);
+        graph.nodes[upd].children.remove(&u);
+        // The cleared node keeps its place in the graph
+        let op = Box::new(Cleared{});
+        let mut data = op.apply_null();
+        data.id = u;
+        data.scope = graph.nodes[u].scope.clone();
+        graph.nodes[u] = data;
+        graph.op_map.entry("Cleared".into()).or_insert_with(Vec::new).push(u);
+        Ok(true)
+    } else {
+        Ok(false)
     }
-    Ok(false)
 }
 
 pub fn cast(graph: &mut Graph, arg: usize, data_type: FundamentalType) -> Result<usize> {
@@ -113,6 +119,19 @@ pub fn make_constant(graph: &mut Graph, arg: usize) -> Result<usize> {
     Ok(graph.apply_op(Box::new(MakeConstant {}), vec![arg])?)
 }
 
+/// Wraps the expression, such that its reverse mode derivative is given by the rule
+pub fn custom_gradient(graph: &mut Graph, arg: usize, rule: GradientRule) -> Result<usize> {
+    Ok(graph.apply_op(Box::new(CustomGradient {rule: rule}), vec![arg])?)
+}
+
+/// Marks the expression to be recomputed during the backward pass of `reverse_diff`,
+/// rather than being kept from the forward pass
+pub fn checkpoint(graph: &mut Graph, arg: usize) -> Result<()> {
+    graph.get_node(arg)?;
+    graph.checkpoints.insert(arg);
+    Ok(())
+}
+
 /// Reverses the axes if order is None (e.g. transpose)
 pub fn reorder(graph: &mut Graph, arg: usize, order: Option<[Axis; 4]>) -> Result<usize> {
     match order {
@@ -1,6 +1,7 @@
 use graph::*;
 use primitives::*;
 use errors::*;
+use passes::{OptimizationLevel, optimize};
 
 use std::io;
 use std::collections::HashMap;
@@ -52,6 +53,14 @@ pub trait Backend<F>: Default {
     fn set_precisions(&mut self, precisions: BackendPrecisions);
 
     fn make_function(&self, graph_function: GraphFunction) -> F;
+
+    /// Runs the default optimization pipeline of the level before making the function
+    fn make_optimized_function(&self, graph_function: GraphFunction, level: OptimizationLevel)
+                               -> Result<F> {
+        let mut graph_function = graph_function;
+        optimize(&mut graph_function, level)?;
+        Ok(self.make_function(graph_function))
+    }
 }
 
 pub fn verify_shapes(new_shapes: &[[usize; 4]],
@@ -86,39 +95,156 @@ pub fn verify_shapes(new_shapes: &[[usize; 4]],
     }
 }
 
+/// Whether the value of the expression is placed in the memory map. Scalars, shapes
+/// and broadcasts are computed on the fly, while parameters and their updates are
+/// kept by the backend.
+pub fn occupies_memory(node: &ExprData) -> bool {
+    match node.op.get_meta().name {
+        "Scalar" | "SymIntInput" | "TensorShape" | "Broadcast" | "Parameter" | "Update" => false,
+        _ => true
+    }
+}
+
+/// Returns for every expression occupying memory the first and the last position in
+/// the order of the graph, during which its value must be kept. The inputs are kept
+/// from the start and the outputs until the end of the function. Since broadcasts
+/// are computed on the fly, their arguments are kept until the broadcast is last used.
+pub fn live_ranges(gf: &GraphFunction) -> Vec<Option<(usize, usize)>> {
+    let ref graph = gf.graph;
+    let last = graph.order.len().saturating_sub(1);
+    let mut position = vec![0; graph.nodes.len()];
+    for (i, &id) in graph.order.iter().enumerate() {
+        position[id] = i;
+    }
+    // The last position at which the value of each expression is read
+    let mut last_use = vec![0; graph.nodes.len()];
+    for (i, &id) in graph.order.iter().enumerate().rev() {
+        let ref node = graph.nodes[id];
+        last_use[id] = if gf.outputs.contains(&id) {
+            last
+        } else {
+            node.children.iter().map(|&c| if graph.nodes[c].op.get_meta().name == "Broadcast" {
+                last_use[c]
+            } else {
+                position[c]
+            }).fold(i, ::std::cmp::max)
+        };
+    }
+    let mut ranges = vec![None; graph.nodes.len()];
+    for (i, &id) in graph.order.iter().enumerate() {
+        if !occupies_memory(&graph.nodes[id]) {
+            continue
+        }
+        let start = if gf.inputs.contains(&id) { 0 } else { i };
+        ranges[id] = Some((start, last_use[id]));
+    }
+    ranges
+}
+
+/// Whether `a <= b` for all positive values of the symbolic variables. This holds
+/// when all coefficients of the difference `b - a` are non negative.
+pub fn symbolic_le(a: &SymInt, b: &SymInt) -> bool {
+    let mut difference = b.clone();
+    difference -= a;
+    difference.monomials.iter().all(|m| m.coefficient >= 0)
+}
+
+/// A contiguous region of the memory map shared by expressions with disjoint lifetimes
+struct Region {
+    offset: SymInt,
+    size: SymInt,
+    data_type: FundamentalType,
+    // The expression currently holding the region and its last use in the order
+    occupant: usize,
+    free_after: usize
+}
+
+/// Builds the memory map of the function, by assigning every expression occupying
+/// memory a region, which can be reused by any later expression of the same data type
+/// after the last use of the current value, as long as it is known to be large enough
+/// for every value of the symbolic variables. Elementwise operators write their result
+/// in place of an argument which is not used afterwards. The size of the map for each
+/// data type is the total size of all regions of that type, which are laid out one
+/// after another.
 pub fn build_memory_map(gf: &GraphFunction) -> AbstractMemoryMap {
-    let mut map = HashMap::new();
+    let ranges = live_ranges(gf);
+    let mut nodes: Vec<usize> = gf.graph.order.iter().cloned()
+        .filter(|&i| ranges[i].is_some()).collect();
+    // The inputs are present from the start
+    nodes.sort_by_key(|&i| ranges[i].unwrap().0);
+    let mut regions: Vec<Region> = Vec::new();
+    let mut assigned = HashMap::new();
     let mut offset: SymInt = 0.into();
+    for i in nodes {
+        let ref node = gf.graph.nodes[i];
+        let (start, end) = ranges[i].unwrap();
+        let n = node.shape.elements();
+        let fits = |region: &Region, free: bool| free && region.data_type == node.data_type &&
+            symbolic_le(&n, &region.size);
+        // In place of an argument of the same size, whose last use is this expression
+        let in_place = if node.op.get_meta().elementwise && !gf.inputs.contains(&i) {
+            node.ancestors.iter()
+                .filter(|&&a| symbolic_le(&gf.graph.nodes[a].shape.elements(), &n))
+                .filter_map(|&a| assigned.get(&a).map(|&r| (a, r)))
+                .find(|&(a, r): &(usize, usize)| fits(&regions[r],
+                    regions[r].occupant == a && regions[r].free_after == start))
+                .map(|(_, r)| r)
+        } else {
+            None
+        };
+        // Otherwise in the smallest free region large enough, preferring exact fits
+        let free = in_place.or_else(|| {
+            let candidates: Vec<usize> = (0..regions.len())
+                .filter(|&r| fits(&regions[r], regions[r].free_after < start))
+                .collect();
+            candidates.iter().cloned().find(|&r| symbolic_le(&regions[r].size, &n))
+                .or_else(|| candidates.iter().cloned()
+                    .fold(None, |best: Option<usize>, r| match best {
+                        Some(b) if symbolic_le(&regions[b].size, &regions[r].size) => Some(b),
+                        _ => Some(r)
+                    }))
+        });
+        let r = match free {
+            Some(r) => r,
+            None => {
+                regions.push(Region {
+                    offset: offset.clone(),
+                    size: n.clone(),
+                    data_type: node.data_type,
+                    occupant: i,
+                    free_after: end
+                });
+                offset += &n;
+                regions.len() - 1
+            }
+        };
+        regions[r].occupant = i;
+        regions[r].free_after = end;
+        assigned.insert(i, r);
+    }
     let mut b_size: SymInt = 0.into();
     let mut i_size: SymInt = 0.into();
     let mut f_size: SymInt = 0.into();
     let mut c_size: SymInt = 0.into();
-    for &i in &gf.graph.order {
-        let ref node = gf.graph.nodes[i];
-        let op_meta = node.op.get_meta();
-        match op_meta.name {
-            "Scalar" | "SymIntInput" | "TensorShape" | "Broadcast" | "Parameter" => {},
-            _ => {
-                let n = gf.graph.nodes[i].shape.elements();
-                map.insert(i, (offset.clone(), n.clone()));
-                match gf.graph.nodes[i].data_type {
-                    FundamentalType::Boolean => {
-                        b_size += &n;
-                    },
-                    FundamentalType::SignedInt | FundamentalType::UnsignedInt => {
-                        i_size += &n;
-                    },
-                    FundamentalType::Float => {
-                        f_size += &n;
-                    },
-                    FundamentalType::Complex => {
-                        c_size += &n;
-                    }
-                }
-                offset += &n;
+    for region in &regions {
+        match region.data_type {
+            FundamentalType::Boolean => {
+                b_size += &region.size;
+            },
+            FundamentalType::SignedInt | FundamentalType::UnsignedInt => {
+                i_size += &region.size;
+            },
+            FundamentalType::Float => {
+                f_size += &region.size;
+            },
+            FundamentalType::Complex => {
+                c_size += &region.size;
             }
         }
     }
+    let map = assigned.into_iter()
+        .map(|(i, r)| (i, (regions[r].offset.clone(), gf.graph.nodes[i].shape.elements())))
+        .collect();
     AbstractMemoryMap {
         abstract_map: map,
         abstract_size: (b_size, i_size, f_size, c_size),
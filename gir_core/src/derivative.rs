@@ -3,6 +3,7 @@ use primitives::*;
 use errors::*;
 use graph::*;
 use ops::interface::default::*;
+use ops::Rematerialize;
 use api;
 
 use std::collections::HashMap;
@@ -36,6 +37,175 @@ pub fn gradient<T1: AsRef<Expr>, T2: AsRef<Expr>>(f: T1, x: &Vec<T2>) -> Result<
     }
 }
 
+/// Calculates the product of the Hessian of the expression **f** with respect to
+/// the expressions in **x** and the vectors **v**, given for each of them.
+///
+/// The product is computed in forward-over-reverse mode - the gradient is built
+/// using reverse mode and then differentiated in forward mode along **v**.
+pub fn hessian_vector_product<T1: AsRef<Expr>, T2: AsRef<Expr>, T3: AsRef<Expr>>(
+    f: T1, x: &Vec<T2>, v: &Vec<T3>) -> Result<Vec<Expr>> {
+    let f = f.as_ref();
+    let ref wrapper = f.wrapper;
+    let x: Vec<&Expr> = x.iter().map(|e| e.as_ref()).collect();
+    let v: Vec<&Expr> = v.iter().map(|e| e.as_ref()).collect();
+    let grads = gradient(f, &x)?;
+    let combined: Vec<&Expr> = grads.iter().chain(x.iter().cloned()).chain(v.iter().cloned()).collect();
+    same_graph(&combined)?;
+    let grads = grads.iter().map(|e| e.id).collect();
+    let x = x.iter().map(|e| e.id).collect();
+    let v = v.iter().map(|e| e.id).collect();
+    let result = forward_diff(wrapper.get_mut().deref_mut(), &grads, &x, &v)?;
+    Ok(result.into_iter().map(|x| wrapper.as_expr(x).unwrap()).collect())
+}
+
+/// Calculates the full Hessian of the expression **f** with respect to the
+/// expressions in **x**, such that the result at `[i][j]` is the derivative
+/// of **f** with respect to **x[i]** and **x[j]**.
+///
+/// Every column requires a separate forward sweep, thus this is intended only for
+/// a small number of parameters. All of them must be scalars, for tensors use
+/// `hessian_vector_product` instead.
+pub fn hessian<T1: AsRef<Expr>, T2: AsRef<Expr>>(f: T1, x: &Vec<T2>) -> Result<Vec<Vec<Expr>>> {
+    let f = f.as_ref();
+    let ref wrapper = f.wrapper;
+    let x: Vec<&Expr> = x.iter().map(|e| e.as_ref()).collect();
+    for (i, xi) in x.iter().enumerate() {
+        if xi.get()?.shape.order() != 0 {
+            let err = ErrorKind::Msg(format!("Requested full hessian with respect to \
+            a non scalar expression at index {}.", i)).into();
+            error!(wrapper.get().log, format!("[derivative] {}", err));
+            return Err(err)
+        }
+    }
+    let grads = gradient(f, &x)?;
+    let grads: Vec<usize> = grads.iter().map(|e| e.id).collect();
+    let mut columns = Vec::with_capacity(x.len());
+    for &xj in &x {
+        let data_type = xj.get()?.data_type;
+        let one = wrapper.scalar(1.0, data_type).id;
+        let column = forward_diff(wrapper.get_mut().deref_mut(), &grads, &vec![xj.id], &vec![one])?;
+        columns.push(column);
+    }
+    // Each column holds the derivatives of all of the gradients with respect to one parameter
+    Ok((0..x.len()).map(|i| columns.iter()
+        .map(|column| wrapper.as_expr(column[i]).unwrap()).collect()).collect())
+}
+
+/// Calculates the full Jacobian of the expression **f** with respect to each of the
+/// expressions in **x**. The result for **x[i]** is a matrix with as many rows as
+/// elements in **f** and as many columns as elements in **x[i]**.
+///
+/// The Jacobian is built either with one reverse sweep per element of **f** or with
+/// one forward sweep per element of all of **x**, whichever are fewer. The number of
+/// sweeps must be constant, while the other side can have a symbolic number of elements.
+/// As tensors can not be reshaped, all of the expressions must be scalars or vectors,
+/// which can lie along any axis.
+pub fn jacobian<T1: AsRef<Expr>, T2: AsRef<Expr>>(f: T1, x: &Vec<T2>) -> Result<Vec<Expr>> {
+    let f = f.as_ref();
+    let ref wrapper = f.wrapper;
+    let combined: Vec<&Expr> = Some(f).into_iter().chain(x.iter().map(|e| e.as_ref())).collect();
+    same_graph(&combined)?;
+    let x = x.iter().map(|e| e.as_ref().id).collect();
+    let result = build_jacobian(wrapper.get_mut().deref_mut(), f.id, &x)?;
+    Ok(result.into_iter().map(|x| wrapper.as_expr(x).unwrap()).collect())
+}
+
+fn build_jacobian(graph: &mut Graph, f: usize, x: &Vec<usize>) -> Result<Vec<usize>> {
+    for &i in Some(&f).into_iter().chain(x.iter()) {
+        if non_unit_axes(&graph.get_node(i)?.shape).len() > 1 {
+            let err = ErrorKind::Msg(format!("The jacobian can only be computed for scalars \
+            and vectors, but the expression {} has shape {}.", i, graph.nodes[i].shape));
+            error!(graph.log, format!("[derivative] {}", err));
+            return Err(err.into())
+        }
+    }
+    if x.is_empty() {
+        return Ok(Vec::new())
+    }
+    let constant = |value: SymInt| if value.is_constant() {
+        value.eval(&HashMap::new()).ok().map(|v| v as usize)
+    } else {
+        None
+    };
+    let rows = constant(graph.nodes[f].shape.elements());
+    let columns = x.iter().fold(Some(0), |acc, &xi| match (acc, constant(graph.nodes[xi].shape.elements())) {
+        (Some(a), Some(b)) => Some(a + b),
+        _ => None
+    });
+    let forward = match (rows, columns) {
+        (Some(r), Some(c)) => c < r,
+        (Some(_), None) => false,
+        (None, Some(_)) => true,
+        (None, None) => {
+            let err = ErrorKind::Msg("The jacobian requires a constant number of elements \
+            either in the function or in the parameters.".into());
+            error!(graph.log, format!("[derivative] {}", err));
+            return Err(err.into())
+        }
+    };
+    debug!(graph.log, "[derivative] Building the jacobian of {} in {} mode.",
+           f, if forward { "forward" } else { "reverse" });
+
+    let mut blocks: Vec<Vec<usize>> = vec![Vec::new(); x.len()];
+    if forward {
+        // Each forward sweep gives a single column
+        for (j, &xj) in x.iter().enumerate() {
+            let shape = graph.nodes[xj].shape.clone();
+            let data_type = graph.nodes[xj].data_type;
+            for k in 0..constant(shape.elements()).unwrap() {
+                let v = graph.basis(k, shape.clone(), data_type)?;
+                let column = forward_diff(graph, &vec![f], &vec![xj], &vec![v])?[0];
+                // Independent derivatives are scalar zeros
+                let column = api::ids::broadcast_to(graph, column, f)?;
+                blocks[j].push(outer_product(graph, column, v)?);
+            }
+        }
+    } else {
+        // Each reverse sweep gives a single row for every parameter
+        let shape = graph.nodes[f].shape.clone();
+        let data_type = graph.nodes[f].data_type;
+        for i in 0..rows.unwrap() {
+            let u = graph.basis(i, shape.clone(), data_type)?;
+            let rows = reverse_diff(graph, &vec![f], x, &vec![u])?;
+            for (j, (&xj, &row)) in x.iter().zip(rows.iter()).enumerate() {
+                // Independent derivatives are scalar zeros
+                let row = api::ids::broadcast_to(graph, row, xj)?;
+                blocks[j].push(outer_product(graph, u, row)?);
+            }
+        }
+    }
+    blocks.into_iter().map(|block| match block.len() {
+        1 => Ok(block[0]),
+        _ => api::ids::add(graph, block)
+    }).collect()
+}
+
+/// The axes of the shape which are not equal to 1
+fn non_unit_axes(shape: &Shape) -> Vec<Axis> {
+    Axis::iter().cloned().filter(|&axis| *shape.get(axis) != 1).collect()
+}
+
+/// The vector as a column, by swapping its only non unit axis with the first one
+fn as_column(graph: &mut Graph, a: usize) -> Result<usize> {
+    match non_unit_axes(&graph.get_node(a)?.shape).get(0).cloned() {
+        None | Some(Axis::Axis0) => Ok(a),
+        Some(axis) => {
+            let mut order = [Axis::Axis0, Axis::Axis1, Axis::Axis2, Axis::Axis3];
+            order[0] = axis;
+            order[axis as usize] = Axis::Axis0;
+            api::ids::reorder(graph, a, Some(order))
+        }
+    }
+}
+
+/// The outer product **a b<sup>T</sup>** of two vectors along any axis
+fn outer_product(graph: &mut Graph, a: usize, b: usize) -> Result<usize> {
+    let a = as_column(graph, a)?;
+    let b = as_column(graph, b)?;
+    let b_transpose = api::ids::reorder(graph, b, None)?;
+    api::ids::mat_mul(graph, a, b_transpose)
+}
+
 pub fn reverse_diff_expr(f: &Vec<&Expr>, x: &Vec<&Expr>, u: &Vec<&Expr>) -> Result<Vec<Expr>> {
     let combined: Vec<&Expr> = f.iter().cloned()
         .chain(x.iter().cloned())
@@ -54,6 +224,35 @@ pub fn reverse_diff_expr(f: &Vec<&Expr>, x: &Vec<&Expr>, u: &Vec<&Expr>) -> Resu
     }
 }
 
+/// Calculates the directional derivative of the expression **f** along the
+/// vectors **v**, given for each of the expressions in **x**, using forward
+/// mode automatic differentiation.
+pub fn jvp<T1: AsRef<Expr>, T2: AsRef<Expr>, T3: AsRef<Expr>>(f: T1, x: &Vec<T2>, v: &Vec<T3>)
+                                                               -> Result<Expr> {
+    let f = f.as_ref();
+    let x: Vec<&Expr> = x.iter().map(|e| e.as_ref()).collect();
+    let v: Vec<&Expr> = v.iter().map(|e| e.as_ref()).collect();
+    Ok(forward_diff_expr(&vec![f], &x, &v)?.remove(0))
+}
+
+pub fn forward_diff_expr(f: &Vec<&Expr>, x: &Vec<&Expr>, v: &Vec<&Expr>) -> Result<Vec<Expr>> {
+    let combined: Vec<&Expr> = f.iter().cloned()
+        .chain(x.iter().cloned())
+        .chain(v.iter().cloned()).collect();
+    if combined.len() == 0 {
+        Ok(Vec::new())
+    } else {
+        // Verify all nodes are from the same graph
+        same_graph(&combined)?;
+        let f = f.iter().map(|e| e.id).collect();
+        let x = x.iter().map(|e| e.id).collect();
+        let v = v.iter().map(|e| e.id).collect();
+        let ref wrapper = combined[0].wrapper;
+        let result = forward_diff(wrapper.get_mut().deref_mut(), &f, &x, &v)?;
+        Ok(result.into_iter().map(|x| wrapper.as_expr(x).unwrap()).collect())
+    }
+}
+
 /// Performs reverse mode automatic differentiation.
 ///
 /// Mathematically the procedure computes **u^T J<sub>f</sub>**, where **J<sub>f</sub>**
@@ -73,6 +272,13 @@ pub fn reverse_diff_expr(f: &Vec<&Expr>, x: &Vec<&Expr>, u: &Vec<&Expr>) -> Resu
 /// * `u` - a vector of symbolic expressions defining projection vector which is multiplied
 /// with the columns of the Jacobian.
 ///
+/// ## Checkpointing
+///
+/// Expressions marked with `api::checkpoint` or inside of one of the `checkpoint_scopes`
+/// of the graph properties are recomputed during the backward pass, rather than kept
+/// from the forward pass. The outputs of a checkpointed scope, which are used outside
+/// of it, are still kept, such that each scope is recomputed only from its inputs.
+///
 /// ## Constraints
 ///
 /// All of the expressions in the three vectors must be part of the same graph. Additionally,
@@ -110,7 +316,8 @@ pub fn reverse_diff(graph: &mut Graph, f: &Vec<usize>, x: &Vec<usize>, u: &Vec<u
 
         trace!(graph.log, "[derivative] Starting reverse_diff.");
         // Flow tree
-        let flow_tree = graph.get_flow(x, f);
+        let mut flow_tree = graph.get_flow(x, f);
+        let mut recomputation = Recomputation::new(graph, f);
         // Derivative messages
         let mut derivatives: HashMap<usize, Vec<usize>> = HashMap::new();
         let init_grad_level = graph.grad_level;
@@ -138,7 +345,10 @@ pub fn reverse_diff(graph: &mut Graph, f: &Vec<usize>, x: &Vec<usize>, u: &Vec<u
         // Send derivative message in reverse mode
         let traversal: Vec<usize> = graph.order[min_index..max_index]
             .iter().cloned().collect();
-        for i in traversal.into_iter().rev().filter(|&x| flow_tree[x]) {
+        for i in traversal.into_iter().rev() {
+            if !flow_tree[i] {
+                continue
+            }
             let pd = derivatives.remove(&i).unwrap_or(Vec::new());
             for &x_i in x {
                 if x_i == i {
@@ -146,7 +356,14 @@ pub fn reverse_diff(graph: &mut Graph, f: &Vec<usize>, x: &Vec<usize>, u: &Vec<u
                     break;
                 }
             }
-            for (a, df_da) in reverse_diff_op(graph, i, pd, &flow_tree)? {
+            // Checkpointed expressions are differentiated through their recomputed copies
+            let target = if !pd.is_empty() && recomputation.is_required(graph, i) {
+                recomputation.copy(graph, i, pd[0], &mut flow_tree)?
+            } else {
+                i
+            };
+            for (a, df_da) in reverse_diff_op(graph, target, pd, &flow_tree)? {
+                let a = recomputation.original(a);
                 derivatives.entry(a).or_insert(Vec::new()).push(df_da);
             }
         }
@@ -197,11 +414,119 @@ pub fn reverse_diff(graph: &mut Graph, f: &Vec<usize>, x: &Vec<usize>, u: &Vec<u
         // Reset the grad_level
         graph.grad_level = init_grad_level;
 //        graph.scope = init_scope;
-        trace!(graph.log, "[derivative] Fished reverse_diff.");
+        trace!(graph.log, "[derivative] Finished reverse_diff.");
         Ok(ut_jf)
     }
 }
 
+/// The copies of the checkpointed expressions recomputed during the backward pass
+struct Recomputation {
+    /// Whether each of the expressions existing before the backward pass is recomputed
+    checkpointed: Vec<bool>,
+    /// Maps expressions to their recomputed copies
+    copies: HashMap<usize, usize>,
+    /// Maps the copies, including the `Rematerialize`d inputs, to the original expressions
+    originals: HashMap<usize, usize>
+}
+
+impl Recomputation {
+    fn new(graph: &Graph, f: &Vec<usize>) -> Self {
+        let ref patterns = graph.props.checkpoint_scopes;
+        // The part of the scope, which is checkpointed
+        let segment = |node: &ExprData| node.scope.iter()
+            .position(|s| patterns.iter().any(|p| scope_matches(p, s)))
+            .map(|k| node.scope[..k + 1].to_vec());
+        let checkpointed = graph.nodes.iter().map(|node| {
+            if node.ancestors.is_empty() || node.op.get_meta().name == "Update"
+                || f.contains(&node.id) {
+                false
+            } else if graph.checkpoints.contains(&node.id) {
+                true
+            } else if let Some(s) = segment(node) {
+                // Outputs of the segment are kept
+                node.children.iter()
+                    .map(|&c| &graph.nodes[c])
+                    .filter(|c| c.grad_level == node.grad_level)
+                    .all(|c| segment(c).as_ref() == Some(&s))
+            } else {
+                false
+            }
+        }).collect();
+        Recomputation {
+            checkpointed: checkpointed,
+            copies: HashMap::new(),
+            originals: HashMap::new()
+        }
+    }
+
+    /// Whether the derivative of **x** must use a recomputed copy, as it is
+    /// either checkpointed itself or one of its ancestors is
+    fn is_required(&self, graph: &Graph, x: usize) -> bool {
+        self.checkpointed[x] || graph.nodes[x].ancestors.iter().any(|&a| self.checkpointed[a])
+    }
+
+    /// Returns the expression, which the id refers to before the recomputation
+    fn original(&self, id: usize) -> usize {
+        self.originals.get(&id).cloned().unwrap_or(id)
+    }
+
+    /// Returns the recomputed copy of **x**, building it if necessary. The inputs of
+    /// the recomputation are ordered after the `dependency`.
+    fn copy(&mut self, graph: &mut Graph, x: usize, dependency: usize, flow_tree: &mut Vec<bool>)
+            -> Result<usize> {
+        if let Some(&c) = self.copies.get(&x) {
+            return Ok(c)
+        }
+        let ancestors = graph.nodes[x].ancestors.clone();
+        let mut args = Vec::with_capacity(ancestors.len());
+        for &a in &ancestors {
+            if self.checkpointed.get(a) == Some(&true) {
+                args.push(self.copy(graph, a, dependency, flow_tree)?);
+            } else {
+                args.push(a);
+            }
+        }
+        graph.scope.insert(0, format!("rd{}", graph.grad_level));
+        if args == ancestors {
+            // Without any recomputed arguments the copy would be the same expression
+            for arg in args.iter_mut() {
+                let r = graph.apply_op(Box::new(Rematerialize {}), vec![*arg, dependency])?;
+                let flow = flow_tree[*arg];
+                set_flow(flow_tree, r, flow);
+                self.originals.insert(r, *arg);
+                *arg = r;
+            }
+        }
+        let op = graph.nodes[x].op.clone();
+        let c = graph.apply_op(op, args)?;
+        graph.scope.remove(0);
+        graph.nodes[c].name = format!("{}|rm[{}]", graph.nodes[c].name, x);
+        debug!(graph.log, "[derivative] Recomputing {} as {}.", x, c);
+        let flow = flow_tree[x];
+        set_flow(flow_tree, c, flow);
+        self.copies.insert(x, c);
+        self.originals.insert(c, x);
+        Ok(c)
+    }
+}
+
+/// The copies have the same flow as the originals
+fn set_flow(flow_tree: &mut Vec<bool>, id: usize, value: bool) {
+    if flow_tree.len() <= id {
+        flow_tree.resize(id + 1, false);
+    }
+    flow_tree[id] = value;
+}
+
+/// Whether the scope name matches the pattern, where a trailing `*` matches any suffix
+fn scope_matches(pattern: &str, name: &str) -> bool {
+    if pattern.ends_with('*') {
+        name.starts_with(&pattern[..pattern.len() - 1])
+    } else {
+        name == pattern
+    }
+}
+
 /// Returns the a vector of pairs of parent ids and their corresponding derivatives
 /// arising from their dependence on **x**.
 ///
@@ -257,4 +582,156 @@ pub fn reverse_diff_op<'a>(graph: &mut Graph, x: usize, dx: Vec<usize>, flow_tre
     graph.scope.remove(0);
 
     Ok(parent_derivatives)
-}
\ No newline at end of file
+}
+
+/// Performs forward mode automatic differentiation.
+///
+/// Mathematically the procedure computes **J<sub>f</sub> v**, where **J<sub>f</sub>**
+/// is the Jacobian of **f** with respect to **x**. Each of the expressions in **x**
+/// is treated as an independent variable, even if it depends on another one.
+///
+/// The above mathematical formulas are valid even when **x**, **f** and **v** are lists of
+/// tensors, by flattening and stacking them in single vectors.
+///
+/// # Arguments
+///
+/// * `f` - a vector of symbolic expressions defining all of the functions of which the Jacobian
+/// will be
+/// * `x` - a vector of symbolic expressions defining what the Jacobian will be with respect to.
+/// * `v` - a vector of symbolic expressions defining the tangent vector which is multiplied
+/// with the rows of the Jacobian.
+///
+/// ## Constraints
+///
+/// All of the expressions in the three vectors must be part of the same graph. Additionally,
+/// it is required that `x` and `v` have the same length and each individual entries of
+/// the two vectors have the same shape.
+pub fn forward_diff(graph: &mut Graph, f: &Vec<usize>, x: &Vec<usize>, v: &Vec<usize>)
+                    -> Result<Vec<usize>> {
+    if f.is_empty() {
+        // If no functions then no derivatives
+        return Ok(Vec::new())
+    }
+    if x.len() == 0 {
+        // At least one parameter needed
+        let err = ErrorKind::Msg("No parameters provided to forward_diff.".into()).into();
+        error!(graph.log, format!("[derivative] {}", err));
+        Err(err)
+    } else if x.len() != v.len() {
+        // Same number of 'x' and 'v'
+        let err = ErrorKind::Msg(format!("Invalid number of tangent tensors - \
+        expected {}, actual - {}.", x.len(), v.len()));
+        error!(graph.log, format!("[derivative] {}", err));
+        Err(err.into())
+    } else {
+        // Verify shapes of 'x[i]' and 'v[i]'
+        for (&xi, &vi) in x.iter().zip(v.iter()) {
+            if graph.get_node(xi)?.shape != graph.get_node(vi)?.shape {
+                let err = ErrorKind::InvalidShapes(
+                    format!("forward_diff"),
+                    format!("{}", graph.get_node(xi)?.shape),
+                    format!("{}", graph.get_node(vi)?.shape));
+                error!(graph.log, format!("[derivative] {}", err));
+                return Err(err.into())
+            }
+        }
+        for &fi in f {
+            graph.get_node(fi)?;
+        }
+
+        trace!(graph.log, "[derivative] Starting forward_diff.");
+        // Flow tree
+        let flow_tree = graph.get_flow(x, f);
+        // Derivatives of the expressions
+        let mut derivatives: HashMap<usize, usize> = HashMap::new();
+        let init_grad_level = graph.grad_level;
+        let mut grad_level = 0;
+        for (i, (&xi, &vi)) in x.iter().zip(v.iter()).enumerate() {
+            debug!(graph.log, "[derivative] Initial derivative at index {} for {} is {}.", i, xi, vi);
+            derivatives.insert(xi, vi);
+        }
+        for &fi in f {
+            grad_level = ::std::cmp::max(grad_level, graph.nodes[fi].grad_level + 1);
+        }
+        graph.grad_level = grad_level;
+
+        // Send derivative messages in forward mode
+        let traversal: Vec<usize> = graph.order.iter().cloned()
+            .filter(|&i| flow_tree[i] && !derivatives.contains_key(&i)).collect();
+        for i in traversal {
+            let dx = graph.nodes[i].ancestors.iter()
+                .map(|a| derivatives.get(a).cloned()).collect();
+            if let Some(d) = forward_diff_op(graph, i, dx)? {
+                derivatives.insert(i, d);
+            }
+        }
+
+        let id = graph.props.policies.independent_derivative;
+        let mut jf_v = Vec::with_capacity(f.len());
+        for (i, &fi) in f.iter().enumerate() {
+            match derivatives.get(&fi).cloned() {
+                Some(d) => jf_v.push(d),
+                None => match id {
+                    Policy::Quite => {
+                        let data_type = graph.get_node(fi)?.data_type;
+                        let di = graph.scalar(0.0, data_type);
+                        graph.nodes[di].name = format!("{}|fd[{}]", graph.nodes[di].name, fi);
+                        jf_v.push(di);
+                    },
+                    Policy::Warn => {
+                        let err = ErrorKind::Msg(
+                            format!("The function 'f' at index {} is independent of \
+                            the tensors 'x'.", i));
+                        warn!(graph.log, format!("[derivative] {}", err));
+                        let data_type = graph.get_node(fi)?.data_type;
+                        let di = graph.scalar(0.0, data_type);
+                        graph.nodes[di].name = format!("{}|fd[{}]", graph.nodes[di].name, fi);
+                        jf_v.push(di);
+                    },
+                    Policy::Raise => {
+                        let err = ErrorKind::Msg(
+                            format!("The function 'f' at index {} is independent of \
+                            the tensors 'x'.", i));
+                        error!(graph.log, format!("[derivative] {}", err));
+                        return Err(err.into())
+                    },
+                }
+            }
+        };
+        // Reset the grad_level
+        graph.grad_level = init_grad_level;
+        trace!(graph.log, "[derivative] Finished forward_diff.");
+        Ok(jf_v)
+    }
+}
+
+/// Returns the derivative of **x** arising from the derivatives of its
+/// immediate ancestors, or `None` if it is zero.
+///
+/// Mathematically this computes **sum<sub>a</sub> dx/da * da**, where **a** are
+/// the immediate ancestors of **x**.
+///
+/// # Arguments
+///
+/// * `x` - a symbolic expression data of the "current" tensor variable
+/// * `dx` - the derivatives of the ancestors of **x** in the same order, where
+/// `None` marks an ancestor with a zero derivative
+pub fn forward_diff_op(graph: &mut Graph, x: usize, dx: Vec<Option<usize>>)
+                       -> Result<Option<usize>> {
+    // The derivatives of all of the parents are 0
+    if dx.iter().all(|d| d.is_none()) {
+        return Ok(None)
+    }
+
+    graph.scope.insert(0, format!("fd{}", graph.grad_level));
+    let op = graph.nodes[x].op.clone();
+    let derivative = op.forward_diff(graph, x, &dx)?;
+    if let Some(d) = derivative {
+        let old_name = graph.nodes[d].name.clone();
+        graph.nodes[d].name = format!("{}|fd[{}]", old_name, x);
+        debug!(graph.log, "[derivative] Derivative of {} is {}.", x, d);
+    }
+    graph.scope.remove(0);
+
+    Ok(derivative)
+}
@@ -1,4 +1,5 @@
 use primitives::*;
+use verify::GraphIssue;
 
 error_chain!{
         errors {
@@ -25,6 +26,24 @@ error_chain!{
                 description("Down casting tensor.")
                 display("Down casting tensor from {} to {}.", from, to)
             }
+
+            /// Error when the textual representation of a graph is malformed.
+            ParseError(line: usize, msg: String) {
+                description("Failed to parse graph.")
+                display("Failed to parse graph at line {}: {}", line, msg)
+            }
+
+            /// Error when the binary representation of a graph is malformed.
+            DecodeError(offset: usize, msg: String) {
+                description("Failed to decode graph.")
+                display("Failed to decode graph at byte {}: {}", offset, msg)
+            }
+
+            /// Error when `Graph::verify` finds inconsistencies in the graph.
+            InvalidGraph(issues: Vec<GraphIssue>) {
+                description("The graph is inconsistent.")
+                display("The graph has {} issues, the first one is: {}", issues.len(), issues[0])
+            }
         }
 //        foreign_links {
 //            LibUsb(::libusb::Error);
@@ -0,0 +1,276 @@
+use primitives::*;
+use graph::*;
+use ops::*;
+use std::io::{Write, Result, Error, ErrorKind};
+use export::GraphExporter;
+
+/// The first bytes of every file in the binary GIR format
+pub const MAGIC: &'static [u8; 4] = b"GIRB";
+/// The version of the binary GIR format
+pub const VERSION: u8 = 2;
+/// Marks that the content is a single `Graph`
+pub const KIND_GRAPH: u8 = 0;
+/// Marks that the content is a `GraphFunction`
+pub const KIND_FUNCTION: u8 = 1;
+
+pub const TAG_BOOL: u8 = 0;
+pub const TAG_INT: u8 = 1;
+pub const TAG_FLOAT: u8 = 2;
+pub const TAG_STR: u8 = 3;
+pub const TAG_DATA_TYPE: u8 = 4;
+pub const TAG_AXIS: u8 = 5;
+pub const TAG_SYM_INT: u8 = 6;
+pub const TAG_SHAPE: u8 = 7;
+pub const TAG_LIST: u8 = 8;
+
+/// Exports graphs and functions in a compact binary format, which can be
+/// read back by the `BinaryImporter`.
+///
+/// In contrast to the textual format, every field of the expressions is
+/// stored, hence the graph is loaded without applying any of the operators.
+/// The policies, the `checkpoint_scopes`, the `size_hints` and the checkpointed
+/// expressions are kept as well.
+/// Integers are written as LEB128 variable length integers, floats as their
+/// little endian bits and symbolic integers as their code representation.
+#[derive(Debug, Clone)]
+pub struct BinaryExporter {
+    pub registry: OperatorRegistry
+}
+
+impl BinaryExporter {
+    pub fn new() -> Self {
+        BinaryExporter::with_registry(OperatorRegistry::default())
+    }
+
+    /// Exporter which can encode operators registered outside of the crate
+    pub fn with_registry(registry: OperatorRegistry) -> Self {
+        BinaryExporter {
+            registry: registry
+        }
+    }
+
+    /// Encodes the graph without the header
+    pub fn encode_graph(&self, buffer: &mut Encoder, graph: &Graph) -> Result<()> {
+        buffer.string(&graph.props.scope_delimiter);
+        buffer.u8(graph.props.policies.implicit_broadcast as u8);
+        buffer.u8(graph.props.policies.implicit_cast as u8);
+        buffer.u8(graph.props.policies.downcast as u8);
+        buffer.u8(graph.props.policies.independent_derivative as u8);
+        buffer.strings(&graph.props.checkpoint_scopes);
+        let mut hints: Vec<(&String, &i64)> = graph.props.size_hints.iter().collect();
+        hints.sort();
+        buffer.varint(hints.len() as u64);
+        for (name, &value) in hints {
+            buffer.string(name);
+            buffer.int(value);
+        }
+        buffer.varint(graph.grad_level as u64);
+        buffer.strings(&graph.scope);
+        buffer.varint(graph.nodes.len() as u64);
+        for node in &graph.nodes {
+            self.encode_expr(buffer, node)?;
+        }
+        buffer.ids(&graph.order);
+        let mut names: Vec<&String> = graph.op_map.keys().collect();
+        names.sort();
+        buffer.varint(names.len() as u64);
+        for name in names {
+            buffer.string(name);
+            buffer.ids(&graph.op_map[name]);
+        }
+        let mut checkpoints: Vec<usize> = graph.checkpoints.iter().cloned().collect();
+        checkpoints.sort();
+        buffer.ids(&checkpoints);
+        Ok(())
+    }
+
+    /// Encodes a single expression
+    pub fn encode_expr(&self, buffer: &mut Encoder, expr: &ExprData) -> Result<()> {
+        let args = self.registry.encode(&*expr.op)
+            .map_err(|e| Error::new(ErrorKind::InvalidInput, format!("{}", e)))?;
+        buffer.varint(expr.id as u64);
+        buffer.string(&expr.name);
+        buffer.string(expr.op.get_meta().name);
+        buffer.varint(args.len() as u64);
+        for arg in &args {
+            buffer.op_arg(arg);
+        }
+        buffer.ids(&expr.ancestors);
+        let mut children: Vec<usize> = expr.children.iter().cloned().collect();
+        children.sort();
+        buffer.ids(&children);
+        buffer.u8(expr.data_type as u8);
+        buffer.shape(&expr.shape);
+        buffer.bool(expr.is_input_dependent);
+        buffer.bool(expr.is_differentiable);
+        buffer.u8(expr.matrix_positivity as u8);
+        buffer.u8(expr.matrix_symmetry as u8);
+        buffer.u8(expr.matrix_fill as u8);
+        buffer.varint(expr.grad_level as u64);
+        buffer.strings(&expr.scope);
+        match expr.sym_int {
+            Some(ref value) => {
+                buffer.bool(true);
+                buffer.sym_int(value);
+            },
+            None => buffer.bool(false)
+        }
+        Ok(())
+    }
+}
+
+impl GraphExporter for BinaryExporter {
+    fn export(&self, io: &mut Write, graph: &Graph) -> Result<()> {
+        let mut buffer = Encoder::new();
+        buffer.header(KIND_GRAPH);
+        self.encode_graph(&mut buffer, graph)?;
+        io.write_all(&buffer.bytes)
+    }
+
+    #[allow(unused_variables)]
+    fn export_expr(&self, io: &mut Write, expr: &ExprData, sep: &str) -> Result<()> {
+        let mut buffer = Encoder::new();
+        self.encode_expr(&mut buffer, expr)?;
+        io.write_all(&buffer.bytes)
+    }
+
+    fn export_function(&self, io: &mut Write, gf: &GraphFunction) -> Result<()> {
+        let mut buffer = Encoder::new();
+        buffer.header(KIND_FUNCTION);
+        buffer.string(&gf.name);
+        buffer.ids(&gf.inputs);
+        buffer.ids(&gf.outputs);
+        let mut parameters: Vec<(&String, &usize)> = gf.parameters.iter().collect();
+        parameters.sort();
+        buffer.varint(parameters.len() as u64);
+        for (name, &id) in parameters {
+            buffer.string(name);
+            buffer.varint(id as u64);
+        }
+        let mut symints: Vec<String> = gf.unique_symints.iter().cloned().collect();
+        symints.sort();
+        buffer.strings(&symints);
+        self.encode_graph(&mut buffer, &gf.graph)?;
+        io.write_all(&buffer.bytes)
+    }
+}
+
+/// Buffer for writing the primitives of the binary GIR format
+#[derive(Debug, Clone, Default)]
+pub struct Encoder {
+    pub bytes: Vec<u8>
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Encoder::default()
+    }
+
+    pub fn header(&mut self, kind: u8) {
+        self.bytes.extend_from_slice(MAGIC);
+        self.u8(VERSION);
+        self.u8(kind);
+    }
+
+    pub fn u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    pub fn bool(&mut self, value: bool) {
+        self.u8(value as u8);
+    }
+
+    pub fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.u8((value as u8 & 0x7f) | 0x80);
+            value >>= 7;
+        }
+        self.u8(value as u8);
+    }
+
+    /// Signed integers are zig-zag encoded
+    pub fn int(&mut self, value: i64) {
+        self.varint(((value << 1) ^ (value >> 63)) as u64);
+    }
+
+    pub fn f64(&mut self, value: f64) {
+        let bits = value.to_bits();
+        for i in 0..8 {
+            self.u8((bits >> (8 * i)) as u8);
+        }
+    }
+
+    pub fn string(&mut self, value: &str) {
+        self.varint(value.len() as u64);
+        self.bytes.extend_from_slice(value.as_bytes());
+    }
+
+    pub fn strings(&mut self, values: &[String]) {
+        self.varint(values.len() as u64);
+        for value in values {
+            self.string(value);
+        }
+    }
+
+    pub fn ids(&mut self, values: &[usize]) {
+        self.varint(values.len() as u64);
+        for &value in values {
+            self.varint(value as u64);
+        }
+    }
+
+    pub fn sym_int(&mut self, value: &SymInt) {
+        self.string(&value.to_code(&|x: String| x));
+    }
+
+    pub fn shape(&mut self, value: &Shape) {
+        self.sym_int(&value.0);
+        self.sym_int(&value.1);
+        self.sym_int(&value.2);
+        self.sym_int(&value.3);
+    }
+
+    pub fn op_arg(&mut self, arg: &OpArg) {
+        match *arg {
+            OpArg::Bool(v) => {
+                self.u8(TAG_BOOL);
+                self.bool(v);
+            },
+            OpArg::Int(v) => {
+                self.u8(TAG_INT);
+                self.int(v);
+            },
+            OpArg::Float(v) => {
+                self.u8(TAG_FLOAT);
+                self.f64(v);
+            },
+            OpArg::Str(ref v) => {
+                self.u8(TAG_STR);
+                self.string(v);
+            },
+            OpArg::DataType(v) => {
+                self.u8(TAG_DATA_TYPE);
+                self.u8(v as u8);
+            },
+            OpArg::Axis(v) => {
+                self.u8(TAG_AXIS);
+                self.u8(v as u8);
+            },
+            OpArg::SymInt(ref v) => {
+                self.u8(TAG_SYM_INT);
+                self.sym_int(v);
+            },
+            OpArg::Shape(ref v) => {
+                self.u8(TAG_SHAPE);
+                self.shape(v);
+            },
+            OpArg::List(ref v) => {
+                self.u8(TAG_LIST);
+                self.varint(v.len() as u64);
+                for x in v {
+                    self.op_arg(x);
+                }
+            }
+        }
+    }
+}
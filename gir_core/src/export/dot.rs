@@ -39,7 +39,7 @@ impl GraphExporter for DotExporter {
         let color = match op.name {
             "Input" => "orange",
             "Parameter" => "green",
-            "Scalar" => "yellow",
+            "Scalar" | "Basis" => "yellow",
             _ => "blue"
         };
         writeln!(io, "subgraph cluster_{} {{\n\
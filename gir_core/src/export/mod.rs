@@ -11,7 +11,21 @@ pub trait GraphExporter {
         let mut file = File::create(path)?;
         self.export(&mut file, graph)
     }
+
+    /// By default exports only the graph of the function
+    fn export_function(&self, io: &mut Write, gf: &GraphFunction) -> Result<()> {
+        self.export(io, &gf.graph)
+    }
+
+    fn export_function_to_file(&self, path: &str, gf: &GraphFunction) -> Result<()> {
+        let mut file = File::create(path)?;
+        self.export_function(&mut file, gf)
+    }
 }
 
 pub mod dot;
+pub mod text;
+pub mod binary;
 pub use self::dot::DotExporter;
+pub use self::text::TextExporter;
+pub use self::binary::BinaryExporter;
@@ -0,0 +1,119 @@
+use graph::*;
+use ops::*;
+use std::io::{Write, Result, Error, ErrorKind};
+use export::GraphExporter;
+
+/// Exports graphs in the textual GIR format, which can be read back by the
+/// `TextImporter`.
+///
+/// Every expression is written on a single line as
+///
+/// `%id = Operator<args>(%ancestors...) : DataType (shape) @[scope...] "name"`
+///
+/// where the `<args>` are present only for operators with arguments.
+///
+/// Only the expressions are written, while the graph properties, e.g. the policies,
+/// the `checkpoint_scopes` and the `size_hints`, as well as the checkpointed
+/// expressions are dropped. Use the `BinaryExporter` to keep them.
+#[derive(Debug, Clone)]
+pub struct TextExporter {
+    pub registry: OperatorRegistry
+}
+
+impl TextExporter {
+    pub fn new() -> Self {
+        TextExporter::with_registry(OperatorRegistry::default())
+    }
+
+    /// Exporter which can format operators registered outside of the crate
+    pub fn with_registry(registry: OperatorRegistry) -> Self {
+        TextExporter {
+            registry: registry
+        }
+    }
+}
+
+pub fn quote(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c => quoted.push(c)
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn format_list<T, F: FnMut(&T) -> String>(values: &[T], f: F) -> String {
+    let values: Vec<String> = values.iter().map(f).collect();
+    format!("[{}]", values.join(", "))
+}
+
+/// Formats a single operator argument
+pub fn format_op_arg(arg: &OpArg) -> String {
+    match *arg {
+        OpArg::Bool(v) => format!("{}", v),
+        OpArg::Int(v) => format!("{}", v),
+        OpArg::Float(v) => format!("{:?}", v),
+        OpArg::Str(ref v) => quote(v),
+        OpArg::DataType(v) => format!("{}", v),
+        OpArg::Axis(v) => format!("{}", v),
+        OpArg::SymInt(ref v) => format!("sym({})", v.to_code(&|x: String| x)),
+        OpArg::Shape(ref v) => format!("{}", v),
+        OpArg::List(ref v) => format_list(v, format_op_arg)
+    }
+}
+
+/// Formats the arguments of the operator, if it has any
+pub fn format_op_args(registry: &OperatorRegistry, op: &Operator) -> Result<Option<String>> {
+    let args = registry.encode(op).map_err(|e| Error::new(ErrorKind::InvalidInput,
+                                                           format!("{}", e)))?;
+    if args.is_empty() {
+        Ok(None)
+    } else {
+        let args: Vec<String> = args.iter().map(format_op_arg).collect();
+        Ok(Some(args.join(", ")))
+    }
+}
+
+impl GraphExporter for TextExporter {
+    fn export(&self, io: &mut Write, graph: &Graph) -> Result<()> {
+        writeln!(io, "graph {{")?;
+        for &id in &graph.order {
+            self.export_expr(io, &graph.nodes[id], &graph.props.scope_delimiter)?;
+        }
+        writeln!(io, "}}")
+    }
+
+    #[allow(unused_variables)]
+    fn export_expr(&self, io: &mut Write, expr: &ExprData, sep: &str) -> Result<()> {
+        let args = format_op_args(&self.registry, &*expr.op)?
+            .map(|x| format!("<{}>", x)).unwrap_or("".into());
+        let ancestors: Vec<String> = expr.ancestors.iter().map(|x| format!("%{}", x)).collect();
+        writeln!(io, "    %{} = {}{}({}) : {} {} @{} {}",
+                 expr.id,
+                 expr.op.get_meta().name,
+                 args,
+                 ancestors.join(", "),
+                 expr.data_type,
+                 expr.shape,
+                 format_list(&expr.scope, |x| quote(x)),
+                 quote(&expr.name))
+    }
+
+    fn export_function(&self, io: &mut Write, gf: &GraphFunction) -> Result<()> {
+        let inputs: Vec<String> = gf.inputs.iter().map(|x| format!("%{}", x)).collect();
+        let outputs: Vec<String> = gf.outputs.iter().map(|x| format!("%{}", x)).collect();
+        writeln!(io, "function {}({}) -> ({}) {{",
+                 quote(&gf.name), inputs.join(", "), outputs.join(", "))?;
+        for &id in &gf.graph.order {
+            self.export_expr(io, &gf.graph.nodes[id], &gf.graph.props.scope_delimiter)?;
+        }
+        writeln!(io, "}}")
+    }
+}
@@ -3,6 +3,7 @@ use props::*;
 use ops::*;
 use errors::*;
 use api::ids;
+use backend::BackendPrecisions;
 
 use std::boxed::Box;
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -59,11 +60,19 @@ impl Expr {
 #[derive(Debug, Clone)]
 pub struct Graph {
     pub nodes: Vec<ExprData>,
+    /// The order in which the expressions are computed by the backends. New expressions
+    /// are appended to it, while passes like the `MemoryScheduler` can change it to any
+    /// other topological order.
     pub order: Vec<usize>,
     pub props: GraphProperties,
     pub grad_level: usize,
     pub scope: Vec<String>,
     pub op_map: HashMap<String, Vec<usize>>,
+    /// Maps the operator key and the ancestors to the expression, used for
+    /// finding equivalent expressions
+    pub expr_map: HashMap<(String, Vec<usize>), usize>,
+    /// Expressions marked to be recomputed during the backward pass of `reverse_diff`
+    pub checkpoints: HashSet<usize>,
     //    pub updates: HashMap<usize, usize>,
     pub log: Logger,
 }
@@ -83,6 +92,8 @@ impl Graph {
             grad_level: 0,
             scope: Vec::new(),
             op_map: HashMap::new(),
+            expr_map: HashMap::new(),
+            checkpoints: HashSet::new(),
             //            updates: HashMap::new(),
             log: log
         };
@@ -131,6 +142,10 @@ impl Graph {
             } else {
                 self.op_map.get_mut(data.op.get_meta().name).unwrap().push(data.id);
             }
+            // Insert into expr_map
+            if let Some(key) = Graph::expr_key(&data) {
+                self.expr_map.insert(key, data.id);
+            }
             // Insert into order
             self.order.push(data.id);
             // Insert into nodes
@@ -139,92 +154,52 @@ impl Graph {
         }
     }
 
+    /// The key of the expression in the `expr_map`, if it can be merged
+    pub fn expr_key(data: &ExprData) -> Option<(String, Vec<usize>)> {
+        data.op.get_key().map(|key| {
+            let mut ancestors = data.ancestors.clone();
+            if !data.op.get_meta().ordered_parents {
+                ancestors.sort();
+            }
+            (key, ancestors)
+        })
+    }
+
+    /// Recreates the `expr_map` from the nodes
+    pub fn rebuild_expr_map(&mut self) {
+        self.expr_map.clear();
+        for &id in &self.order {
+            if let Some(key) = Graph::expr_key(&self.nodes[id]) {
+                self.expr_map.insert(key, id);
+            }
+        }
+    }
+
     pub fn equivalent_node(&self, data: &ExprData) -> Result<Option<usize>> {
         let meta = data.op.get_meta();
-        if data.ancestors.len() == 0 {
-            // Only input operators have no parents
-            match meta.name {
-                "Parameter" => {
-                    // Check if parameter already exists
-                    if let Some(v) = self.op_map.get("Parameter") {
-                        let (_, _, name) = *data.op.get_args().unwrap()
-                            .downcast::<(FundamentalType, Shape, Vec<String>)>().unwrap();
-                        for &id in v {
-                            let (_, _, v_name) = *self.nodes[id].op.get_args().unwrap()
-                                .downcast::<(FundamentalType, Shape, Vec<String>)>().unwrap();
-                            if name == v_name {
-                                return Err(ErrorKind::Msg(
-                                    format!("The parameter '{}' already exists \
-                                    in the graph.", name.join("::"))).into())
-                            }
-                        }
-                    }
-                },
-                "Scalar" => {
-                    if let Some(v) = self.op_map.get("Scalar") {
-                        let (value, data_type) = *data.op.get_args().unwrap()
-                            .downcast::<(f64, FundamentalType)>().unwrap();
-                        for &id in v {
-                            let (v_value, v_data_type) = *self.nodes[id].op.get_args().unwrap()
-                                .downcast::<(f64, FundamentalType)>().unwrap();
-                            if value == v_value && data_type == v_data_type {
-                                return Ok(Some(id));
-                            }
-                        }
-                    }
-                },
-                "SymIntInput" => {
-                    if let Some(v) = self.op_map.get("SymIntInput") {
-                        let sym_int_id = data.op.get_args().unwrap()
-                            .downcast::<String>().unwrap();
-                        for &id in v {
-                            let v_id = self.nodes[id].op.get_args().unwrap()
-                                .downcast::<String>().unwrap();
-                            if sym_int_id == v_id {
-                                return Ok(Some(id));
-                            }
-                        }
+        if meta.name == "Parameter" {
+            // Check if parameter already exists
+            if let Some(v) = self.op_map.get("Parameter") {
+                let (_, _, name) = *data.op.get_args().unwrap()
+                    .downcast::<(FundamentalType, Shape, Vec<String>)>().unwrap();
+                for &id in v {
+                    let (_, _, v_name) = *self.nodes[id].op.get_args().unwrap()
+                        .downcast::<(FundamentalType, Shape, Vec<String>)>().unwrap();
+                    if name == v_name {
+                        return Err(ErrorKind::Msg(
+                            format!("The parameter '{}' already exists \
+                            in the graph.", name.join("::"))).into())
                     }
-                },
-                _ => {}
+                }
             }
-            return Ok(None)
         }
-        // For reductions if they have not changed the shape its a pointless exercise
-        if meta.reduction && data.shape == self.nodes[data.ancestors[0]].shape {
+        // For reductions if they have not changed the shape or the type its a pointless exercise
+        if meta.reduction && data.ancestors.len() > 0 &&
+            data.shape == self.nodes[data.ancestors[0]].shape &&
+            data.data_type == self.nodes[data.ancestors[0]].data_type {
             return Ok(Some(data.ancestors[0]))
         }
-        // Candidates are all children of the ancestor
-        for &a in &data.ancestors {
-            for &c in &self.nodes.get(a).unwrap().children {
-                let node = self.nodes.get(c).unwrap();
-                if meta == node.op.get_meta() {
-                    if meta.name == "TensorShape" {
-                        let axis = data.op.get_args().unwrap()
-                            .downcast::<Axis>().unwrap();
-                        let node_axis = node.op.get_args().unwrap()
-                            .downcast::<Axis>().unwrap();
-                        if axis == node_axis {
-                            return Ok(Some(node.id));
-                        }
-                    } else {
-                        let ordered_parents = data.op.get_meta().ordered_parents;
-                        if ordered_parents && data.ancestors == node.ancestors {
-                            return Ok(Some(c));
-                        } else if !ordered_parents && data.ancestors.len() == node.ancestors.len() {
-                            let mut v1_sorted = data.ancestors.clone();
-                            v1_sorted.sort();
-                            let mut v2_sorted = node.ancestors.clone();
-                            v2_sorted.sort();
-                            if v1_sorted == v2_sorted {
-                                return Ok(Some(c));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        Ok(None)
+        Ok(Graph::expr_key(data).and_then(|key| self.expr_map.get(&key).cloned()))
     }
 
     pub fn get_descendants(&self, roots: &Vec<usize>) -> Vec<bool> {
@@ -288,6 +263,22 @@ impl Graph {
         self.add_node(op.apply_null()).unwrap()
     }
 
+    pub fn basis(&mut self, index: usize, shape: Shape, data_type: FundamentalType) -> Result<usize> {
+        let elements = shape.elements();
+        if elements.is_constant() && index as i64 >= elements.eval(&HashMap::new()).unwrap() {
+            let err = ErrorKind::InvalidArguments("Basis".into(), Vec::new(),
+                format!("The index {} is out of bounds for shape {}.", index, shape));
+            error!(self.log, format!("[graph] {}", err));
+            return Err(err.into())
+        }
+        let op = Box::new(Basis{
+            index: index,
+            shape: shape,
+            data_type: data_type,
+        });
+        self.add_node(op.apply_null())
+    }
+
     pub fn input(&mut self, data_type: FundamentalType, shape: Shape, name: Option<String>) -> usize {
         let op = Box::new(Input{
             data_type: data_type,
@@ -322,7 +313,7 @@ impl Graph {
                 let op = node.op.clone();
                 graph.scope = self.nodes[id].scope.clone();
                 match op.get_meta().name {
-                    "Input" | "Parameter" | "Scalar" => {
+                    "Input" | "Parameter" | "Scalar" | "Basis" | "SymIntInput" => {
                         let new_id = graph.add_node(op.apply_null())?;
                         provided.insert(id, new_id);
                     },
@@ -353,6 +344,12 @@ impl Graph {
             }
         }
         graph.scope = init_scope;
+        // Keep the checkpoints of the copied expressions
+        for c in self.checkpoints.iter().filter(|&&c| mask[c]) {
+            if let Some(&new_id) = provided.get(c) {
+                graph.checkpoints.insert(new_id);
+            }
+        }
         Ok(provided)
     }
 }
@@ -417,6 +414,11 @@ impl GraphWrapper {
         self.as_expr(x).unwrap()
     }
 
+    pub fn basis(&self, index: usize, shape: Shape, data_type: FundamentalType) -> Result<Expr> {
+        let x = self.get_mut().basis(index, shape, data_type)?;
+        self.as_expr(x)
+    }
+
     pub fn input(&self, data_type: FundamentalType, shape: Shape, name: Option<String>) -> Expr {
         // This can not fail
         let x = self.get_mut().input(data_type, shape, name);
@@ -487,28 +489,42 @@ impl GraphFunction {
         for &(ref var, ref upd) in extra_updates {
             ids::update(&mut sub_graph, *mapping.get(var).unwrap(), *mapping.get(upd).unwrap())?;
         }
+        // Verify the copy in debug builds
+        if cfg!(debug_assertions) {
+            sub_graph.verify()?;
+        }
+        // Return the function created
+        Ok(GraphFunction::from_graph(
+            sub_graph,
+            inputs.iter().map(|x| *mapping.get(x).unwrap()).collect(),
+            outputs.iter().map(|x| *mapping.get(x).unwrap()).collect(),
+            name.unwrap_or("main".into())))
+    }
+
+    /// Wraps an already constructed graph, which is assumed to contain only
+    /// the nodes needed by the function.
+    pub fn from_graph(graph: Graph, inputs: Vec<usize>, outputs: Vec<usize>, name: String) -> Self {
         // Fill up the unique symbolic integers
         let mut unique = HashSet::new();
-        for ref node in &sub_graph.nodes {
+        for ref node in &graph.nodes {
             node.shape.0.unique_identifiers(&mut unique);
             node.shape.1.unique_identifiers(&mut unique);
             node.shape.2.unique_identifiers(&mut unique);
             node.shape.3.unique_identifiers(&mut unique);
         }
         // Find all of the parameters
-        let params = sub_graph.op_map.get("Parameter")
+        let params = graph.op_map.get("Parameter")
             .map(|v| v.iter()
-                .map(|&id| (sub_graph.nodes[id].name.clone(), id)).collect())
+                .map(|&id| (graph.nodes[id].name.clone(), id)).collect())
             .unwrap_or(HashMap::new());
-        // Return the function created
-        Ok(GraphFunction{
-            name: name.unwrap_or("main".into()),
-            graph: sub_graph,
-            inputs: inputs.iter().map(|x| *mapping.get(x).unwrap()).collect(),
-            outputs: outputs.iter().map(|x| *mapping.get(x).unwrap()).collect(),
+        GraphFunction{
+            name: name,
+            graph: graph,
+            inputs: inputs,
+            outputs: outputs,
             parameters: params,
             unique_symints: unique,
-        })
+        }
     }
 
     pub fn new_from_expr(inputs: &[Expr],
@@ -552,4 +568,22 @@ impl GraphFunction {
                   inputs.as_slice(), outputs.as_slice(), discard_updates,
                   extra_updates.as_slice(), name)
     }
-}
\ No newline at end of file
+    /// Sums the symbolic costs of all expressions of the function per scope, where
+    /// the cost of each scope includes all of its nested scopes and the empty
+    /// scope is the cost of the whole function.
+    pub fn cost_report(&self, precisions: &BackendPrecisions) -> HashMap<String, OperatorCost> {
+        let ref sep = self.graph.props.scope_delimiter;
+        let mut report: HashMap<String, OperatorCost> = HashMap::new();
+        report.insert("".into(), OperatorCost::default());
+        for &id in &self.graph.order {
+            let ref node = self.graph.nodes[id];
+            let cost = node.op.get_cost(&self.graph, id, precisions);
+            for i in 0..node.scope.len() + 1 {
+                report.entry(node.scope[..i].join(sep))
+                    .or_insert_with(OperatorCost::default)
+                    .add(&cost);
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,371 @@
+use primitives::*;
+use graph::*;
+use ops::*;
+use errors::*;
+use import::FunctionImporter;
+use import::text::parse_sym_int;
+use export::binary::*;
+
+use std::io::Read;
+use std::collections::{HashMap, HashSet};
+
+/// Reads graphs and functions in the binary GIR format produced by the
+/// `BinaryExporter`.
+///
+/// The expressions are restored exactly as they were written, without
+/// applying the operators or recomputing any of their properties.
+#[derive(Debug, Clone)]
+pub struct BinaryImporter {
+    pub registry: OperatorRegistry
+}
+
+impl BinaryImporter {
+    pub fn new() -> Self {
+        BinaryImporter::with_registry(OperatorRegistry::default())
+    }
+
+    /// Importer which can reconstruct operators registered outside of the crate
+    pub fn with_registry(registry: OperatorRegistry) -> Self {
+        BinaryImporter {
+            registry: registry
+        }
+    }
+
+    /// Decodes a `GraphFunction` from bytes
+    pub fn decode_function(&self, bytes: &[u8]) -> Result<GraphFunction> {
+        let mut decoder = Decoder::new(bytes);
+        decoder.header(KIND_FUNCTION)?;
+        let name = decoder.string()?;
+        let inputs = decoder.ids()?;
+        let outputs = decoder.ids()?;
+        let mut parameters = HashMap::new();
+        for _ in 0..decoder.varint()? {
+            let name = decoder.string()?;
+            parameters.insert(name, decoder.varint()? as usize);
+        }
+        let unique_symints: HashSet<String> = decoder.strings()?.into_iter().collect();
+        let graph = self.graph(&mut decoder)?;
+        decoder.end()?;
+        for &id in inputs.iter().chain(outputs.iter()).chain(parameters.values()) {
+            if id >= graph.nodes.len() {
+                return Err(ErrorKind::InvalidExprAccess(id).into())
+            }
+        }
+        Ok(GraphFunction {
+            name: name,
+            graph: graph,
+            inputs: inputs,
+            outputs: outputs,
+            parameters: parameters,
+            unique_symints: unique_symints
+        })
+    }
+
+    /// Decodes a `Graph` from bytes
+    pub fn decode_graph(&self, bytes: &[u8]) -> Result<Graph> {
+        let mut decoder = Decoder::new(bytes);
+        decoder.header(KIND_GRAPH)?;
+        let graph = self.graph(&mut decoder)?;
+        decoder.end()?;
+        Ok(graph)
+    }
+
+    fn graph(&self, decoder: &mut Decoder) -> Result<Graph> {
+        let mut graph = Graph::default();
+        graph.props.scope_delimiter = decoder.string()?;
+        graph.props.policies.implicit_broadcast = decoder.policy()?;
+        graph.props.policies.implicit_cast = decoder.policy()?;
+        graph.props.policies.downcast = decoder.policy()?;
+        graph.props.policies.independent_derivative = decoder.policy()?;
+        graph.props.checkpoint_scopes = decoder.strings()?;
+        for _ in 0..decoder.varint()? {
+            let name = decoder.string()?;
+            let value = decoder.int()?;
+            graph.props.size_hints.insert(name, value);
+        }
+        graph.grad_level = decoder.varint()? as usize;
+        graph.scope = decoder.strings()?;
+        let n = decoder.varint()? as usize;
+        for i in 0..n {
+            let expr = self.expr(decoder, i, n)?;
+            graph.nodes.push(expr);
+        }
+        graph.order = decoder.ids()?;
+        graph.op_map.clear();
+        for _ in 0..decoder.varint()? {
+            let name = decoder.string()?;
+            graph.op_map.insert(name, decoder.ids()?);
+        }
+        graph.checkpoints = decoder.ids()?.into_iter().collect();
+        for &id in graph.order.iter().chain(graph.op_map.values().flat_map(|x| x.iter()))
+            .chain(graph.checkpoints.iter()) {
+            if id >= n {
+                return decoder.error(format!("Invalid expression id {}.", id))
+            }
+        }
+        graph.rebuild_expr_map();
+        Ok(graph)
+    }
+
+    /// Decodes the expression at position `index` of a graph with `n` expressions
+    fn expr(&self, decoder: &mut Decoder, index: usize, n: usize) -> Result<ExprData> {
+        let id = decoder.varint()? as usize;
+        if id != index {
+            return decoder.error(format!("The expression at {} has the id {}.", index, id))
+        }
+        let name = decoder.string()?;
+        let op_name = decoder.string()?;
+        let mut args = Vec::new();
+        for _ in 0..decoder.varint()? {
+            args.push(decoder.op_arg()?);
+        }
+        let op = match self.registry.decode(&op_name, &args) {
+            Ok(op) => op,
+            Err(e) => return decoder.error(format!("{}", e))
+        };
+        let ancestors = decoder.ids()?;
+        let children: HashSet<usize> = decoder.ids()?.into_iter().collect();
+        if let Some(&a) = ancestors.iter().chain(children.iter()).find(|&&x| x >= n) {
+            return decoder.error(format!("Invalid expression id {}.", a))
+        }
+        // Ancestors must come first, which also excludes cycles
+        if let Some(&a) = ancestors.iter().find(|&&x| x >= id) {
+            return decoder.error(format!("The ancestor {} of {} is not defined before it.", a, id))
+        }
+        Ok(ExprData {
+            id: id,
+            name: name,
+            ancestors: ancestors,
+            children: children,
+            op: op,
+            data_type: decoder.data_type()?,
+            shape: decoder.shape()?,
+            is_input_dependent: decoder.bool()?,
+            is_differentiable: decoder.bool()?,
+            matrix_positivity: match decoder.u8()? {
+                0 => MatrixPositivity::Indefinite,
+                1 => MatrixPositivity::PositiveSemiDefinite,
+                2 => MatrixPositivity::PositiveDefinite,
+                3 => MatrixPositivity::NegativeDefinite,
+                4 => MatrixPositivity::NegativeSemiDefinite,
+                v => return decoder.error(format!("Invalid matrix positivity {}.", v))
+            },
+            matrix_symmetry: match decoder.u8()? {
+                0 => MatrixSymmetry::NonSymmetric,
+                1 => MatrixSymmetry::Symmetric,
+                2 => MatrixSymmetry::SkewSymmetric,
+                v => return decoder.error(format!("Invalid matrix symmetry {}.", v))
+            },
+            matrix_fill: match decoder.u8()? {
+                0 => MatrixFill::NonStructuredFill,
+                1 => MatrixFill::Diagonal,
+                2 => MatrixFill::TriDiagonal,
+                3 => MatrixFill::LowerTriangular,
+                4 => MatrixFill::StrictlyLowerTriangular,
+                5 => MatrixFill::UpperTriangular,
+                6 => MatrixFill::StrictlyUpperTriangular,
+                v => return decoder.error(format!("Invalid matrix fill {}.", v))
+            },
+            grad_level: decoder.varint()? as usize,
+            scope: decoder.strings()?,
+            sym_int: if decoder.bool()? { Some(decoder.sym_int()?) } else { None }
+        })
+    }
+}
+
+impl FunctionImporter for BinaryImporter {
+    fn import(&self, io: &mut Read) -> Result<GraphFunction> {
+        let mut bytes = Vec::new();
+        io.read_to_end(&mut bytes).map_err(|e| ErrorKind::Msg(
+            format!("Could not read the graph: {}", e)))?;
+        self.decode_function(&bytes)
+    }
+}
+
+/// Cursor for reading the primitives of the binary GIR format
+#[derive(Debug, Clone)]
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    pos: usize
+}
+
+impl<'a> Decoder<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Decoder {
+            bytes: bytes,
+            pos: 0
+        }
+    }
+
+    pub fn error<T>(&self, msg: String) -> Result<T> {
+        Err(ErrorKind::DecodeError(self.pos, msg).into())
+    }
+
+    pub fn end(&self) -> Result<()> {
+        if self.pos != self.bytes.len() {
+            self.error(format!("Unexpected {} bytes after the end of the graph.",
+                               self.bytes.len() - self.pos))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn header(&mut self, kind: u8) -> Result<()> {
+        if self.bytes.len() < MAGIC.len() || &self.bytes[..MAGIC.len()] != MAGIC {
+            return self.error("The data is not in the binary GIR format.".into())
+        }
+        self.pos = MAGIC.len();
+        let version = self.u8()?;
+        if version != VERSION {
+            return self.error(format!("Unsupported format version {}, expected {}.",
+                                      version, VERSION))
+        }
+        let found = self.u8()?;
+        if found != kind {
+            return self.error(format!("Expected content of kind {}, found {}.", kind, found))
+        }
+        Ok(())
+    }
+
+    pub fn u8(&mut self) -> Result<u8> {
+        match self.bytes.get(self.pos) {
+            Some(&v) => {
+                self.pos += 1;
+                Ok(v)
+            },
+            None => self.error("Unexpected end of data.".into())
+        }
+    }
+
+    pub fn bool(&mut self) -> Result<bool> {
+        match self.u8()? {
+            0 => Ok(false),
+            1 => Ok(true),
+            v => self.error(format!("Invalid boolean {}.", v))
+        }
+    }
+
+    pub fn varint(&mut self) -> Result<u64> {
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift > 63 {
+                return self.error("Variable length integer is too long.".into())
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value)
+            }
+            shift += 7;
+        }
+    }
+
+    pub fn int(&mut self) -> Result<i64> {
+        let value = self.varint()?;
+        Ok(((value >> 1) as i64) ^ -((value & 1) as i64))
+    }
+
+    pub fn f64(&mut self) -> Result<f64> {
+        let mut bits = 0u64;
+        for i in 0..8 {
+            bits |= (self.u8()? as u64) << (8 * i);
+        }
+        Ok(f64::from_bits(bits))
+    }
+
+    pub fn string(&mut self) -> Result<String> {
+        let len = self.varint()? as usize;
+        if len > self.bytes.len() - self.pos {
+            return self.error("Unexpected end of data.".into())
+        }
+        let value = match ::std::str::from_utf8(&self.bytes[self.pos..self.pos + len]) {
+            Ok(s) => s.to_string(),
+            Err(e) => return self.error(format!("Invalid string: {}", e))
+        };
+        self.pos += len;
+        Ok(value)
+    }
+
+    pub fn strings(&mut self) -> Result<Vec<String>> {
+        let len = self.varint()?;
+        let mut values = Vec::new();
+        for _ in 0..len {
+            values.push(self.string()?);
+        }
+        Ok(values)
+    }
+
+    pub fn ids(&mut self) -> Result<Vec<usize>> {
+        let len = self.varint()?;
+        let mut values = Vec::new();
+        for _ in 0..len {
+            values.push(self.varint()? as usize);
+        }
+        Ok(values)
+    }
+
+    pub fn policy(&mut self) -> Result<Policy> {
+        match self.u8()? {
+            0 => Ok(Policy::Quite),
+            1 => Ok(Policy::Warn),
+            2 => Ok(Policy::Raise),
+            v => self.error(format!("Invalid policy {}.", v))
+        }
+    }
+
+    pub fn data_type(&mut self) -> Result<FundamentalType> {
+        match self.u8()? {
+            0 => Ok(FundamentalType::Boolean),
+            1 => Ok(FundamentalType::UnsignedInt),
+            2 => Ok(FundamentalType::SignedInt),
+            3 => Ok(FundamentalType::Float),
+            4 => Ok(FundamentalType::Complex),
+            v => self.error(format!("Invalid data type {}.", v))
+        }
+    }
+
+    pub fn axis(&mut self) -> Result<Axis> {
+        match self.u8()? {
+            0 => Ok(Axis::Axis0),
+            1 => Ok(Axis::Axis1),
+            2 => Ok(Axis::Axis2),
+            3 => Ok(Axis::Axis3),
+            v => self.error(format!("Invalid axis {}.", v))
+        }
+    }
+
+    pub fn sym_int(&mut self) -> Result<SymInt> {
+        let code = self.string()?;
+        match parse_sym_int(&code) {
+            Ok(value) => Ok(value),
+            Err(e) => self.error(format!("{}", e))
+        }
+    }
+
+    pub fn shape(&mut self) -> Result<Shape> {
+        Ok(Shape(self.sym_int()?, self.sym_int()?, self.sym_int()?, self.sym_int()?))
+    }
+
+    pub fn op_arg(&mut self) -> Result<OpArg> {
+        match self.u8()? {
+            TAG_BOOL => Ok(OpArg::Bool(self.bool()?)),
+            TAG_INT => Ok(OpArg::Int(self.int()?)),
+            TAG_FLOAT => Ok(OpArg::Float(self.f64()?)),
+            TAG_STR => Ok(OpArg::Str(self.string()?)),
+            TAG_DATA_TYPE => Ok(OpArg::DataType(self.data_type()?)),
+            TAG_AXIS => Ok(OpArg::Axis(self.axis()?)),
+            TAG_SYM_INT => Ok(OpArg::SymInt(self.sym_int()?)),
+            TAG_SHAPE => Ok(OpArg::Shape(self.shape()?)),
+            TAG_LIST => {
+                let len = self.varint()?;
+                let mut values = Vec::new();
+                for _ in 0..len {
+                    values.push(self.op_arg()?);
+                }
+                Ok(OpArg::List(values))
+            },
+            v => self.error(format!("Invalid operator argument tag {}.", v))
+        }
+    }
+}
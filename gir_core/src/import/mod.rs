@@ -0,0 +1,19 @@
+use graph::*;
+use errors::*;
+use std::io::Read;
+use std::fs::File;
+
+pub trait FunctionImporter {
+    fn import(&self, io: &mut Read) -> Result<GraphFunction>;
+
+    fn import_from_file(&self, path: &str) -> Result<GraphFunction> {
+        let mut file = File::open(path).map_err(|e| ErrorKind::Msg(
+            format!("Could not open file {}: {}", path, e)))?;
+        self.import(&mut file)
+    }
+}
+
+pub mod text;
+pub mod binary;
+pub use self::text::TextImporter;
+pub use self::binary::BinaryImporter;
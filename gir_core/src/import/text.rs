@@ -0,0 +1,520 @@
+use primitives::*;
+use graph::*;
+use ops::*;
+use errors::*;
+use import::FunctionImporter;
+
+use std::io::Read;
+use std::collections::HashMap;
+use symbolic_polynomials::variable;
+
+/// Reads graphs in the textual GIR format produced by the `TextExporter`.
+///
+/// The expression ids in the text are only labels, thus hand written graphs
+/// do not need to be numbered consecutively. The type and shape annotations,
+/// as well as the scope and the name of each expression are optional. If the
+/// type or the shape are present they are verified against the ones inferred
+/// by the operator.
+#[derive(Debug, Clone)]
+pub struct TextImporter {
+    pub registry: OperatorRegistry
+}
+
+impl TextImporter {
+    pub fn new() -> Self {
+        TextImporter::with_registry(OperatorRegistry::default())
+    }
+
+    /// Importer which can reconstruct operators registered outside of the crate
+    pub fn with_registry(registry: OperatorRegistry) -> Self {
+        TextImporter {
+            registry: registry
+        }
+    }
+
+    /// Parses a `GraphFunction` from a string
+    pub fn parse_function(&self, text: &str) -> Result<GraphFunction> {
+        let mut parser = Parser::new(text)?;
+        let gf = parser.function(&self.registry)?;
+        parser.end()?;
+        Ok(gf)
+    }
+
+    /// Parses a `Graph` from a string
+    pub fn parse_graph(&self, text: &str) -> Result<Graph> {
+        let mut parser = Parser::new(text)?;
+        parser.expect_ident("graph")?;
+        let (graph, _) = parser.body(Graph::default(), &self.registry)?;
+        parser.end()?;
+        Ok(graph)
+    }
+}
+
+impl FunctionImporter for TextImporter {
+    fn import(&self, io: &mut Read) -> Result<GraphFunction> {
+        let mut text = String::new();
+        io.read_to_string(&mut text).map_err(|e| ErrorKind::Msg(
+            format!("Could not read the graph: {}", e)))?;
+        self.parse_function(&text)
+    }
+}
+
+/// Parses a symbolic integer expression, such as `2 * n^2 + m - 1`
+pub fn parse_sym_int(text: &str) -> Result<SymInt> {
+    let mut parser = Parser::new(text)?;
+    let value = parser.sym_int()?;
+    parser.end()?;
+    Ok(value)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Id(usize),
+    Ident(String),
+    Number(String),
+    Str(String),
+    Punct(char),
+    Arrow,
+    Pow
+}
+
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '\n' {
+            line += 1;
+            i += 1;
+        } else if c.is_whitespace() {
+            i += 1;
+        } else if c == ';' || c == '#' {
+            // Comment until the end of the line
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '%' {
+            let start = i + 1;
+            i += 1;
+            while i < chars.len() && chars[i].is_digit(10) {
+                i += 1;
+            }
+            let id: String = chars[start..i].iter().cloned().collect();
+            let id = id.parse::<usize>().map_err(|_| ErrorKind::ParseError(
+                line, "Expected an expression id after '%'.".into()))?;
+            tokens.push((Token::Id(id), line));
+        } else if c == '"' {
+            let mut s = String::new();
+            i += 1;
+            loop {
+                if i >= chars.len() {
+                    return Err(ErrorKind::ParseError(line, "Unterminated string.".into()).into())
+                }
+                match chars[i] {
+                    '"' => break,
+                    '\\' if i + 1 < chars.len() => {
+                        i += 1;
+                        s.push(match chars[i] {
+                            'n' => '\n',
+                            't' => '\t',
+                            c => c
+                        });
+                    },
+                    c => s.push(c)
+                }
+                i += 1;
+            }
+            i += 1;
+            tokens.push((Token::Str(s), line));
+        } else if c.is_digit(10) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_digit(10) || chars[i] == '.') {
+                i += 1;
+            }
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                i += 1;
+                if i < chars.len() && (chars[i] == '-' || chars[i] == '+') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i].is_digit(10) {
+                    i += 1;
+                }
+            }
+            tokens.push((Token::Number(chars[start..i].iter().cloned().collect()), line));
+        } else if c.is_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            tokens.push((Token::Ident(chars[start..i].iter().cloned().collect()), line));
+        } else if c == '-' && i + 1 < chars.len() && chars[i + 1] == '>' {
+            tokens.push((Token::Arrow, line));
+            i += 2;
+        } else if c == '*' && i + 1 < chars.len() && chars[i + 1] == '*' {
+            tokens.push((Token::Pow, line));
+            i += 2;
+        } else if c == '^' {
+            tokens.push((Token::Pow, line));
+            i += 1;
+        } else if "=<>()[],:@{}*+-".contains(c) {
+            tokens.push((Token::Punct(c), line));
+            i += 1;
+        } else {
+            return Err(ErrorKind::ParseError(line, format!("Unexpected character '{}'.", c)).into())
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize
+}
+
+impl Parser {
+    fn new(text: &str) -> Result<Self> {
+        Ok(Parser {
+            tokens: tokenize(text)?,
+            pos: 0
+        })
+    }
+
+    fn line(&self) -> usize {
+        self.tokens.get(self.pos).or(self.tokens.last()).map(|&(_, l)| l).unwrap_or(1)
+    }
+
+    fn error<T>(&self, msg: String) -> Result<T> {
+        Err(ErrorKind::ParseError(self.line(), msg).into())
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|&(ref t, _)| t)
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        match self.tokens.get(self.pos).map(|&(ref t, _)| t.clone()) {
+            Some(t) => {
+                self.pos += 1;
+                Ok(t)
+            },
+            None => self.error("Unexpected end of input.".into())
+        }
+    }
+
+    fn accept(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<()> {
+        if self.accept(&Token::Punct(c)) {
+            Ok(())
+        } else {
+            self.error(format!("Expected '{}', found {:?}.", c, self.peek()))
+        }
+    }
+
+    fn expect_ident(&mut self, ident: &str) -> Result<()> {
+        if self.accept(&Token::Ident(ident.into())) {
+            Ok(())
+        } else {
+            self.error(format!("Expected '{}', found {:?}.", ident, self.peek()))
+        }
+    }
+
+    fn end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(t) => self.error(format!("Unexpected {:?} after the end of the graph.", t))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Ident(s) => Ok(s),
+            t => self.error(format!("Expected an identifier, found {:?}.", t))
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.next()? {
+            Token::Str(s) => Ok(s),
+            t => self.error(format!("Expected a string, found {:?}.", t))
+        }
+    }
+
+    fn node_id(&mut self) -> Result<usize> {
+        match self.next()? {
+            Token::Id(id) => Ok(id),
+            t => self.error(format!("Expected an expression id, found {:?}.", t))
+        }
+    }
+
+    fn integer(&mut self) -> Result<i64> {
+        let negative = self.accept(&Token::Punct('-'));
+        match self.next()? {
+            Token::Number(ref s) => match s.parse::<i64>() {
+                Ok(v) => Ok(if negative { -v } else { v }),
+                Err(_) => self.error(format!("Expected an integer, found {}.", s))
+            },
+            t => self.error(format!("Expected an integer, found {:?}.", t))
+        }
+    }
+
+    fn data_type(&mut self) -> Result<FundamentalType> {
+        match self.ident()?.as_ref() {
+            "Boolean" => Ok(FundamentalType::Boolean),
+            "UnsignedInt" => Ok(FundamentalType::UnsignedInt),
+            "SignedInt" => Ok(FundamentalType::SignedInt),
+            "Float" => Ok(FundamentalType::Float),
+            "Complex" => Ok(FundamentalType::Complex),
+            s => self.error(format!("Unknown data type {}.", s))
+        }
+    }
+
+    /// Parses a comma separated list enclosed in the delimiters
+    fn list<T, F>(&mut self, open: char, close: char, mut f: F) -> Result<Vec<T>>
+        where F: FnMut(&mut Parser) -> Result<T> {
+        self.expect(open)?;
+        let mut values = Vec::new();
+        if self.accept(&Token::Punct(close)) {
+            return Ok(values)
+        }
+        loop {
+            values.push(f(self)?);
+            if self.accept(&Token::Punct(close)) {
+                return Ok(values)
+            }
+            self.expect(',')?;
+        }
+    }
+
+    fn shape(&mut self) -> Result<Shape> {
+        let mut dims = self.list('(', ')', |p| p.sym_int())?;
+        if dims.len() != 4 {
+            return self.error(format!("Expected a shape with 4 dimensions, found {}.", dims.len()))
+        }
+        let dim3 = dims.pop().unwrap();
+        let dim2 = dims.pop().unwrap();
+        let dim1 = dims.pop().unwrap();
+        let dim0 = dims.pop().unwrap();
+        Ok(Shape(dim0, dim1, dim2, dim3))
+    }
+
+    fn sym_int(&mut self) -> Result<SymInt> {
+        let mut value = self.sym_int_term()?;
+        loop {
+            if self.accept(&Token::Punct('+')) {
+                value += &self.sym_int_term()?;
+            } else if self.accept(&Token::Punct('-')) {
+                let minus_one: SymInt = (-1 as i64).into();
+                value += &(&minus_one * &self.sym_int_term()?);
+            } else {
+                return Ok(value)
+            }
+        }
+    }
+
+    fn sym_int_term(&mut self) -> Result<SymInt> {
+        let mut value = self.sym_int_factor()?;
+        while self.accept(&Token::Punct('*')) {
+            value = &value * &self.sym_int_factor()?;
+        }
+        Ok(value)
+    }
+
+    fn sym_int_factor(&mut self) -> Result<SymInt> {
+        if self.accept(&Token::Punct('-')) {
+            let minus_one: SymInt = (-1 as i64).into();
+            return Ok(&minus_one * &self.sym_int_factor()?)
+        }
+        let base: SymInt = match self.next()? {
+            Token::Number(ref s) => match s.parse::<i64>() {
+                Ok(v) => v.into(),
+                Err(_) => return self.error(format!("Expected an integer, found {}.", s))
+            },
+            Token::Ident(s) => variable(s),
+            Token::Punct('(') => {
+                let value = self.sym_int()?;
+                self.expect(')')?;
+                value
+            },
+            t => return self.error(format!("Expected a symbolic integer, found {:?}.", t))
+        };
+        if self.accept(&Token::Pow) {
+            let power = self.integer()?;
+            if power < 0 {
+                return self.error(format!("Negative powers are not allowed, found {}.", power))
+            }
+            let mut value: SymInt = (1 as i64).into();
+            for _ in 0..power {
+                value = &value * &base;
+            }
+            Ok(value)
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn number(&mut self) -> Result<OpArg> {
+        let negative = self.accept(&Token::Punct('-'));
+        let sign = if negative { -1.0 } else { 1.0 };
+        match self.next()? {
+            Token::Number(ref s) => match s.parse::<i64>() {
+                Ok(v) => Ok(OpArg::Int(if negative { -v } else { v })),
+                Err(_) => match s.parse::<f64>() {
+                    Ok(v) => Ok(OpArg::Float(sign * v)),
+                    Err(_) => self.error(format!("Expected a number, found {}.", s))
+                }
+            },
+            Token::Ident(ref s) if s == "inf" => Ok(OpArg::Float(sign * ::std::f64::INFINITY)),
+            Token::Ident(ref s) if s == "NaN" => Ok(OpArg::Float(::std::f64::NAN)),
+            t => self.error(format!("Expected a number, found {:?}.", t))
+        }
+    }
+
+    /// Parses a single operator argument
+    fn op_arg(&mut self) -> Result<OpArg> {
+        match self.peek().cloned() {
+            Some(Token::Str(_)) => Ok(OpArg::Str(self.string()?)),
+            Some(Token::Punct('[')) => Ok(OpArg::List(self.list('[', ']', |p| p.op_arg())?)),
+            Some(Token::Punct('(')) => Ok(OpArg::Shape(self.shape()?)),
+            Some(Token::Punct('-')) | Some(Token::Number(_)) => self.number(),
+            Some(Token::Ident(ref s)) => match s.as_ref() {
+                "true" | "false" => {
+                    self.pos += 1;
+                    Ok(OpArg::Bool(s == "true"))
+                },
+                "inf" | "NaN" => self.number(),
+                "sym" => {
+                    self.pos += 1;
+                    self.expect('(')?;
+                    let value = self.sym_int()?;
+                    self.expect(')')?;
+                    Ok(OpArg::SymInt(value))
+                },
+                _ => Ok(OpArg::DataType(self.data_type()?))
+            },
+            t => self.error(format!("Expected an operator argument, found {:?}.", t))
+        }
+    }
+
+    fn operator(&mut self, name: &str, registry: &OperatorRegistry) -> Result<Box<Operator>> {
+        let args = if self.accept(&Token::Punct('<')) {
+            let mut args = vec![self.op_arg()?];
+            while self.accept(&Token::Punct(',')) {
+                args.push(self.op_arg()?);
+            }
+            self.expect('>')?;
+            args
+        } else {
+            Vec::new()
+        };
+        match registry.decode(name, &args) {
+            Ok(op) => Ok(op),
+            Err(e) => self.error(format!("{}", e))
+        }
+    }
+
+    /// Parses a single expression and adds it to the graph
+    fn expr(&mut self, graph: &mut Graph, labels: &mut HashMap<usize, usize>,
+            registry: &OperatorRegistry) -> Result<()> {
+        let label = self.node_id()?;
+        if labels.contains_key(&label) {
+            return self.error(format!("The expression %{} is defined twice.", label))
+        }
+        self.expect('=')?;
+        let name = self.ident()?;
+        let op = self.operator(&name, registry)?;
+        let mut ancestors = Vec::new();
+        for a in self.list('(', ')', |p| p.node_id())? {
+            match labels.get(&a) {
+                Some(&id) => ancestors.push(id),
+                None => return self.error(format!("The expression %{} is used before \
+                it is defined.", a))
+            }
+        }
+        let mut data = if op.get_meta().arity == Arity::Nullary {
+            if ancestors.len() > 0 {
+                return self.error(format!("The operator {} does not take any arguments.", name))
+            }
+            op.apply_null()
+        } else {
+            op.apply(graph, ancestors)?
+        };
+        // Verify the type and shape if they are provided
+        if self.accept(&Token::Punct(':')) {
+            let data_type = self.data_type()?;
+            if data_type != data.data_type {
+                return self.error(format!("The expression %{} has type {}, \
+                but {} was inferred.", label, data_type, data.data_type))
+            }
+            let shape = self.shape()?;
+            if shape != data.shape {
+                return self.error(format!("The expression %{} has shape {}, \
+                but {} was inferred.", label, shape, data.shape))
+            }
+        }
+        let scope = if self.accept(&Token::Punct('@')) {
+            self.list('[', ']', |p| p.string())?
+        } else {
+            Vec::new()
+        };
+        if let Some(&Token::Str(_)) = self.peek() {
+            data.name = self.string()?;
+        }
+        let init_scope = ::std::mem::replace(&mut graph.scope, scope);
+        let id = graph.add_node(data);
+        graph.scope = init_scope;
+        labels.insert(label, id?);
+        Ok(())
+    }
+
+    /// Parses all expressions between curly brackets
+    fn body(&mut self, mut graph: Graph, registry: &OperatorRegistry)
+            -> Result<(Graph, HashMap<usize, usize>)> {
+        let mut labels = HashMap::new();
+        self.expect('{')?;
+        while !self.accept(&Token::Punct('}')) {
+            self.expr(&mut graph, &mut labels, registry)?;
+        }
+        Ok((graph, labels))
+    }
+
+    fn function(&mut self, registry: &OperatorRegistry) -> Result<GraphFunction> {
+        self.expect_ident("function")?;
+        let name = self.string()?;
+        let inputs = self.list('(', ')', |p| p.node_id())?;
+        if !self.accept(&Token::Arrow) {
+            return self.error(format!("Expected '->', found {:?}.", self.peek()))
+        }
+        let outputs = self.list('(', ')', |p| p.node_id())?;
+        let (graph, labels) = self.body(Graph::default(), registry)?;
+        let map = |ids: Vec<usize>| -> Result<Vec<usize>> {
+            let mut mapped = Vec::with_capacity(ids.len());
+            for id in ids {
+                match labels.get(&id) {
+                    Some(&v) => mapped.push(v),
+                    None => return Err(ErrorKind::Msg(format!(
+                        "The expression %{} of the function signature is not defined.", id)).into())
+                }
+            }
+            Ok(mapped)
+        };
+        let inputs = map(inputs)?;
+        let outputs = map(outputs)?;
+        for &id in &inputs {
+            if graph.nodes[id].op.get_meta().name != "Input" {
+                return Err(ErrorKind::Msg(format!("The function input %{} is not an \
+                Input expression.", id)).into())
+            }
+        }
+        Ok(GraphFunction::from_graph(graph, inputs, outputs, name))
+    }
+}
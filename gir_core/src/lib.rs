@@ -10,6 +10,7 @@ pub mod primitives;
 pub mod errors;
 pub mod props;
 pub mod graph;
+pub mod verify;
 
 pub mod ops;
 #[macro_use]
@@ -17,10 +18,13 @@ pub mod api;
 pub mod derivative;
 pub mod utils;
 pub mod export;
+pub mod import;
+pub mod passes;
 pub mod backend;
 
 pub use primitives::*;
 pub use graph::*;
+pub use verify::GraphIssue;
 pub use backend::*;
 
 use std::collections::HashMap;
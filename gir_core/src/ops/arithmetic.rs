@@ -17,6 +17,17 @@ impl Operator for Add {
             .map(|id| (*id, dx)).collect())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        let dx: Vec<usize> = dx.iter().filter_map(|&d| d).collect();
+        match dx.len() {
+            0 => Ok(None),
+            1 => Ok(Some(dx[0])),
+            _ => Ok(Some(ids::add(g, dx)?))
+        }
+    }
+
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
@@ -27,6 +38,10 @@ impl Operator for Add {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static ADD: OperatorMetaData = OperatorMetaData{
             name: "Add",
@@ -40,6 +55,7 @@ impl Operator for Add {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &ADD
@@ -62,6 +78,15 @@ impl Operator for Neg {
         }
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        match dx[0] {
+            Some(d) => Ok(Some(ids::neg(g, d)?)),
+            None => Ok(None)
+        }
+    }
+
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
@@ -77,6 +102,10 @@ impl Operator for Neg {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static NEG: OperatorMetaData = OperatorMetaData{
             name: "Neg",
@@ -90,6 +119,7 @@ impl Operator for Neg {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &NEG
@@ -119,7 +149,39 @@ impl Operator for Mul {
                 }
                 Ok(result)
             },
-            _ => unimplemented!()
+            _ => {
+                // The derivative of each parent is the product of all of the other parents
+                let mut result = Vec::new();
+                let ids = g.get_node(x)?.ancestors.clone();
+                for (i, &id) in ids.iter().enumerate() {
+                    if flow_tree[id] {
+                        let mut factors = ids.clone();
+                        factors[i] = dx;
+                        result.push((id, ids::mul(g, factors)?));
+                    }
+                }
+                Ok(result)
+            }
+        }
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // Product rule - the sum of the products with one factor replaced by its derivative
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut terms = Vec::new();
+        for (i, d) in dx.iter().enumerate() {
+            if let Some(d) = *d {
+                let mut factors = anc.clone();
+                factors[i] = d;
+                terms.push(ids::mul(g, factors)?);
+            }
+        }
+        match terms.len() {
+            0 => Ok(None),
+            1 => Ok(Some(terms[0])),
+            _ => Ok(Some(ids::add(g, terms)?))
         }
     }
 
@@ -133,6 +195,10 @@ impl Operator for Mul {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static MUL: OperatorMetaData = OperatorMetaData{
             name: "Mul",
@@ -146,6 +212,7 @@ impl Operator for Mul {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &MUL
@@ -169,6 +236,19 @@ impl Operator for Div {
         }
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        match dx[0] {
+            Some(d) => {
+                let data_type = g.get_node(x)?.data_type;
+                let minus_one = g.scalar(-1.0, data_type);
+                Ok(Some(ids::mul(g, vec![d, x, x, minus_one])?))
+            },
+            None => Ok(None)
+        }
+    }
+
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
@@ -184,6 +264,10 @@ impl Operator for Div {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static DIV: OperatorMetaData = OperatorMetaData{
             name: "Div",
@@ -197,8 +281,385 @@ impl Operator for Div {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &DIV
     }
 }
+
+/// The partial derivative of an elementwise operator `x` with respect to its
+/// `i`-th argument
+type Partial = fn(&mut Graph, usize, &[usize], usize) -> Result<usize>;
+
+/// Multiplies `dx` by the partial derivative for every argument in the flow tree
+fn reverse_partials(g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>, partial: Partial)
+                    -> Result<Vec<(usize, usize)>> {
+    let anc = g.get_node(x)?.ancestors.clone();
+    let mut result = Vec::new();
+    for (i, &id) in anc.iter().enumerate() {
+        if flow_tree[id] {
+            let dp = partial(g, x, &anc, i)?;
+            result.push((id, ids::mul(g, vec![dx, dp])?));
+        }
+    }
+    Ok(result)
+}
+
+/// The sum of the derivatives of the arguments multiplied by the partial derivatives
+fn forward_partials(g: &mut Graph, x: usize, dx: &Vec<Option<usize>>, partial: Partial)
+                    -> Result<Option<usize>> {
+    let anc = g.get_node(x)?.ancestors.clone();
+    let mut terms = Vec::new();
+    for (i, d) in dx.iter().enumerate() {
+        if let Some(d) = *d {
+            let dp = partial(g, x, &anc, i)?;
+            terms.push(ids::mul(g, vec![d, dp])?);
+        }
+    }
+    match terms.len() {
+        0 => Ok(None),
+        1 => Ok(Some(terms[0])),
+        _ => Ok(Some(ids::add(g, terms)?))
+    }
+}
+
+/// `(1 + sign(a - b)) / 2`, which is 1 where `a > b`, 0 where `a < b` and 1/2 where
+/// they are equal.
+fn step(g: &mut Graph, a: usize, b: usize) -> Result<usize> {
+    let one = g.scalar(1.0, FundamentalType::Float);
+    let half = g.scalar(0.5, FundamentalType::Float);
+    let diff = ids::sub(g, a, b)?;
+    let sign = ids::sign(g, diff)?;
+    let shifted = ids::add(g, vec![one, sign])?;
+    ids::mul(g, vec![half, shifted])
+}
+
+fn verify_non_boolean(meta: &OperatorMetaData, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+    let args = default::verify_args(meta, g, args)?;
+    for &arg in &args {
+        if g.get_node(arg)?.data_type == FundamentalType::Boolean {
+            return Err(ErrorKind::InvalidArguments(
+                String::new() + meta.name, args,
+                "Not applicable to boolean expressions.".into()).into())
+        }
+    }
+    Ok(default::broadcast_shapes(g, meta.name, args)?)
+}
+
+/// Raises the first argument to the power of the second.
+#[derive(Debug, Clone)]
+pub struct Pow {}
+
+impl Pow {
+    fn partial(g: &mut Graph, x: usize, anc: &[usize], i: usize) -> Result<usize> {
+        if i == 0 {
+            // b a^(b - 1)
+            let one = g.scalar(1.0, FundamentalType::Float);
+            let exponent = ids::sub(g, anc[1], one)?;
+            let power = ids::pow(g, anc[0], exponent)?;
+            ids::mul(g, vec![anc[1], power])
+        } else {
+            // a^b log(a)
+            let log = ids::log(g, anc[0])?;
+            ids::mul(g, vec![x, log])
+        }
+    }
+}
+
+impl Operator for Pow {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        reverse_partials(g, x, dx, flow_tree, Pow::partial)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        forward_partials(g, x, dx, Pow::partial)
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        verify_non_boolean(self.get_meta(), g, args)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static POW: OperatorMetaData = OperatorMetaData{
+            name: "Pow",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 2,
+            ordered_parents: true,
+            elementwise: true,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &POW
+    }
+}
+
+/// The elementwise maximum of two arguments. Where they are equal the
+/// subgradient is split evenly between them.
+#[derive(Debug, Clone)]
+pub struct Maximum {}
+
+impl Maximum {
+    #[allow(unused_variables)]
+    fn partial(g: &mut Graph, x: usize, anc: &[usize], i: usize) -> Result<usize> {
+        step(g, anc[i], anc[1 - i])
+    }
+}
+
+impl Operator for Maximum {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        reverse_partials(g, x, dx, flow_tree, Maximum::partial)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        forward_partials(g, x, dx, Maximum::partial)
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        Ok(default::broadcast_shapes(g, meta.name, args)?)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static MAXIMUM: OperatorMetaData = OperatorMetaData{
+            name: "Maximum",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 2,
+            ordered_parents: false,
+            elementwise: true,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &MAXIMUM
+    }
+}
+
+/// The elementwise minimum of two arguments. Where they are equal the
+/// subgradient is split evenly between them.
+#[derive(Debug, Clone)]
+pub struct Minimum {}
+
+impl Minimum {
+    #[allow(unused_variables)]
+    fn partial(g: &mut Graph, x: usize, anc: &[usize], i: usize) -> Result<usize> {
+        step(g, anc[1 - i], anc[i])
+    }
+}
+
+impl Operator for Minimum {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        reverse_partials(g, x, dx, flow_tree, Minimum::partial)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        forward_partials(g, x, dx, Minimum::partial)
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        Ok(default::broadcast_shapes(g, meta.name, args)?)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static MINIMUM: OperatorMetaData = OperatorMetaData{
+            name: "Minimum",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 2,
+            ordered_parents: false,
+            elementwise: true,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &MINIMUM
+    }
+}
+
+/// The angle of the point `(x, y)` for the arguments `y` and `x`, in that order.
+#[derive(Debug, Clone)]
+pub struct Atan2 {}
+
+impl Atan2 {
+    #[allow(unused_variables)]
+    fn partial(g: &mut Graph, x: usize, anc: &[usize], i: usize) -> Result<usize> {
+        // x / (x^2 + y^2) and -y / (x^2 + y^2)
+        let y_squared = ids::mul(g, vec![anc[0], anc[0]])?;
+        let x_squared = ids::mul(g, vec![anc[1], anc[1]])?;
+        let norm = ids::add(g, vec![x_squared, y_squared])?;
+        let inv_norm = ids::reciprocal(g, norm)?;
+        if i == 0 {
+            ids::mul(g, vec![anc[1], inv_norm])
+        } else {
+            let minus_y = ids::neg(g, anc[0])?;
+            ids::mul(g, vec![minus_y, inv_norm])
+        }
+    }
+}
+
+impl Operator for Atan2 {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        reverse_partials(g, x, dx, flow_tree, Atan2::partial)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        forward_partials(g, x, dx, Atan2::partial)
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        verify_non_boolean(self.get_meta(), g, args)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static ATAN2: OperatorMetaData = OperatorMetaData{
+            name: "Atan2",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 2,
+            ordered_parents: true,
+            elementwise: true,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &ATAN2
+    }
+}
+
+/// Limits the first argument to the interval given by the second and the third,
+/// as `min(max(a, low), high)`. At either bound the subgradient is split evenly
+/// between the argument and the bound.
+#[derive(Debug, Clone)]
+pub struct Clamp {}
+
+impl Clamp {
+    #[allow(unused_variables)]
+    fn partial(g: &mut Graph, x: usize, anc: &[usize], i: usize) -> Result<usize> {
+        match i {
+            0 => {
+                let above_low = step(g, anc[0], anc[1])?;
+                let below_high = step(g, anc[2], anc[0])?;
+                ids::mul(g, vec![above_low, below_high])
+            },
+            1 => step(g, anc[1], anc[0]),
+            _ => step(g, anc[0], anc[2])
+        }
+    }
+}
+
+impl Operator for Clamp {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        reverse_partials(g, x, dx, flow_tree, Clamp::partial)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        forward_partials(g, x, dx, Clamp::partial)
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        Ok(default::broadcast_shapes(g, meta.name, args)?)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static CLAMP: OperatorMetaData = OperatorMetaData{
+            name: "Clamp",
+            arity: Arity::Ternary,
+            num_outputs: 1,
+            differential_parents: 3,
+            ordered_parents: true,
+            elementwise: true,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &CLAMP
+    }
+}
@@ -0,0 +1,585 @@
+use ops::interface::*;
+use primitives::*;
+use graph::*;
+use errors::*;
+use backend::{BackendPrecisions, symbolic_le};
+use api::ids;
+use std::any::Any;
+
+/// The elements `start, start + step, ...` before `stop` along a single axis.
+/// The bounds can be symbolic, but the step is always a positive constant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SliceRange {
+    pub start: SymInt,
+    pub stop: SymInt,
+    pub step: i64
+}
+
+impl SliceRange {
+    pub fn new(start: SymInt, stop: SymInt, step: i64) -> Self {
+        SliceRange {
+            start: start,
+            stop: stop,
+            step: step
+        }
+    }
+
+    /// The number of elements in the range. With a step larger than one this
+    /// can be computed only if `stop - start` is a constant.
+    pub fn length(&self) -> Result<SymInt> {
+        let mut length = self.stop.clone();
+        length -= &self.start;
+        if self.step == 1 {
+            Ok(length)
+        } else if self.step > 1 && length.is_constant() {
+            let length = length.eval(&::std::collections::HashMap::new()).unwrap();
+            Ok(((length + self.step - 1) / self.step).into())
+        } else {
+            Err(ErrorKind::Msg(format!("Can not compute the length of a range from {} to {} \
+            with step {}.", self.start.to_code(&|x: String| x), self.stop.to_code(&|x: String| x),
+                                       self.step)).into())
+        }
+    }
+}
+
+/// Whether `a > b` for all positive values of the symbolic variables
+fn symbolic_gt(a: &SymInt, b: &SymInt) -> bool {
+    let mut b_plus_one = b.clone();
+    b_plus_one += &1.into();
+    symbolic_le(&b_plus_one, a)
+}
+
+/// A tensor of the same shape and type as `x` filled with zeros
+fn zeros_like(g: &mut Graph, x: usize) -> Result<usize> {
+    let data_type = g.get_node(x)?.data_type;
+    let zero = g.scalar(0.0, data_type);
+    ids::broadcast_to(g, zero, x)
+}
+
+fn verify_ranges(name: &str, g: &Graph, args: &[usize], ranges: &[Option<SliceRange>; 4]) -> Result<()> {
+    let ref shape = g.get_node(args[0])?.shape;
+    for &axis in Axis::iter() {
+        if let Some(ref range) = ranges[axis as usize] {
+            let length = range.length().map_err(|err| Error::from(ErrorKind::InvalidArguments(
+                String::new() + name, args.to_vec(), format!("{}", err))))?;
+            let zero: SymInt = 0.into();
+            if symbolic_gt(&zero, &range.start) || symbolic_gt(&zero, &length) ||
+                symbolic_gt(&range.stop, shape.get(axis)) {
+                return Err(ErrorKind::InvalidArguments(
+                    String::new() + name, args.to_vec(),
+                    format!("The range {:?} is out of bounds for axis {} of shape {}.",
+                            range, axis, shape)).into())
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The ranges as part of the key of an operator
+fn ranges_key(ranges: &[Option<SliceRange>; 4]) -> String {
+    let ranges: Vec<String> = ranges.iter().map(|range| match *range {
+        Some(ref range) => format!("({}, {}, {})", range.start.to_code(&|x: String| x),
+                                   range.stop.to_code(&|x: String| x), range.step),
+        None => "()".into()
+    }).collect();
+    format!("[{}]", ranges.join(", "))
+}
+
+/// The shape after slicing with the ranges
+fn sliced_shape(mut shape: Shape, ranges: &[Option<SliceRange>; 4]) -> Shape {
+    for &axis in Axis::iter() {
+        if let Some(ref range) = ranges[axis as usize] {
+            shape.set(axis, range.length().unwrap());
+        }
+    }
+    shape
+}
+
+/// Takes the elements in the range of every axis which has one, while any
+/// other axis is kept whole.
+#[derive(Debug, Clone)]
+pub struct Slice {
+    pub ranges: [Option<SliceRange>; 4]
+}
+
+impl Operator for Slice {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let ancestor = g.get_node(x)?.ancestors[0];
+        if flow_tree[ancestor] {
+            let zeros = zeros_like(g, ancestor)?;
+            Ok(vec![(ancestor, ids::slice_add(g, zeros, dx, self.ranges.clone())?)])
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        match dx[0] {
+            Some(d) => Ok(Some(ids::slice(g, d, self.ranges.clone())?)),
+            None => Ok(None)
+        }
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        verify_ranges(meta.name, g, &args, &self.ranges)?;
+        Ok(args)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Slice{}", ranges_key(&self.ranges)))
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new(self.ranges.clone()))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static SLICE: OperatorMetaData = OperatorMetaData{
+            name: "Slice",
+            arity: Arity::Unary,
+            num_outputs: 1,
+            differential_parents: 1,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: true,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &SLICE
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        sliced_shape(g.get_node(args[0]).unwrap().shape.clone(), &self.ranges)
+    }
+
+    /// Only copies the elements in the ranges
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let bytes = default::get_bytes(g, x, precisions);
+        OperatorCost {
+            flops: 0.into(),
+            bytes_read: bytes.clone(),
+            bytes_written: bytes
+        }
+    }
+}
+
+/// Adds the second argument to the elements of the first one in the ranges,
+/// such that it has the shape of the `Slice` of the first argument.
+#[derive(Debug, Clone)]
+pub struct SliceAdd {
+    pub ranges: [Option<SliceRange>; 4]
+}
+
+impl Operator for SliceAdd {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut result = Vec::new();
+        if flow_tree[anc[0]] {
+            result.push((anc[0], dx));
+        }
+        if flow_tree[anc[1]] {
+            result.push((anc[1], ids::slice(g, dx, self.ranges.clone())?));
+        }
+        Ok(result)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        match (dx[0], dx[1]) {
+            (None, None) => Ok(None),
+            (Some(d), None) => Ok(Some(d)),
+            (d0, Some(d1)) => {
+                let d0 = match d0 {
+                    Some(d) => d,
+                    None => zeros_like(g, anc[0])?
+                };
+                Ok(Some(ids::slice_add(g, d0, d1, self.ranges.clone())?))
+            }
+        }
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        verify_ranges(meta.name, g, &args, &self.ranges)?;
+        let expected = sliced_shape(g.get_node(args[0])?.shape.clone(), &self.ranges);
+        if g.get_node(args[1])?.shape != expected {
+            return Err(ErrorKind::InvalidShapes(
+                String::new() + meta.name,
+                format!("{}", expected),
+                format!("{}", g.get_node(args[1])?.shape)).into())
+        }
+        Ok(args)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("SliceAdd{}", ranges_key(&self.ranges)))
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new(self.ranges.clone()))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static SLICE_ADD: OperatorMetaData = OperatorMetaData{
+            name: "SliceAdd",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 2,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &SLICE_ADD
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        g.get_node(args[0]).unwrap().shape.clone()
+    }
+
+    /// One operation per element of the update
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let mut cost = default::get_memory_cost(g, x, precisions);
+        cost.flops = g.nodes[g.nodes[x].ancestors[1]].shape.elements();
+        cost
+    }
+}
+
+/// Verifies that the indices are an integer scalar or vector
+fn verify_indices(name: &str, g: &Graph, args: &[usize], indices: usize) -> Result<()> {
+    let node = g.get_node(indices)?;
+    match node.data_type {
+        FundamentalType::UnsignedInt | FundamentalType::SignedInt => {},
+        data_type => return Err(ErrorKind::InvalidArguments(
+            String::new() + name, args.to_vec(),
+            format!("The indices must be integers, but are {}.", data_type)).into())
+    }
+    if node.shape.1 != 1 || node.shape.2 != 1 || node.shape.3 != 1 {
+        return Err(ErrorKind::InvalidArguments(
+            String::new() + name, args.to_vec(),
+            format!("The indices must be a vector, but have shape {}.", node.shape)).into())
+    }
+    Ok(())
+}
+
+/// The shape of `arg` with its `axis` replaced by the number of indices
+fn gathered_shape(g: &Graph, arg: usize, indices: usize, axis: Axis) -> Shape {
+    let mut shape = g.get_node(arg).unwrap().shape.clone();
+    shape.set(axis, g.get_node(indices).unwrap().shape.0.clone());
+    shape
+}
+
+/// Takes the subtensors at each of the indices along the axis, where the
+/// indices are an integer vector. The result has as many elements along the
+/// axis as there are indices.
+///
+/// Index tensors of higher order are not supported, as their result would need
+/// more than the four axes of a tensor.
+#[derive(Debug, Clone)]
+pub struct Gather {
+    pub axis: Axis
+}
+
+impl Operator for Gather {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        if flow_tree[anc[0]] {
+            let zeros = zeros_like(g, anc[0])?;
+            Ok(vec![(anc[0], ids::scatter_add(g, zeros, anc[1], dx, self.axis)?)])
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // The indices are not differentiable
+        match dx[0] {
+            Some(d) => {
+                let indices = g.get_node(x)?.ancestors[1];
+                Ok(Some(ids::gather(g, d, indices, self.axis)?))
+            },
+            None => Ok(None)
+        }
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        verify_indices(meta.name, g, &args, args[1])?;
+        Ok(args)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Gather({})", self.axis))
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new(self.axis))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static GATHER: OperatorMetaData = OperatorMetaData{
+            name: "Gather",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 1,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: true,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &GATHER
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        gathered_shape(g, args[0], args[1], self.axis)
+    }
+
+    /// Only copies the gathered elements
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let bytes = default::get_bytes(g, x, precisions);
+        OperatorCost {
+            flops: 0.into(),
+            bytes_read: bytes.clone(),
+            bytes_written: bytes
+        }
+    }
+}
+
+/// Verifies the arguments of `Scatter` and `ScatterAdd`
+fn verify_scatter(meta: &OperatorMetaData, g: &mut Graph, args: Vec<usize>, axis: Axis)
+                  -> Result<Vec<usize>> {
+    let args = default::verify_args(meta, g, args)?;
+    verify_indices(meta.name, g, &args, args[1])?;
+    let expected = gathered_shape(g, args[0], args[1], axis);
+    if g.get_node(args[2])?.shape != expected {
+        return Err(ErrorKind::InvalidShapes(
+            String::new() + meta.name,
+            format!("{}", expected),
+            format!("{}", g.get_node(args[2])?.shape)).into())
+    }
+    Ok(args)
+}
+
+/// Replaces the subtensors of the first argument at each of the indices along
+/// the axis with the corresponding subtensor of the updates, which have the
+/// shape of the `Gather` of the first argument. The indices are assumed to be
+/// unique, otherwise it is unspecified which of the updates is used.
+#[derive(Debug, Clone)]
+pub struct Scatter {
+    pub axis: Axis
+}
+
+impl Operator for Scatter {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut result = Vec::new();
+        if flow_tree[anc[0]] {
+            // The overwritten elements do not contribute to the result
+            let zeros = zeros_like(g, anc[2])?;
+            result.push((anc[0], ids::scatter(g, dx, anc[1], zeros, self.axis)?));
+        }
+        if flow_tree[anc[2]] {
+            result.push((anc[2], ids::gather(g, dx, anc[1], self.axis)?));
+        }
+        Ok(result)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        if dx[0].is_none() && dx[2].is_none() {
+            return Ok(None)
+        }
+        let d0 = match dx[0] {
+            Some(d) => d,
+            None => zeros_like(g, anc[0])?
+        };
+        let d2 = match dx[2] {
+            Some(d) => d,
+            None => zeros_like(g, anc[2])?
+        };
+        Ok(Some(ids::scatter(g, d0, anc[1], d2, self.axis)?))
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        verify_scatter(self.get_meta(), g, args, self.axis)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Scatter({})", self.axis))
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new(self.axis))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static SCATTER: OperatorMetaData = OperatorMetaData{
+            name: "Scatter",
+            arity: Arity::Ternary,
+            num_outputs: 1,
+            differential_parents: 3,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: true,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &SCATTER
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        g.get_node(args[0]).unwrap().shape.clone()
+    }
+
+    /// Copies the first argument and the updates
+    #[allow(unused_variables, unused_mut)]
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        default::get_memory_cost(g, x, precisions)
+    }
+}
+
+/// Adds the subtensors of the updates to the first argument at each of the
+/// indices along the axis, where the updates have the shape of the `Gather` of
+/// the first argument. Updates at repeated indices are accumulated.
+#[derive(Debug, Clone)]
+pub struct ScatterAdd {
+    pub axis: Axis
+}
+
+impl Operator for ScatterAdd {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut result = Vec::new();
+        if flow_tree[anc[0]] {
+            result.push((anc[0], dx));
+        }
+        if flow_tree[anc[2]] {
+            result.push((anc[2], ids::gather(g, dx, anc[1], self.axis)?));
+        }
+        Ok(result)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        match (dx[0], dx[2]) {
+            (None, None) => Ok(None),
+            (Some(d), None) => Ok(Some(d)),
+            (d0, Some(d2)) => {
+                let d0 = match d0 {
+                    Some(d) => d,
+                    None => zeros_like(g, anc[0])?
+                };
+                Ok(Some(ids::scatter_add(g, d0, anc[1], d2, self.axis)?))
+            }
+        }
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        verify_scatter(self.get_meta(), g, args, self.axis)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("ScatterAdd({})", self.axis))
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new(self.axis))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static SCATTER_ADD: OperatorMetaData = OperatorMetaData{
+            name: "ScatterAdd",
+            arity: Arity::Ternary,
+            num_outputs: 1,
+            differential_parents: 3,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: true,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &SCATTER_ADD
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        g.get_node(args[0]).unwrap().shape.clone()
+    }
+
+    /// One operation per element of the updates
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let mut cost = default::get_memory_cost(g, x, precisions);
+        cost.flops = g.nodes[g.nodes[x].ancestors[2]].shape.elements();
+        cost
+    }
+}
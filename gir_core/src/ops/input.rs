@@ -19,6 +19,12 @@ impl Operator for Input {
         Ok(Vec::new())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
     fn apply_null(&self) -> ExprData {
         ExprData{
             id: 0,
@@ -47,6 +53,10 @@ impl Operator for Input {
         Some(Box::new((self.data_type, self.shape.clone())))
     }
 
+    fn get_key(&self) -> Option<String> {
+        None
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static INPUT: OperatorMetaData = OperatorMetaData{
             name: "Input",
@@ -60,6 +70,7 @@ impl Operator for Input {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &INPUT
@@ -96,6 +107,12 @@ impl Operator for Parameter {
         Ok(Vec::new())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
     fn apply_null(&self) -> ExprData {
         ExprData{
             id: 0,
@@ -124,6 +141,10 @@ impl Operator for Parameter {
         Some(Box::new((self.data_type, self.shape.clone(), self.param_name.clone())))
     }
 
+    fn get_key(&self) -> Option<String> {
+        None
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static PARAMETER: OperatorMetaData = OperatorMetaData{
             name: "Parameter",
@@ -137,6 +158,7 @@ impl Operator for Parameter {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &PARAMETER
@@ -172,6 +194,12 @@ impl Operator for Scalar {
         Ok(Vec::new())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
     fn apply_null(&self) -> ExprData {
         ExprData{
             id: 0,
@@ -196,6 +224,10 @@ impl Operator for Scalar {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Scalar({:x}, {})", self.value.to_bits(), self.data_type))
+    }
+
     fn get_args(&self) -> Option<Box<Any>> {
         Some(Box::new((self.value, self.data_type)))
     }
@@ -213,6 +245,7 @@ impl Operator for Scalar {
             differentiable: false,
             scalar_output: true,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &SCALAR
@@ -223,6 +256,97 @@ impl Operator for Scalar {
     }
 }
 
+/// A constant tensor of zeros with a single one at the linear `index`,
+/// where the first axis is the fastest changing
+#[derive(Debug, Clone)]
+pub struct Basis {
+    pub index: usize,
+    pub shape: Shape,
+    pub data_type: FundamentalType
+}
+
+impl Operator for Basis {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        Ok(Vec::new())
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
+    fn apply_null(&self) -> ExprData {
+        ExprData{
+            id: 0,
+            name: format!("Basis[{}]", self.index),
+            ancestors: Vec::new(),
+            children: HashSet::new(),
+            op: self.clone_box(),
+            data_type: self.data_type,
+            shape: self.shape.clone(),
+            is_input_dependent: false,
+            is_differentiable: false,
+            matrix_positivity: MatrixPositivity::Indefinite,
+            matrix_symmetry: MatrixSymmetry::NonSymmetric,
+            matrix_fill: MatrixFill::NonStructuredFill,
+            grad_level: 0,
+            scope: Vec::new(),
+            sym_int: None
+        }
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Basis({}, {}, {})", self.index, self.shape, self.data_type))
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new((self.index, self.shape.clone(), self.data_type)))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static BASIS: OperatorMetaData = OperatorMetaData{
+            name: "Basis",
+            arity: Arity::Nullary,
+            num_outputs: 1,
+            differential_parents: 0,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: false,
+            reduction: false,
+            differentiable: false,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &BASIS
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn get_data_type(&self, g: &Graph, args: &Vec<usize>) -> FundamentalType {
+        self.data_type
+    }
+    #[allow(unused_variables, unused_mut)]
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        self.shape.clone()
+    }
+    #[allow(unused_variables, unused_mut)]
+    fn get_is_input_dependent(&self, g: &Graph, args: &Vec<usize>) -> bool {
+        false
+    }
+    #[allow(unused_variables, unused_mut)]
+    fn get_is_differentiable(&self, g: &Graph, args: &Vec<usize>) -> bool {
+        false
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct SymIntInput {
     pub identifier: String,
@@ -235,6 +359,12 @@ impl Operator for SymIntInput {
         Ok(Vec::new())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
     fn apply_null(&self) -> ExprData {
         ExprData{
             id: 0,
@@ -259,6 +389,10 @@ impl Operator for SymIntInput {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(format!("SymIntInput({})", self.identifier))
+    }
+
     fn get_args(&self) -> Option<Box<Any>> {
         Some(Box::new((self.identifier.clone())))
     }
@@ -276,7 +410,8 @@ impl Operator for SymIntInput {
             differentiable: false,
             scalar_output: true,
             shape_operator: true,
-            fixed_output_type: Some(FundamentalType::SignedInt),
+            copy_operator: false,
+            fixed_output_type: Some(FundamentalType::UnsignedInt),
         };
         &SYM_INT_INPUT
     }
@@ -296,6 +431,12 @@ impl Operator for Cleared {
         unimplemented!()
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
     fn apply_null(&self) -> ExprData {
         ExprData{
             id: 0,
@@ -320,6 +461,10 @@ impl Operator for Cleared {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        None
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static CLEARED: OperatorMetaData = OperatorMetaData{
             name: "Cleared",
@@ -333,6 +478,7 @@ impl Operator for Cleared {
             differentiable: false,
             scalar_output: true,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: Some(FundamentalType::Boolean),
         };
         &CLEARED
@@ -2,6 +2,7 @@ use primitives::*;
 use graph::*;
 use errors::*;
 use api::ids;
+use backend::BackendPrecisions;
 use std::any::Any;
 use std::collections::HashSet;
 //use std::borrow::Borrow;
@@ -20,15 +21,58 @@ pub struct OperatorMetaData {
     pub differentiable: bool,
     pub scalar_output: bool,
     pub shape_operator: bool,
+    pub copy_operator: bool,
     pub fixed_output_type: Option<FundamentalType>,
 }
 
+/// The symbolic cost of computing a single expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct OperatorCost {
+    /// Number of floating point or integer operations
+    pub flops: SymInt,
+    pub bytes_read: SymInt,
+    pub bytes_written: SymInt,
+}
+
+impl Default for OperatorCost {
+    fn default() -> Self {
+        OperatorCost {
+            flops: 0.into(),
+            bytes_read: 0.into(),
+            bytes_written: 0.into()
+        }
+    }
+}
+
+impl OperatorCost {
+    /// Adds the cost of another expression to this one
+    pub fn add(&mut self, other: &OperatorCost) {
+        self.flops += &other.flops;
+        self.bytes_read += &other.bytes_read;
+        self.bytes_written += &other.bytes_written;
+    }
+}
+
+impl ::std::fmt::Display for OperatorCost {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let f = |x: String| x;
+        write!(fmt, "flops: {}, read: {}B, written: {}B", self.flops.to_code(&f),
+               self.bytes_read.to_code(&f), self.bytes_written.to_code(&f))
+    }
+}
+
 pub trait Operator: ::std::fmt::Debug {
     /// Calculates the derivative of the parent expressions given the derivative
     /// of the current.
     fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
                     -> Result<Vec<(usize, usize)>>;
 
+    /// Calculates the derivative of the current expression given the derivatives
+    /// of its parents, where `None` marks a parent with a zero derivative.
+    /// Returns `None` if the derivative of the current expression is zero.
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>>;
+
     /// Clones the concrete operator and wraps it in a box again
     fn clone_box(&self) -> Box<Operator>;
 
@@ -37,6 +81,14 @@ pub trait Operator: ::std::fmt::Debug {
         None
     }
 
+    /// Key identifying the operator together with its arguments, such that
+    /// equal keys applied to the same ancestors give the same expression.
+    /// The key must contain every argument, with floats given by their bits.
+    /// By default expressions are never merged.
+    fn get_key(&self) -> Option<String> {
+        None
+    }
+
     /// Returns the meta data
     fn get_meta(&self) -> &OperatorMetaData;
 
@@ -117,6 +169,13 @@ pub trait Operator: ::std::fmt::Debug {
     fn get_grad_level(&self, g: &Graph, args: &Vec<usize>) -> usize {
         args.iter().map(|&x| g.nodes[x].grad_level).max().unwrap()
     }
+
+    /// Returns the symbolic cost of computing the expression `x` with this operator,
+    /// where the sizes of the values are given by the precisions of the backend
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let meta = self.get_meta();
+        default::get_cost(meta, g, x, precisions)
+    }
 }
 
 impl Clone for Box<Operator> {
@@ -201,6 +260,54 @@ pub mod default {
         }
     }
 
+    /// The number of bytes of the value of the expression
+    pub fn get_bytes(g: &Graph, x: usize, precisions: &BackendPrecisions) -> SymInt {
+        let ref node = g.nodes[x];
+        let bytes = match node.data_type {
+            FundamentalType::Boolean => 1,
+            FundamentalType::UnsignedInt | FundamentalType::SignedInt =>
+                precisions.integer_precision as i64,
+            FundamentalType::Float => precisions.float_precision as i64,
+            FundamentalType::Complex => 2 * precisions.complex_precision as i64,
+        };
+        &node.shape.elements() * &bytes.into()
+    }
+
+    /// Reading all arguments and writing the result, without any operations
+    pub fn get_memory_cost(g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let mut bytes_read: SymInt = 0.into();
+        for &a in &g.nodes[x].ancestors {
+            bytes_read += &get_bytes(g, a, precisions);
+        }
+        OperatorCost {
+            flops: 0.into(),
+            bytes_read: bytes_read,
+            bytes_written: get_bytes(g, x, precisions)
+        }
+    }
+
+    /// Operators with no arguments and shape operators are free and copy operators only
+    /// access memory, while elementwise operators perform one operation per element for
+    /// each argument after the first. For any other operator one operation per element
+    /// of the result is assumed.
+    pub fn get_cost(meta: &OperatorMetaData, g: &Graph, x: usize, precisions: &BackendPrecisions)
+                    -> OperatorCost {
+        if meta.arity == Arity::Nullary || meta.shape_operator {
+            return OperatorCost::default()
+        }
+        let mut cost = get_memory_cost(g, x, precisions);
+        if meta.copy_operator {
+            return cost
+        }
+        let operations = if meta.elementwise {
+            ::std::cmp::max(g.nodes[x].ancestors.len(), 2) as i64 - 1
+        } else {
+            1
+        };
+        cost.flops = &g.nodes[x].shape.elements() * &operations.into();
+        cost
+    }
+
     pub fn get_reduction_shape(mut shape: Shape, axes: &[bool; 4]) -> Shape {
         let axes = axes.as_ref();
         for &axis in Axis::iter() {
@@ -2,8 +2,13 @@ use ops::interface::*;
 use primitives::*;
 use graph::*;
 use errors::*;
+use props::DEFAULT_SIZE_HINT;
+use backend::{BackendPrecisions, symbolic_le};
 use api::*;
 
+use std::any::Any;
+use std::collections::{HashMap, HashSet};
+
 
 #[derive(Debug, Clone)]
 pub struct MatMul {}
@@ -12,38 +17,82 @@ impl Operator for MatMul {
     #[allow(unused_variables, unused_mut)]
     fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
                     -> Result<Vec<(usize, usize)>> {
+        // The derivative of the i-th factor is the chain of the transposed factors
+        // on its left, dx and the transposed factors on its right
+        // and is summed over any batch axes along which the factor was broadcasted
         let anc = g.get_node(x)?.ancestors.clone();
-        if anc.len() == 2 {
-            let mut res = Vec::new();
-            if flow_tree[anc[0]] {
-                let anc_transpose = ids::reorder(g, anc[1], None)?;
-                res.push((anc[0], ids::mat_mul(g, dx, anc_transpose)?));
+        let shape = g.get_node(x)?.shape.clone();
+        let mut res = Vec::new();
+        for (i, &a) in anc.iter().enumerate() {
+            if flow_tree[a] {
+                let mut chain = Vec::with_capacity(anc.len());
+                for &left in anc[..i].iter().rev() {
+                    chain.push(transpose(g, left)?);
+                }
+                chain.push(dx);
+                for &right in anc[i + 1..].iter().rev() {
+                    chain.push(transpose(g, right)?);
+                }
+                let grad = ids::mat_mul_chain(g, chain)?;
+                let axes = {
+                    let ref a_shape = g.get_node(a)?.shape;
+                    [false, false, a_shape.2 != shape.2, a_shape.3 != shape.3]
+                };
+                if axes[2] || axes[3] {
+                    res.push((a, ids::sum(g, grad, axes)?));
+                } else {
+                    res.push((a, grad));
+                }
             }
-            if flow_tree[anc[1]] {
-                let anc_transpose = ids::reorder(g, anc[0], None)?;
-                res.push((anc[1], ids::mat_mul(g, anc_transpose, dx)?));
+        }
+        Ok(res)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // Product rule - the sum of the products with one factor replaced by its derivative
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut terms = Vec::new();
+        for (i, d) in dx.iter().enumerate() {
+            if let Some(d) = *d {
+                let mut factors = anc.clone();
+                factors[i] = d;
+                terms.push(g.apply_op(Box::new(MatMul {}), factors)?);
             }
-            Ok(res)
-        } else {
-            unimplemented!()
+        }
+        match terms.len() {
+            0 => Ok(None),
+            1 => Ok(Some(terms[0])),
+            _ => Ok(Some(ids::add(g, terms)?))
         }
     }
 
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
-        // Verify all args are 2 dimensional and that their mid shapes match
+        // Verify that the mid shapes match and that the batch shapes are either
+        // equal or one
         let mut last_shape = g.get_node(args[0]).unwrap().shape.0.clone();
+        let mut batch_shape = g.get_node(args[0]).unwrap().shape.clone();
         let mut index = None;
         for (i, &a) in args.iter().enumerate() {
             let ref shape = g.get_node(a).unwrap().shape;
-            if shape.order() > 2 {
-                return Err(ErrorKind::InvalidArguments(String::new() + meta.name, args.clone(),
-                                                       format!("The tensor at index {} is of order {} > 2.", i, shape.order())).into());
-            } else if last_shape != shape.0 {
+            if last_shape != shape.0 {
                 index = Some(i);
                 break;
             }
+            for &axis in &[Axis::Axis2, Axis::Axis3] {
+                if *shape.get(axis) == 1 {
+                    continue
+                } else if *batch_shape.get(axis) == 1 {
+                    batch_shape.set(axis, shape.get(axis).clone());
+                } else if batch_shape.get(axis) != shape.get(axis) {
+                    return Err(ErrorKind::InvalidArguments(String::new() + meta.name, args.clone(),
+                        format!("The batch axes of the tensor at index {} with shape {} \
+                        do not match the shape {}.", i, shape, batch_shape)).into());
+                }
+            }
             last_shape = shape.1.clone();
         }
         if let Some(id) = index {
@@ -58,6 +107,10 @@ impl Operator for MatMul {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static MATRIX_MUL: OperatorMetaData = OperatorMetaData{
             name: "MatMul",
@@ -71,14 +124,326 @@ impl Operator for MatMul {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &MATRIX_MUL
     }
 
+    /// The product is over the first two axes, while the last two are batch axes,
+    /// along which any argument of size one is broadcasted
     fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
         let m = g.get_node(args[0]).unwrap().shape.0.clone();
         let n = g.get_node(*args.last().unwrap()).unwrap().shape.1.clone();
-        Shape(m, n, 1.into(), 1.into())
+        let mut shape = Shape(m, n, 1.into(), 1.into());
+        for &a in args {
+            let ref a_shape = g.get_node(a).unwrap().shape;
+            if a_shape.2 != 1 {
+                shape.2 = a_shape.2.clone();
+            }
+            if a_shape.3 != 1 {
+                shape.3 = a_shape.3.clone();
+            }
+        }
+        shape
+    }
+
+    /// Multiplying the matrices from left to right, with `2mkn` operations for each
+    /// product of an `m x k` and a `k x n` matrix in every batch
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let mut cost = default::get_memory_cost(g, x, precisions);
+        let ref args = g.nodes[x].ancestors;
+        let ref shape = g.nodes[x].shape;
+        let m = &(&shape.0 * &shape.2) * &shape.3;
+        let mut flops: SymInt = 0.into();
+        for &a in args.iter().skip(1) {
+            let ref shape = g.nodes[a].shape;
+            flops += &(&(&m * &shape.0) * &(&shape.1 * &2.into()));
+        }
+        cost.flops = flops;
+        cost
+    }
+}
+
+/// A tensor contraction given by a subscript specification such as `ij,jk->ik`.
+/// Every letter labels an axis of the argument in order, such that all axes with
+/// the same label must have the same size, while any axes without a label must be
+/// of size one. The result is the sum, over all labels missing from the output,
+/// of the product of the arguments. Without an explicit output it consists of the
+/// labels appearing only once, in alphabetical order.
+#[derive(Debug, Clone)]
+pub struct Einsum {
+    /// The labels of the axes of every argument
+    pub inputs: Vec<Vec<char>>,
+    /// The labels of the axes of the result
+    pub output: Vec<char>
+}
+
+impl Einsum {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let invalid = |msg: &str| -> Result<Self> {
+            Err(ErrorKind::Msg(format!("Invalid einsum specification '{}': {}", spec, msg)).into())
+        };
+        let spec_chars: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+        let mut parts = spec_chars.split("->");
+        let inputs: Vec<Vec<char>> = parts.next().unwrap().split(',')
+            .map(|x| x.chars().collect()).collect();
+        let output: Option<Vec<char>> = parts.next().map(|x| x.chars().collect());
+        if parts.next().is_some() {
+            return invalid("more than one '->'.")
+        }
+        for labels in inputs.iter().chain(output.iter()) {
+            if labels.len() > 4 {
+                return invalid("more than 4 axes.")
+            } else if labels.iter().any(|c| !c.is_ascii_alphabetic()) {
+                return invalid("the labels must be letters.")
+            } else if labels.iter().enumerate().any(|(i, c)| labels[..i].contains(c)) {
+                return invalid("repeated labels of the same tensor are not supported.")
+            }
+        }
+        let output = match output {
+            Some(output) => {
+                if output.iter().any(|c| !inputs.iter().any(|x| x.contains(c))) {
+                    return invalid("the output contains a label which is not in the inputs.")
+                }
+                output
+            },
+            None => {
+                let mut output: Vec<char> = inputs.iter().flat_map(|x| x.iter().cloned())
+                    .filter(|c| inputs.iter().filter(|x| x.contains(c)).count() == 1)
+                    .collect();
+                output.sort();
+                if output.len() > 4 {
+                    return invalid("the implicit output has more than 4 axes.")
+                }
+                output
+            }
+        };
+        Ok(Einsum {
+            inputs: inputs,
+            output: output
+        })
+    }
+
+    /// The specification in the form `ij,jk->ik`
+    pub fn spec(&self) -> String {
+        let inputs: Vec<String> = self.inputs.iter().map(|x| x.iter().collect()).collect();
+        format!("{}->{}", inputs.join(","), self.output.iter().collect::<String>())
+    }
+
+    /// All labels in order of appearance together with their sizes
+    pub fn label_dims(&self, g: &Graph, args: &[usize]) -> Vec<(char, SymInt)> {
+        let mut dims: Vec<(char, SymInt)> = Vec::new();
+        for (labels, &a) in self.inputs.iter().zip(args) {
+            for (&c, &axis) in labels.iter().zip(Axis::iter()) {
+                if !dims.iter().any(|&(l, _)| l == c) {
+                    dims.push((c, g.nodes[a].shape.get(axis).clone()));
+                }
+            }
+        }
+        dims
+    }
+}
+
+impl Operator for Einsum {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        // The derivative of each argument is the contraction of dx with the rest of
+        // the arguments, broadcasted along the labels which appear only in it
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut res = Vec::new();
+        for (i, &a) in anc.iter().enumerate() {
+            if flow_tree[a] {
+                let mut args = vec![dx];
+                let mut inputs = vec![self.output.clone()];
+                for (j, &other) in anc.iter().enumerate() {
+                    if j != i {
+                        args.push(other);
+                        inputs.push(self.inputs[j].clone());
+                    }
+                }
+                if self.inputs[i].iter().any(|c| !inputs.iter().any(|x| x.contains(c))) {
+                    let data_type = g.get_node(a)?.data_type;
+                    let one = g.scalar(1.0, data_type);
+                    args.push(ids::broadcast_to(g, one, a)?);
+                    inputs.push(self.inputs[i].clone());
+                }
+                let op = Einsum {
+                    inputs: inputs,
+                    output: self.inputs[i].clone()
+                };
+                res.push((a, g.apply_op(Box::new(op), args)?));
+            }
+        }
+        Ok(res)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // Product rule - the sum of the contractions with one argument replaced by its derivative
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut terms = Vec::new();
+        for (i, d) in dx.iter().enumerate() {
+            if let Some(d) = *d {
+                let mut args = anc.clone();
+                args[i] = d;
+                terms.push(g.apply_op(self.clone_box(), args)?);
+            }
+        }
+        match terms.len() {
+            0 => Ok(None),
+            1 => Ok(Some(terms[0])),
+            _ => Ok(Some(ids::add(g, terms)?))
+        }
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        if args.len() != self.inputs.len() {
+            return Err(ErrorKind::InvalidArguments(String::new() + meta.name, args.clone(),
+                format!("Expecting {} arguments for '{}', got {}.",
+                        self.inputs.len(), self.spec(), args.len())).into())
+        }
+        for &arg in &args {
+            if g.get_node(arg)?.op.get_meta().name == "Update" {
+                return Err(ErrorKind::Msg("Attempting to use 'Update' \
+                in another operation.".into()).into())
+            }
+        }
+        // Verify that the axes with the same label match and the rest are of size one
+        let mut dims: HashMap<char, (SymInt, usize)> = HashMap::new();
+        for (labels, &a) in self.inputs.iter().zip(args.iter()) {
+            let shape = g.get_node(a)?.shape.clone();
+            for (i, &axis) in Axis::iter().enumerate() {
+                if i >= labels.len() {
+                    if *shape.get(axis) != 1 {
+                        return Err(ErrorKind::InvalidArguments(String::new() + meta.name,
+                            args.clone(), format!("The tensor with shape {} has more axes \
+                            than its labels in '{}'.", shape, self.spec())).into())
+                    }
+                    continue
+                }
+                match dims.get(&labels[i]).cloned() {
+                    Some((dim, other)) => if dim != *shape.get(axis) {
+                        return Err(ErrorKind::InvalidShapes(String::new() + meta.name,
+                            format!("{}", g.get_node(other)?.shape), format!("{}", shape)).into())
+                    },
+                    None => {
+                        dims.insert(labels[i], (shape.get(axis).clone(), a));
+                    }
+                }
+            }
+        }
+        Ok(args)
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_args(&self) -> Option<Box<Any>> {
+        Some(Box::new((self.inputs.clone(), self.output.clone())))
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Einsum({})", self.spec()))
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static EINSUM: OperatorMetaData = OperatorMetaData{
+            name: "Einsum",
+            arity: Arity::Nary,
+            num_outputs: 1,
+            differential_parents: ::std::usize::MAX,
+            ordered_parents: true,
+            elementwise: false,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &EINSUM
+    }
+
+    fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+        let dims = self.label_dims(g, args);
+        let mut shape = Shape::scalar_shape();
+        for (&c, &axis) in self.output.iter().zip(Axis::iter()) {
+            let dim = dims.iter().find(|&&(l, _)| l == c).unwrap().1.clone();
+            shape.set(axis, dim);
+        }
+        shape
+    }
+
+    /// For every combination of the labels one multiplication for each argument after
+    /// the first and one addition
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let mut cost = default::get_memory_cost(g, x, precisions);
+        let mut flops: SymInt = (self.inputs.len() as i64).into();
+        for (_, dim) in self.label_dims(g, &g.nodes[x].ancestors) {
+            flops = &flops * &dim;
+        }
+        cost.flops = flops;
+        cost
+    }
+}
+
+/// Swaps the first two axes, which are the axes of the matrices
+fn transpose(g: &mut Graph, x: usize) -> Result<usize> {
+    if g.get_node(x)?.shape.order() <= 2 {
+        ids::reorder(g, x, None)
+    } else {
+        ids::reorder(g, x, Some([Axis::Axis1, Axis::Axis0, Axis::Axis2, Axis::Axis3]))
+    }
+}
+
+/// Whether the cost `a` is lower than `b`. If this does not hold for all values of
+/// the symbolic integers, they are compared using the size hints.
+fn is_cheaper(a: &SymInt, b: &SymInt, hints: &HashMap<String, i64>) -> bool {
+    if symbolic_le(a, b) {
+        !symbolic_le(b, a)
+    } else if symbolic_le(b, a) {
+        false
+    } else {
+        let mut unique = HashSet::new();
+        a.unique_identifiers(&mut unique);
+        b.unique_identifiers(&mut unique);
+        let values: HashMap<String, i64> = unique.into_iter()
+            .map(|x| {
+                let value = *hints.get(&x).unwrap_or(&DEFAULT_SIZE_HINT);
+                (x, value)
+            }).collect();
+        a.eval(&values).unwrap() < b.eval(&values).unwrap()
+    }
+}
+
+/// Finds the order of multiplying a chain of matrices with the least number of
+/// operations, where the i-th matrix has `dims[i]` rows and `dims[i + 1]` columns.
+/// Returns the table of splits, such that the product of the matrices from `i` to
+/// `j` inclusive is the product of those from `i` to `splits[i][j]` and those from
+/// `splits[i][j] + 1` to `j`.
+pub fn matrix_chain_order(dims: &[SymInt], hints: &HashMap<String, i64>) -> Vec<Vec<usize>> {
+    let n = dims.len() - 1;
+    let mut costs: Vec<Vec<SymInt>> = vec![vec![0.into(); n]; n];
+    let mut splits = vec![vec![0; n]; n];
+    for length in 1..n {
+        for i in 0..n - length {
+            let j = i + length;
+            for k in i..j {
+                let mut cost = &(&dims[i] * &dims[k + 1]) * &dims[j + 1];
+                cost += &costs[i][k];
+                cost += &costs[k + 1][j];
+                if k == i || is_cheaper(&cost, &costs[i][j], hints) {
+                    costs[i][j] = cost;
+                    splits[i][j] = k;
+                }
+            }
+        }
     }
+    splits
 }
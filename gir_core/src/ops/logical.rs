@@ -0,0 +1,219 @@
+use ops::interface::*;
+use primitives::*;
+use graph::*;
+use errors::*;
+use api::ids;
+
+
+/// Defines an elementwise operator with a `Boolean` result. Such operators are
+/// piecewise constant, hence no gradients are passed trough them. When
+/// `boolean_args` is set all arguments must be `Boolean` as well.
+macro_rules! boolean_operator {
+    ( $(#[$attr: meta])* $op: ident, $arity: ident, $ordered: expr, $boolean_args: expr ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $op {}
+
+        impl Operator for $op {
+            #[allow(unused_variables, unused_mut)]
+            fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                            -> Result<Vec<(usize, usize)>> {
+                Ok(Vec::new())
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                            -> Result<Option<usize>> {
+                Ok(None)
+            }
+
+            fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+                let meta = self.get_meta();
+                let args = default::verify_args(meta, g, args)?;
+                if $boolean_args {
+                    verify_boolean(meta.name, g, &args)?;
+                }
+                if args.len() > 1 {
+                    Ok(default::broadcast_shapes(g, meta.name, args)?)
+                } else {
+                    Ok(args)
+                }
+            }
+
+            fn clone_box(&self) -> Box<Operator> {
+                Box::new(self.clone())
+            }
+
+            fn get_key(&self) -> Option<String> {
+                Some(stringify!($op).into())
+            }
+
+            fn get_meta(&self) -> &OperatorMetaData {
+                static META: OperatorMetaData = OperatorMetaData{
+                    name: stringify!($op),
+                    arity: Arity::$arity,
+                    num_outputs: 1,
+                    differential_parents: 0,
+                    ordered_parents: $ordered,
+                    elementwise: true,
+                    type_preserving: false,
+                    reduction: false,
+                    differentiable: false,
+                    scalar_output: false,
+                    shape_operator: false,
+                    copy_operator: false,
+                    fixed_output_type: Some(FundamentalType::Boolean),
+                };
+                &META
+            }
+        }
+    };
+}
+
+fn verify_boolean(name: &str, g: &Graph, args: &[usize]) -> Result<()> {
+    for &arg in args {
+        if g.get_node(arg)?.data_type != FundamentalType::Boolean {
+            return Err(ErrorKind::InvalidArguments(
+                String::new() + name, args.to_vec(),
+                "Expecting only boolean expressions.".into()).into())
+        }
+    }
+    Ok(())
+}
+
+boolean_operator!(
+    /// `a > b`
+    Gt, Binary, true, false
+);
+
+boolean_operator!(
+    /// `a >= b`
+    Ge, Binary, true, false
+);
+
+boolean_operator!(
+    /// `a < b`
+    Lt, Binary, true, false
+);
+
+boolean_operator!(
+    /// `a <= b`
+    Le, Binary, true, false
+);
+
+boolean_operator!(
+    /// `a == b`
+    Equal, Binary, false, false
+);
+
+boolean_operator!(
+    /// `a != b`
+    Ne, Binary, false, false
+);
+
+boolean_operator!(
+    /// The logical conjunction of two boolean expressions
+    And, Binary, false, true
+);
+
+boolean_operator!(
+    /// The logical disjunction of two boolean expressions
+    Or, Binary, false, true
+);
+
+boolean_operator!(
+    /// The logical negation of a boolean expression
+    Not, Unary, true, true
+);
+
+boolean_operator!(
+    /// The exclusive disjunction of two boolean expressions
+    Xor, Binary, false, true
+);
+
+/// Chooses elementwise between its second and third argument, depending on
+/// whether the boolean first argument is true or false. The gradient is routed
+/// only through the chosen branch, while the condition is not differentiable.
+#[derive(Debug, Clone)]
+pub struct Select {}
+
+impl Select {
+    fn branch(g: &mut Graph, x: usize, cond: usize, on_true: Option<usize>, on_false: Option<usize>)
+              -> Result<usize> {
+        let data_type = g.get_node(x)?.data_type;
+        let on_true = match on_true {
+            Some(d) => d,
+            None => g.scalar(0.0, data_type)
+        };
+        let on_false = match on_false {
+            Some(d) => d,
+            None => g.scalar(0.0, data_type)
+        };
+        ids::select(g, cond, on_true, on_false)
+    }
+}
+
+impl Operator for Select {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let anc = g.get_node(x)?.ancestors.clone();
+        let mut result = Vec::new();
+        if flow_tree[anc[1]] {
+            result.push((anc[1], Select::branch(g, x, anc[0], Some(dx), None)?));
+        }
+        if flow_tree[anc[2]] {
+            result.push((anc[2], Select::branch(g, x, anc[0], None, Some(dx))?));
+        }
+        Ok(result)
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        if dx[1].is_none() && dx[2].is_none() {
+            return Ok(None)
+        }
+        let cond = g.get_node(x)?.ancestors[0];
+        Ok(Some(Select::branch(g, x, cond, dx[1], dx[2])?))
+    }
+
+    fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+        let meta = self.get_meta();
+        let args = default::verify_args(meta, g, args)?;
+        verify_boolean(meta.name, g, &args[..1])?;
+        Ok(default::broadcast_shapes(g, meta.name, args)?)
+    }
+
+    fn get_data_type(&self, g: &Graph, args: &Vec<usize>) -> FundamentalType {
+        let meta = self.get_meta();
+        default::get_data_type(meta, g, &args[1..].to_vec())
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static SELECT: OperatorMetaData = OperatorMetaData{
+            name: "Select",
+            arity: Arity::Ternary,
+            num_outputs: 1,
+            differential_parents: 2,
+            ordered_parents: true,
+            elementwise: true,
+            type_preserving: false,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: false,
+            fixed_output_type: None,
+        };
+        &SELECT
+    }
+}
@@ -6,7 +6,10 @@ pub mod arithmetic;
 pub mod special;
 pub mod linalg;
 pub mod nonl;
+pub mod logical;
 pub mod reduction;
+pub mod indexing;
+pub mod registry;
 
 pub use self::interface::*;
 pub use self::input::*;
@@ -16,4 +19,7 @@ pub use self::special::*;
 pub use self::shape::*;
 pub use self::linalg::*;
 pub use self::nonl::*;
+pub use self::logical::*;
 pub use self::reduction::*;
+pub use self::indexing::*;
+pub use self::registry::{OpArg, OperatorRegistry};
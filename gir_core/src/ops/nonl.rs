@@ -5,43 +5,304 @@ use errors::*;
 use api::ids;
 
 
+/// Defines an elementwise unary operator `f` from its derivative `f'`.
+///
+/// The derivative is given as a body evaluated with the graph, the node of
+/// the operator `x` and its argument `a`. Both the reverse and forward modes
+/// multiply the incoming derivative by it.
+macro_rules! unary_operator {
+    ( $(#[$attr: meta])* $op: ident, |$g: ident, $x: ident, $a: ident| $derivative: expr ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $op {}
+
+        impl $op {
+            #[allow(unused_variables)]
+            fn derivative($g: &mut Graph, $x: usize, $a: usize) -> Result<usize> {
+                $derivative
+            }
+        }
+
+        impl Operator for $op {
+            #[allow(unused_variables, unused_mut)]
+            fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                            -> Result<Vec<(usize, usize)>> {
+                let anc = g.get_node(x)?.ancestors[0];
+                if flow_tree[anc] {
+                    let dp = $op::derivative(g, x, anc)?;
+                    Ok(vec![(anc, ids::mul(g, vec![dx, dp])?)])
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                            -> Result<Option<usize>> {
+                match dx[0] {
+                    Some(d) => {
+                        let anc = g.get_node(x)?.ancestors[0];
+                        let dp = $op::derivative(g, x, anc)?;
+                        Ok(Some(ids::mul(g, vec![d, dp])?))
+                    },
+                    None => Ok(None)
+                }
+            }
+
+            fn clone_box(&self) -> Box<Operator> {
+                Box::new(self.clone())
+            }
+
+            fn get_key(&self) -> Option<String> {
+                Some(stringify!($op).into())
+            }
+
+            fn get_meta(&self) -> &OperatorMetaData {
+                static META: OperatorMetaData = OperatorMetaData{
+                    name: stringify!($op),
+                    arity: Arity::Unary,
+                    num_outputs: 1,
+                    differential_parents: 1,
+                    ordered_parents: true,
+                    elementwise: true,
+                    type_preserving: false,
+                    reduction: false,
+                    differentiable: true,
+                    scalar_output: false,
+                    shape_operator: false,
+                    copy_operator: false,
+                    fixed_output_type: None,
+                };
+                &META
+            }
+        }
+    };
+}
+
+unary_operator!(
+    /// The hyperbolic tangent, with derivative `1 - tanh(a)^2`
+    Tanh, |g, x, a| {
+        let one = g.scalar(1.0, FundamentalType::Float);
+        let tanh_squared = ids::mul(g, vec![x, x])?;
+        ids::sub(g, one, tanh_squared)
+    }
+);
+
+unary_operator!(
+    /// The exponential `e^a`
+    Exp, |g, x, a| Ok(x)
+);
+
+unary_operator!(
+    /// The natural logarithm
+    Log, |g, x, a| ids::reciprocal(g, a)
+);
+
+unary_operator!(
+    /// `log(1 + a)`, accurate for small `a`
+    Log1p, |g, x, a| {
+        let one = g.scalar(1.0, FundamentalType::Float);
+        let one_plus_a = ids::add(g, vec![one, a])?;
+        ids::reciprocal(g, one_plus_a)
+    }
+);
+
+unary_operator!(
+    /// `e^a - 1`, accurate for small `a`
+    Expm1, |g, x, a| {
+        let one = g.scalar(1.0, FundamentalType::Float);
+        ids::add(g, vec![x, one])
+    }
+);
+
+unary_operator!(
+    /// The square root
+    Sqrt, |g, x, a| {
+        let half = g.scalar(0.5, FundamentalType::Float);
+        let inv = ids::reciprocal(g, x)?;
+        ids::mul(g, vec![half, inv])
+    }
+);
+
+unary_operator!(
+    /// The reciprocal of the square root, with derivative `-rsqrt(a)^3 / 2`
+    Rsqrt, |g, x, a| {
+        let half = g.scalar(-0.5, FundamentalType::Float);
+        ids::mul(g, vec![half, x, x, x])
+    }
+);
+
+unary_operator!(
+    /// The square `a^2`
+    Square, |g, x, a| {
+        let two = g.scalar(2.0, FundamentalType::Float);
+        ids::mul(g, vec![two, a])
+    }
+);
+
+unary_operator!(
+    /// The logistic function `1 / (1 + e^-a)`
+    Sigmoid, |g, x, a| {
+        let one = g.scalar(1.0, FundamentalType::Float);
+        let one_minus_x = ids::sub(g, one, x)?;
+        ids::mul(g, vec![x, one_minus_x])
+    }
+);
+
+unary_operator!(
+    /// `log(1 + e^a)`, a smooth version of `Relu`
+    Softplus, |g, x, a| ids::sigmoid(g, a)
+);
+
+unary_operator!(
+    /// `max(a, 0)`. The derivative at 0 is taken to be 0.
+    Relu, |g, x, a| ids::sign(g, x)
+);
+
+unary_operator!(
+    /// The absolute value. The derivative at 0 is taken to be 0.
+    Abs, |g, x, a| ids::sign(g, a)
+);
+
+unary_operator!(
+    /// The sine
+    Sin, |g, x, a| ids::cos(g, a)
+);
+
+unary_operator!(
+    /// The cosine
+    Cos, |g, x, a| {
+        let sin = ids::sin(g, a)?;
+        ids::neg(g, sin)
+    }
+);
+
+unary_operator!(
+    /// The tangent, with derivative `1 + tan(a)^2`
+    Tan, |g, x, a| {
+        let one = g.scalar(1.0, FundamentalType::Float);
+        let tan_squared = ids::mul(g, vec![x, x])?;
+        ids::add(g, vec![one, tan_squared])
+    }
+);
+
+unary_operator!(
+    /// The hyperbolic sine
+    Sinh, |g, x, a| ids::cosh(g, a)
+);
+
+unary_operator!(
+    /// The hyperbolic cosine
+    Cosh, |g, x, a| ids::sinh(g, a)
+);
+
+unary_operator!(
+    /// The error function, with derivative `2 / sqrt(pi) e^(-a^2)`
+    Erf, |g, x, a| {
+        let scale = g.scalar(2.0 / ::std::f64::consts::PI.sqrt(), FundamentalType::Float);
+        let a_squared = ids::mul(g, vec![a, a])?;
+        let minus_a_squared = ids::neg(g, a_squared)?;
+        let gaussian = ids::exp(g, minus_a_squared)?;
+        ids::mul(g, vec![scale, gaussian])
+    }
+);
+
+/// The sign of the argument as -1, 0 or 1. Its derivative is zero almost
+/// everywhere, hence no gradients are passed trough it.
 #[derive(Debug, Clone)]
-pub struct Tanh {}
+pub struct Sign {}
 
-impl Operator for Tanh {
+impl Operator for Sign {
     #[allow(unused_variables, unused_mut)]
     fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
                     -> Result<Vec<(usize, usize)>> {
-        let anc = g.get_node(x)?.ancestors[0];
-        if flow_tree[anc] {
-            let one = g.scalar(1.0, FundamentalType::Float);
-            let tanh_squared = ids::mul(g, vec![x, x])?;
-            let dp = ids::sub(g, one, tanh_squared)?;
-            Ok(vec![(anc, ids::mul(g, vec![dx, dp])?)])
-        } else {
-            Ok(Vec::new())
-        }
+        Ok(Vec::new())
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
     }
 
     fn clone_box(&self) -> Box<Operator> {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
-        static TANH: OperatorMetaData = OperatorMetaData{
-            name: "Tanh",
+        static SIGN: OperatorMetaData = OperatorMetaData{
+            name: "Sign",
             arity: Arity::Unary,
             num_outputs: 1,
-            differential_parents: 1,
+            differential_parents: 0,
             ordered_parents: true,
             elementwise: true,
-            type_preserving: false,
+            type_preserving: true,
             reduction: false,
-            differentiable: true,
+            differentiable: false,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
-        &TANH
+        &SIGN
     }
 }
+
+/// The error function, computed from its Taylor series for small arguments
+/// and from the continued fraction of `erfc` otherwise.
+fn erf(x: f64) -> f64 {
+    if x < 0.0 {
+        return -erf(-x)
+    }
+    let scale = 2.0 / ::std::f64::consts::PI.sqrt();
+    if x < 2.5 {
+        let mut term = x;
+        let mut sum = x;
+        let mut n = 1.0;
+        while term.abs() > 1e-17 * sum.abs() {
+            term *= - x * x / n;
+            sum += term / (2.0 * n + 1.0);
+            n += 1.0;
+        }
+        scale * sum
+    } else {
+        let mut fraction = x;
+        for n in (1..60).rev() {
+            fraction = x + (n as f64) / 2.0 / fraction;
+        }
+        1.0 - (- x * x).exp() * scale / 2.0 / fraction
+    }
+}
+
+/// Evaluates the elementwise unary operator with the given name on a single
+/// value, returns `None` if there is no such operator.
+pub fn unary_value(name: &str, a: f64) -> Option<f64> {
+    let value = match name {
+        "Tanh" => a.tanh(),
+        "Exp" => a.exp(),
+        "Log" => a.ln(),
+        "Log1p" => a.ln_1p(),
+        "Expm1" => a.exp_m1(),
+        "Sqrt" => a.sqrt(),
+        "Rsqrt" => 1.0 / a.sqrt(),
+        "Square" => a * a,
+        "Sigmoid" => 1.0 / (1.0 + (-a).exp()),
+        "Softplus" => if a > 0.0 { a + (-a).exp().ln_1p() } else { a.exp().ln_1p() },
+        "Relu" => if a > 0.0 { a } else { 0.0 },
+        "Abs" => a.abs(),
+        "Sign" => if a > 0.0 { 1.0 } else if a < 0.0 { -1.0 } else { 0.0 },
+        "Sin" => a.sin(),
+        "Cos" => a.cos(),
+        "Tan" => a.tan(),
+        "Sinh" => a.sinh(),
+        "Cosh" => a.cosh(),
+        "Erf" => erf(a),
+        _ => return None
+    };
+    Some(value)
+}
@@ -2,8 +2,8 @@ use ops::interface::*;
 use primitives::*;
 use graph::*;
 use errors::*;
+use backend::BackendPrecisions;
 use api::ids;
-use std::any::Any;
 
 #[derive(Debug, Clone)]
 pub struct Sum {
@@ -22,11 +22,24 @@ impl Operator for Sum {
         }
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        match dx[0] {
+            Some(d) => Ok(Some(ids::sum(g, d, self.axes)?)),
+            None => Ok(None)
+        }
+    }
+
     fn clone_box(&self) -> Box<Operator> {
         Box::new(self.clone())
     }
 
-    fn get_args(&self) -> Option<Box<Any>> {
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Sum{:?}", self.axes))
+    }
+
+    fn get_args(&self) -> Option<Box<::std::any::Any>> {
         Some(Box::new((self.axes.clone())))
     }
 
@@ -43,6 +56,7 @@ impl Operator for Sum {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &SUM
@@ -52,4 +66,249 @@ impl Operator for Sum {
     fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
         default::get_reduction_shape(g.get_node(args[0]).unwrap().shape.clone(), &self.axes)
     }
-}
\ No newline at end of file
+
+    /// One operation per element of the argument
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        let mut cost = default::get_memory_cost(g, x, precisions);
+        cost.flops = g.nodes[g.nodes[x].ancestors[0]].shape.elements();
+        cost
+    }
+}
+
+/// The number of elements of `a` which are reduced into a single one
+fn reduced_elements(g: &mut Graph, a: usize, axes: &[bool; 4]) -> Result<usize> {
+    let mut factors = Vec::new();
+    for &axis in Axis::iter() {
+        if axes[axis as usize] {
+            factors.push(ids::dim(g, a, axis)?);
+        }
+    }
+    match factors.len() {
+        0 => Ok(g.scalar(1.0, FundamentalType::Float)),
+        1 => Ok(factors[0]),
+        _ => ids::mul(g, factors)
+    }
+}
+
+/// The derivative of a `Max` or `Min` reduction, split evenly between all
+/// elements equal to the result
+fn extremum_partial(g: &mut Graph, x: usize, a: usize, axes: &[bool; 4]) -> Result<usize> {
+    let x = ids::broadcast_to(g, x, a)?;
+    let equal = ids::eq(g, a, x)?;
+    let mask = ids::cast(g, equal, FundamentalType::Float)?;
+    let count = ids::sum(g, mask, *axes)?;
+    let count = ids::broadcast_to(g, count, a)?;
+    ids::div(g, mask, count)
+}
+
+/// Defines a reduction over the axes marked in `axes`.
+///
+/// Differentiable reductions are given by the partial derivative of the result
+/// with respect to every element of the argument, evaluated with the graph, the
+/// node of the operator `x`, its argument `a` and the axes. The partial must have
+/// the shape of the argument. Any other reduction is defined by its fixed output
+/// type and passes no gradients.
+macro_rules! reduction_operator {
+    ( $(#[$attr: meta])* $op: ident, $flops: expr,
+      |$g: ident, $x: ident, $a: ident, $axes: ident| $partial: expr ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $op {
+            pub axes: [bool; 4]
+        }
+
+        impl $op {
+            #[allow(unused_variables)]
+            fn partial($g: &mut Graph, $x: usize, $a: usize, $axes: &[bool; 4]) -> Result<usize> {
+                $partial
+            }
+        }
+
+        impl Operator for $op {
+            #[allow(unused_variables, unused_mut)]
+            fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                            -> Result<Vec<(usize, usize)>> {
+                let ancestor = g.get_node(x)?.ancestors[0];
+                if flow_tree[ancestor] {
+                    let dx = ids::broadcast_to(g, dx, ancestor)?;
+                    let dp = $op::partial(g, x, ancestor, &self.axes)?;
+                    Ok(vec![(ancestor, ids::mul(g, vec![dx, dp])?)])
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                            -> Result<Option<usize>> {
+                match dx[0] {
+                    Some(d) => {
+                        let ancestor = g.get_node(x)?.ancestors[0];
+                        let dp = $op::partial(g, x, ancestor, &self.axes)?;
+                        let product = ids::mul(g, vec![d, dp])?;
+                        Ok(Some(ids::sum(g, product, self.axes)?))
+                    },
+                    None => Ok(None)
+                }
+            }
+
+            reduction_operator!(@common $op, 1, true, None, $flops);
+        }
+    };
+    ( $(#[$attr: meta])* $op: ident, $flops: expr, $data_type: expr ) => {
+        $(#[$attr])*
+        #[derive(Debug, Clone)]
+        pub struct $op {
+            pub axes: [bool; 4]
+        }
+
+        impl Operator for $op {
+            #[allow(unused_variables, unused_mut)]
+            fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                            -> Result<Vec<(usize, usize)>> {
+                Ok(Vec::new())
+            }
+
+            #[allow(unused_variables, unused_mut)]
+            fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                            -> Result<Option<usize>> {
+                Ok(None)
+            }
+
+            fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
+                let meta = self.get_meta();
+                let args = default::verify_args(meta, g, args)?;
+                if $data_type == FundamentalType::Boolean &&
+                    g.get_node(args[0])?.data_type != FundamentalType::Boolean {
+                    return Err(ErrorKind::InvalidArguments(
+                        String::new() + meta.name, args,
+                        "Expecting a boolean expression.".into()).into())
+                }
+                Ok(args)
+            }
+
+            reduction_operator!(@common $op, 0, false, Some($data_type), $flops);
+        }
+    };
+    ( @common $op: ident, $differential_parents: expr, $differentiable: expr,
+      $fixed_output_type: expr, $flops: expr ) => {
+        fn clone_box(&self) -> Box<Operator> {
+            Box::new(self.clone())
+        }
+
+        fn get_args(&self) -> Option<Box<::std::any::Any>> {
+            Some(Box::new(self.axes.clone()))
+        }
+
+        fn get_key(&self) -> Option<String> {
+            Some(format!("{}{:?}", stringify!($op), self.axes))
+        }
+
+        fn get_meta(&self) -> &OperatorMetaData {
+            static META: OperatorMetaData = OperatorMetaData{
+                name: stringify!($op),
+                arity: Arity::Unary,
+                num_outputs: 1,
+                differential_parents: $differential_parents,
+                ordered_parents: true,
+                elementwise: false,
+                type_preserving: false,
+                reduction: true,
+                differentiable: $differentiable,
+                scalar_output: false,
+                shape_operator: false,
+                copy_operator: false,
+                fixed_output_type: $fixed_output_type,
+            };
+            &META
+        }
+
+        #[allow(unused_variables, unused_mut)]
+        fn get_shape(&self, g: &Graph, args: &Vec<usize>) -> Shape {
+            default::get_reduction_shape(g.get_node(args[0]).unwrap().shape.clone(), &self.axes)
+        }
+
+        /// A fixed number of operations per element of the argument
+        fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+            let mut cost = default::get_memory_cost(g, x, precisions);
+            cost.flops = &g.nodes[g.nodes[x].ancestors[0]].shape.elements() * &($flops as i64).into();
+            cost
+        }
+    };
+}
+
+reduction_operator!(
+    /// The maximum over the axes. Where several elements are equal to it the
+    /// gradient is split evenly between them.
+    Max, 1, |g, x, a, axes| extremum_partial(g, x, a, axes)
+);
+
+reduction_operator!(
+    /// The minimum over the axes. Where several elements are equal to it the
+    /// gradient is split evenly between them.
+    Min, 1, |g, x, a, axes| extremum_partial(g, x, a, axes)
+);
+
+reduction_operator!(
+    /// The product over the axes. The derivative is computed as the product
+    /// divided by each element, thus it is not defined for zero elements.
+    Prod, 1, |g, x, a, axes| {
+        let x = ids::broadcast_to(g, x, a)?;
+        ids::div(g, x, a)
+    }
+);
+
+reduction_operator!(
+    /// The mean over the axes, which divides by their symbolic size
+    Mean, 1, |g, x, a, axes| {
+        let n = reduced_elements(g, a, axes)?;
+        let inv_n = ids::reciprocal(g, n)?;
+        ids::broadcast_to(g, inv_n, a)
+    }
+);
+
+reduction_operator!(
+    /// `log(sum(exp(a)))` over the axes, computed by first subtracting the
+    /// maximum so that it does not overflow. Its derivative is the softmax.
+    LogSumExp, 3, |g, x, a, axes| {
+        let x = ids::broadcast_to(g, x, a)?;
+        let shifted = ids::sub(g, a, x)?;
+        ids::exp(g, shifted)
+    }
+);
+
+reduction_operator!(
+    /// The population variance over the axes, which divides by their size
+    Variance, 3, |g, x, a, axes| {
+        let mean = ids::mean(g, a, *axes)?;
+        let mean = ids::broadcast_to(g, mean, a)?;
+        let centered = ids::sub(g, a, mean)?;
+        let n = reduced_elements(g, a, axes)?;
+        let two = g.scalar(2.0, FundamentalType::Float);
+        let scale = ids::div(g, two, n)?;
+        let scale = ids::broadcast_to(g, scale, a)?;
+        ids::mul(g, vec![scale, centered])
+    }
+);
+
+reduction_operator!(
+    /// The linear index of the maximum within the axes, with the first axis
+    /// changing fastest. The first of several equal elements is chosen.
+    ArgMax, 1, FundamentalType::UnsignedInt
+);
+
+reduction_operator!(
+    /// The linear index of the minimum within the axes, with the first axis
+    /// changing fastest. The first of several equal elements is chosen.
+    ArgMin, 1, FundamentalType::UnsignedInt
+);
+
+reduction_operator!(
+    /// Whether any of the elements over the axes is true
+    Any, 1, FundamentalType::Boolean
+);
+
+reduction_operator!(
+    /// Whether all of the elements over the axes are true
+    All, 1, FundamentalType::Boolean
+);
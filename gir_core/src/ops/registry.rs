@@ -0,0 +1,582 @@
+use ops::*;
+use primitives::*;
+use errors::*;
+use std::collections::HashMap;
+
+/// A single operator argument in a form which is independent of the concrete
+/// operator, such that it can be printed, serialized and compared.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OpArg {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    DataType(FundamentalType),
+    Axis(Axis),
+    SymInt(SymInt),
+    Shape(Shape),
+    List(Vec<OpArg>)
+}
+
+fn unexpected<T>(expected: &str, found: &OpArg) -> Result<T> {
+    Err(ErrorKind::Msg(format!("Expected an operator argument of type {}, found {:?}.",
+                               expected, found)).into())
+}
+
+impl OpArg {
+    pub fn as_bool(&self) -> Result<bool> {
+        match *self {
+            OpArg::Bool(v) => Ok(v),
+            ref arg => unexpected("Bool", arg)
+        }
+    }
+
+    pub fn as_int(&self) -> Result<i64> {
+        match *self {
+            OpArg::Int(v) => Ok(v),
+            ref arg => unexpected("Int", arg)
+        }
+    }
+
+    pub fn as_f64(&self) -> Result<f64> {
+        match *self {
+            OpArg::Float(v) => Ok(v),
+            OpArg::Int(v) => Ok(v as f64),
+            ref arg => unexpected("Float", arg)
+        }
+    }
+
+    pub fn as_string(&self) -> Result<String> {
+        match *self {
+            OpArg::Str(ref v) => Ok(v.clone()),
+            ref arg => unexpected("Str", arg)
+        }
+    }
+
+    pub fn as_data_type(&self) -> Result<FundamentalType> {
+        match *self {
+            OpArg::DataType(v) => Ok(v),
+            ref arg => unexpected("DataType", arg)
+        }
+    }
+
+    pub fn as_axis(&self) -> Result<Axis> {
+        match *self {
+            OpArg::Axis(v) => Ok(v),
+            OpArg::Int(0) => Ok(Axis::Axis0),
+            OpArg::Int(1) => Ok(Axis::Axis1),
+            OpArg::Int(2) => Ok(Axis::Axis2),
+            OpArg::Int(3) => Ok(Axis::Axis3),
+            ref arg => unexpected("Axis", arg)
+        }
+    }
+
+    pub fn as_sym_int(&self) -> Result<SymInt> {
+        match *self {
+            OpArg::SymInt(ref v) => Ok(v.clone()),
+            OpArg::Int(v) => Ok(v.into()),
+            ref arg => unexpected("SymInt", arg)
+        }
+    }
+
+    pub fn as_shape(&self) -> Result<Shape> {
+        match *self {
+            OpArg::Shape(ref v) => Ok(v.clone()),
+            ref arg => unexpected("Shape", arg)
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&Vec<OpArg>> {
+        match *self {
+            OpArg::List(ref v) => Ok(v),
+            ref arg => unexpected("List", arg)
+        }
+    }
+
+    pub fn as_strings(&self) -> Result<Vec<String>> {
+        self.as_list()?.iter().map(|x| x.as_string()).collect()
+    }
+
+    pub fn as_bool4(&self) -> Result<[bool; 4]> {
+        let list = self.as_list()?;
+        if list.len() != 4 {
+            return unexpected("List of 4 Bool", self)
+        }
+        Ok([list[0].as_bool()?, list[1].as_bool()?, list[2].as_bool()?, list[3].as_bool()?])
+    }
+
+    pub fn as_axis4(&self) -> Result<[Axis; 4]> {
+        let list = self.as_list()?;
+        if list.len() != 4 {
+            return unexpected("List of 4 Axis", self)
+        }
+        Ok([list[0].as_axis()?, list[1].as_axis()?, list[2].as_axis()?, list[3].as_axis()?])
+    }
+
+    pub fn from_bool4(values: &[bool; 4]) -> Self {
+        OpArg::List(values.iter().map(|&x| OpArg::Bool(x)).collect())
+    }
+
+    pub fn from_axis4(values: &[Axis; 4]) -> Self {
+        OpArg::List(values.iter().map(|&x| OpArg::Axis(x)).collect())
+    }
+
+    pub fn from_strings(values: &[String]) -> Self {
+        OpArg::List(values.iter().map(|x| OpArg::Str(x.clone())).collect())
+    }
+}
+
+/// Verifies the number of arguments given to a decoder
+pub fn expect_args(name: &str, args: &[OpArg], n: usize) -> Result<()> {
+    if args.len() != n {
+        Err(ErrorKind::Msg(format!("The operator {} expects {} arguments, but {} were given.",
+                                   name, n, args.len())).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Converts the arguments of an operator to `OpArg`s
+pub type ArgsEncoder = fn(&Operator) -> Vec<OpArg>;
+
+/// Reconstructs an operator from its `OpArg`s
+pub type OperatorDecoder = fn(&[OpArg]) -> Result<Box<Operator>>;
+
+/// Registry of all operators which can be reconstructed by their name.
+///
+/// The default registry contains all of the operators in `gir_core::ops`, except
+/// for `CustomGradient`, whose rule can not be reconstructed.
+/// Any operators defined outside of the crate must be registered in order to
+/// be exported or imported.
+#[derive(Clone)]
+pub struct OperatorRegistry {
+    entries: HashMap<String, (ArgsEncoder, OperatorDecoder)>
+}
+
+impl ::std::fmt::Debug for OperatorRegistry {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let mut names: Vec<&String> = self.entries.keys().collect();
+        names.sort();
+        write!(fmt, "OperatorRegistry {{ operators: {:?} }}", names)
+    }
+}
+
+fn no_args(_: &Operator) -> Vec<OpArg> {
+    Vec::new()
+}
+
+/// The axes of any reduction
+fn reduction_axes(op: &Operator) -> Vec<OpArg> {
+    vec![OpArg::from_bool4(&*op.get_args().unwrap().downcast::<[bool; 4]>().unwrap())]
+}
+
+/// The ranges of a slice, where every axis is either an empty list or a list
+/// of the start, the stop and the step
+fn slice_ranges(op: &Operator) -> Vec<OpArg> {
+    let ranges = op.get_args().unwrap().downcast::<[Option<SliceRange>; 4]>().unwrap();
+    vec![OpArg::List(ranges.iter().map(|range| match *range {
+        Some(ref range) => OpArg::List(vec![OpArg::SymInt(range.start.clone()),
+                                            OpArg::SymInt(range.stop.clone()),
+                                            OpArg::Int(range.step)]),
+        None => OpArg::List(Vec::new())
+    }).collect())]
+}
+
+fn as_slice_ranges(arg: &OpArg) -> Result<[Option<SliceRange>; 4]> {
+    let list = arg.as_list()?;
+    if list.len() != 4 {
+        return unexpected("List of 4 slice ranges", arg)
+    }
+    let mut ranges = [None, None, None, None];
+    for (range, value) in ranges.iter_mut().zip(list.iter()) {
+        let value = value.as_list()?;
+        match value.len() {
+            0 => {},
+            3 => *range = Some(SliceRange::new(value[0].as_sym_int()?, value[1].as_sym_int()?,
+                                               value[2].as_int()?)),
+            _ => return unexpected("slice range", arg)
+        }
+    }
+    Ok(ranges)
+}
+
+/// The axis of a gather or a scatter
+fn indexing_axis(op: &Operator) -> Vec<OpArg> {
+    vec![OpArg::Axis(*op.get_args().unwrap().downcast::<Axis>().unwrap())]
+}
+
+impl OperatorRegistry {
+    pub fn empty() -> Self {
+        OperatorRegistry {
+            entries: HashMap::new()
+        }
+    }
+
+    /// Registers an operator, returns `false` if it was already registered,
+    /// in which case the previous entry is replaced.
+    pub fn register(&mut self, name: &str, encoder: ArgsEncoder, decoder: OperatorDecoder) -> bool {
+        self.entries.insert(name.into(), (encoder, decoder)).is_none()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    pub fn encode(&self, op: &Operator) -> Result<Vec<OpArg>> {
+        let name = op.get_meta().name;
+        match self.entries.get(name) {
+            Some(&(encoder, _)) => Ok(encoder(op)),
+            None => Err(ErrorKind::Msg(format!("The operator {} is not registered.", name)).into())
+        }
+    }
+
+    pub fn decode(&self, name: &str, args: &[OpArg]) -> Result<Box<Operator>> {
+        match self.entries.get(name) {
+            Some(&(_, decoder)) => decoder(args),
+            None => Err(ErrorKind::Msg(format!("The operator {} is not registered.", name)).into())
+        }
+    }
+}
+
+impl Default for OperatorRegistry {
+    fn default() -> Self {
+        let mut registry = OperatorRegistry::empty();
+        // Input
+        registry.register("Input", |op: &Operator| {
+            let (data_type, shape) = *op.get_args().unwrap()
+                .downcast::<(FundamentalType, Shape)>().unwrap();
+            vec![OpArg::DataType(data_type), OpArg::Shape(shape)]
+        }, |args: &[OpArg]| {
+            expect_args("Input", args, 2)?;
+            Ok(Box::new(Input {
+                data_type: args[0].as_data_type()?,
+                shape: args[1].as_shape()?
+            }))
+        });
+        registry.register("Parameter", |op: &Operator| {
+            let (data_type, shape, name) = *op.get_args().unwrap()
+                .downcast::<(FundamentalType, Shape, Vec<String>)>().unwrap();
+            vec![OpArg::DataType(data_type), OpArg::Shape(shape), OpArg::from_strings(&name)]
+        }, |args: &[OpArg]| {
+            expect_args("Parameter", args, 3)?;
+            Ok(Box::new(Parameter {
+                param_name: args[2].as_strings()?,
+                data_type: args[0].as_data_type()?,
+                shape: args[1].as_shape()?
+            }))
+        });
+        registry.register("Scalar", |op: &Operator| {
+            let (value, data_type) = *op.get_args().unwrap()
+                .downcast::<(f64, FundamentalType)>().unwrap();
+            vec![OpArg::Float(value), OpArg::DataType(data_type)]
+        }, |args: &[OpArg]| {
+            expect_args("Scalar", args, 2)?;
+            Ok(Box::new(Scalar {
+                value: args[0].as_f64()?,
+                data_type: args[1].as_data_type()?
+            }))
+        });
+        registry.register("Basis", |op: &Operator| {
+            let (index, shape, data_type) = *op.get_args().unwrap()
+                .downcast::<(usize, Shape, FundamentalType)>().unwrap();
+            vec![OpArg::Int(index as i64), OpArg::Shape(shape), OpArg::DataType(data_type)]
+        }, |args: &[OpArg]| {
+            expect_args("Basis", args, 3)?;
+            Ok(Box::new(Basis {
+                index: args[0].as_int()? as usize,
+                shape: args[1].as_shape()?,
+                data_type: args[2].as_data_type()?
+            }))
+        });
+        registry.register("SymIntInput", |op: &Operator| {
+            vec![OpArg::Str(*op.get_args().unwrap().downcast::<String>().unwrap())]
+        }, |args: &[OpArg]| {
+            expect_args("SymIntInput", args, 1)?;
+            Ok(Box::new(SymIntInput { identifier: args[0].as_string()? }))
+        });
+        registry.register("Cleared", no_args, |args: &[OpArg]| {
+            expect_args("Cleared", args, 0)?;
+            Ok(Box::new(Cleared {}))
+        });
+        // Shape
+        registry.register("TensorShape", |op: &Operator| {
+            vec![OpArg::Axis(*op.get_args().unwrap().downcast::<Axis>().unwrap())]
+        }, |args: &[OpArg]| {
+            expect_args("TensorShape", args, 1)?;
+            Ok(Box::new(TensorShape { axis: args[0].as_axis()? }))
+        });
+        // Arithmetic
+        registry.register("Add", no_args, |args: &[OpArg]| {
+            expect_args("Add", args, 0)?;
+            Ok(Box::new(Add {}))
+        });
+        registry.register("Neg", no_args, |args: &[OpArg]| {
+            expect_args("Neg", args, 0)?;
+            Ok(Box::new(Neg {}))
+        });
+        registry.register("Mul", no_args, |args: &[OpArg]| {
+            expect_args("Mul", args, 0)?;
+            Ok(Box::new(Mul {}))
+        });
+        registry.register("Div", no_args, |args: &[OpArg]| {
+            expect_args("Div", args, 0)?;
+            Ok(Box::new(Div {}))
+        });
+        registry.register("Pow", no_args, |args: &[OpArg]| {
+            expect_args("Pow", args, 0)?;
+            Ok(Box::new(Pow {}))
+        });
+        registry.register("Maximum", no_args, |args: &[OpArg]| {
+            expect_args("Maximum", args, 0)?;
+            Ok(Box::new(Maximum {}))
+        });
+        registry.register("Minimum", no_args, |args: &[OpArg]| {
+            expect_args("Minimum", args, 0)?;
+            Ok(Box::new(Minimum {}))
+        });
+        registry.register("Atan2", no_args, |args: &[OpArg]| {
+            expect_args("Atan2", args, 0)?;
+            Ok(Box::new(Atan2 {}))
+        });
+        registry.register("Clamp", no_args, |args: &[OpArg]| {
+            expect_args("Clamp", args, 0)?;
+            Ok(Box::new(Clamp {}))
+        });
+        // Logical
+        registry.register("Gt", no_args, |args: &[OpArg]| {
+            expect_args("Gt", args, 0)?;
+            Ok(Box::new(Gt {}))
+        });
+        registry.register("Ge", no_args, |args: &[OpArg]| {
+            expect_args("Ge", args, 0)?;
+            Ok(Box::new(Ge {}))
+        });
+        registry.register("Lt", no_args, |args: &[OpArg]| {
+            expect_args("Lt", args, 0)?;
+            Ok(Box::new(Lt {}))
+        });
+        registry.register("Le", no_args, |args: &[OpArg]| {
+            expect_args("Le", args, 0)?;
+            Ok(Box::new(Le {}))
+        });
+        registry.register("Equal", no_args, |args: &[OpArg]| {
+            expect_args("Equal", args, 0)?;
+            Ok(Box::new(Equal {}))
+        });
+        registry.register("Ne", no_args, |args: &[OpArg]| {
+            expect_args("Ne", args, 0)?;
+            Ok(Box::new(Ne {}))
+        });
+        registry.register("And", no_args, |args: &[OpArg]| {
+            expect_args("And", args, 0)?;
+            Ok(Box::new(And {}))
+        });
+        registry.register("Or", no_args, |args: &[OpArg]| {
+            expect_args("Or", args, 0)?;
+            Ok(Box::new(Or {}))
+        });
+        registry.register("Not", no_args, |args: &[OpArg]| {
+            expect_args("Not", args, 0)?;
+            Ok(Box::new(Not {}))
+        });
+        registry.register("Xor", no_args, |args: &[OpArg]| {
+            expect_args("Xor", args, 0)?;
+            Ok(Box::new(Xor {}))
+        });
+        registry.register("Select", no_args, |args: &[OpArg]| {
+            expect_args("Select", args, 0)?;
+            Ok(Box::new(Select {}))
+        });
+        // Special
+        registry.register("Update", no_args, |args: &[OpArg]| {
+            expect_args("Update", args, 0)?;
+            Ok(Box::new(Update {}))
+        });
+        registry.register("Cast", |op: &Operator| {
+            vec![OpArg::DataType(*op.get_args().unwrap().downcast::<FundamentalType>().unwrap())]
+        }, |args: &[OpArg]| {
+            expect_args("Cast", args, 1)?;
+            Ok(Box::new(Cast { data_type: args[0].as_data_type()? }))
+        });
+        registry.register("Broadcast", |op: &Operator| {
+            vec![OpArg::from_bool4(&*op.get_args().unwrap().downcast::<[bool; 4]>().unwrap())]
+        }, |args: &[OpArg]| {
+            expect_args("Broadcast", args, 1)?;
+            Ok(Box::new(Broadcast { axes: args[0].as_bool4()? }))
+        });
+        registry.register("MakeConstant", no_args, |args: &[OpArg]| {
+            expect_args("MakeConstant", args, 0)?;
+            Ok(Box::new(MakeConstant {}))
+        });
+        registry.register("Rematerialize", no_args, |args: &[OpArg]| {
+            expect_args("Rematerialize", args, 0)?;
+            Ok(Box::new(Rematerialize {}))
+        });
+        registry.register("Reorder", |op: &Operator| {
+            vec![OpArg::from_axis4(&*op.get_args().unwrap().downcast::<[Axis; 4]>().unwrap())]
+        }, |args: &[OpArg]| {
+            expect_args("Reorder", args, 1)?;
+            Ok(Box::new(Reorder { order: args[0].as_axis4()? }))
+        });
+        // Linear algebra
+        registry.register("MatMul", no_args, |args: &[OpArg]| {
+            expect_args("MatMul", args, 0)?;
+            Ok(Box::new(MatMul {}))
+        });
+        registry.register("Einsum", |op: &Operator| {
+            let (inputs, output) = *op.get_args().unwrap()
+                .downcast::<(Vec<Vec<char>>, Vec<char>)>().unwrap();
+            let op = Einsum { inputs: inputs, output: output };
+            vec![OpArg::Str(op.spec())]
+        }, |args: &[OpArg]| {
+            expect_args("Einsum", args, 1)?;
+            Ok(Box::new(Einsum::parse(&args[0].as_string()?)?))
+        });
+        // Nonlinear
+        registry.register("Tanh", no_args, |args: &[OpArg]| {
+            expect_args("Tanh", args, 0)?;
+            Ok(Box::new(Tanh {}))
+        });
+        registry.register("Exp", no_args, |args: &[OpArg]| {
+            expect_args("Exp", args, 0)?;
+            Ok(Box::new(Exp {}))
+        });
+        registry.register("Log", no_args, |args: &[OpArg]| {
+            expect_args("Log", args, 0)?;
+            Ok(Box::new(Log {}))
+        });
+        registry.register("Log1p", no_args, |args: &[OpArg]| {
+            expect_args("Log1p", args, 0)?;
+            Ok(Box::new(Log1p {}))
+        });
+        registry.register("Expm1", no_args, |args: &[OpArg]| {
+            expect_args("Expm1", args, 0)?;
+            Ok(Box::new(Expm1 {}))
+        });
+        registry.register("Sqrt", no_args, |args: &[OpArg]| {
+            expect_args("Sqrt", args, 0)?;
+            Ok(Box::new(Sqrt {}))
+        });
+        registry.register("Rsqrt", no_args, |args: &[OpArg]| {
+            expect_args("Rsqrt", args, 0)?;
+            Ok(Box::new(Rsqrt {}))
+        });
+        registry.register("Square", no_args, |args: &[OpArg]| {
+            expect_args("Square", args, 0)?;
+            Ok(Box::new(Square {}))
+        });
+        registry.register("Sigmoid", no_args, |args: &[OpArg]| {
+            expect_args("Sigmoid", args, 0)?;
+            Ok(Box::new(Sigmoid {}))
+        });
+        registry.register("Softplus", no_args, |args: &[OpArg]| {
+            expect_args("Softplus", args, 0)?;
+            Ok(Box::new(Softplus {}))
+        });
+        registry.register("Relu", no_args, |args: &[OpArg]| {
+            expect_args("Relu", args, 0)?;
+            Ok(Box::new(Relu {}))
+        });
+        registry.register("Abs", no_args, |args: &[OpArg]| {
+            expect_args("Abs", args, 0)?;
+            Ok(Box::new(Abs {}))
+        });
+        registry.register("Sign", no_args, |args: &[OpArg]| {
+            expect_args("Sign", args, 0)?;
+            Ok(Box::new(Sign {}))
+        });
+        registry.register("Sin", no_args, |args: &[OpArg]| {
+            expect_args("Sin", args, 0)?;
+            Ok(Box::new(Sin {}))
+        });
+        registry.register("Cos", no_args, |args: &[OpArg]| {
+            expect_args("Cos", args, 0)?;
+            Ok(Box::new(Cos {}))
+        });
+        registry.register("Tan", no_args, |args: &[OpArg]| {
+            expect_args("Tan", args, 0)?;
+            Ok(Box::new(Tan {}))
+        });
+        registry.register("Sinh", no_args, |args: &[OpArg]| {
+            expect_args("Sinh", args, 0)?;
+            Ok(Box::new(Sinh {}))
+        });
+        registry.register("Cosh", no_args, |args: &[OpArg]| {
+            expect_args("Cosh", args, 0)?;
+            Ok(Box::new(Cosh {}))
+        });
+        registry.register("Erf", no_args, |args: &[OpArg]| {
+            expect_args("Erf", args, 0)?;
+            Ok(Box::new(Erf {}))
+        });
+        // Reductions
+        registry.register("Sum", |op: &Operator| {
+            vec![OpArg::from_bool4(&*op.get_args().unwrap().downcast::<[bool; 4]>().unwrap())]
+        }, |args: &[OpArg]| {
+            expect_args("Sum", args, 1)?;
+            Ok(Box::new(Sum { axes: args[0].as_bool4()? }))
+        });
+        registry.register("Max", reduction_axes, |args: &[OpArg]| {
+            expect_args("Max", args, 1)?;
+            Ok(Box::new(Max { axes: args[0].as_bool4()? }))
+        });
+        registry.register("Min", reduction_axes, |args: &[OpArg]| {
+            expect_args("Min", args, 1)?;
+            Ok(Box::new(Min { axes: args[0].as_bool4()? }))
+        });
+        registry.register("Prod", reduction_axes, |args: &[OpArg]| {
+            expect_args("Prod", args, 1)?;
+            Ok(Box::new(Prod { axes: args[0].as_bool4()? }))
+        });
+        registry.register("Mean", reduction_axes, |args: &[OpArg]| {
+            expect_args("Mean", args, 1)?;
+            Ok(Box::new(Mean { axes: args[0].as_bool4()? }))
+        });
+        registry.register("LogSumExp", reduction_axes, |args: &[OpArg]| {
+            expect_args("LogSumExp", args, 1)?;
+            Ok(Box::new(LogSumExp { axes: args[0].as_bool4()? }))
+        });
+        registry.register("Variance", reduction_axes, |args: &[OpArg]| {
+            expect_args("Variance", args, 1)?;
+            Ok(Box::new(Variance { axes: args[0].as_bool4()? }))
+        });
+        registry.register("ArgMax", reduction_axes, |args: &[OpArg]| {
+            expect_args("ArgMax", args, 1)?;
+            Ok(Box::new(ArgMax { axes: args[0].as_bool4()? }))
+        });
+        registry.register("ArgMin", reduction_axes, |args: &[OpArg]| {
+            expect_args("ArgMin", args, 1)?;
+            Ok(Box::new(ArgMin { axes: args[0].as_bool4()? }))
+        });
+        registry.register("Any", reduction_axes, |args: &[OpArg]| {
+            expect_args("Any", args, 1)?;
+            Ok(Box::new(Any { axes: args[0].as_bool4()? }))
+        });
+        registry.register("All", reduction_axes, |args: &[OpArg]| {
+            expect_args("All", args, 1)?;
+            Ok(Box::new(All { axes: args[0].as_bool4()? }))
+        });
+        // Indexing
+        registry.register("Slice", slice_ranges, |args: &[OpArg]| {
+            expect_args("Slice", args, 1)?;
+            Ok(Box::new(Slice { ranges: as_slice_ranges(&args[0])? }))
+        });
+        registry.register("SliceAdd", slice_ranges, |args: &[OpArg]| {
+            expect_args("SliceAdd", args, 1)?;
+            Ok(Box::new(SliceAdd { ranges: as_slice_ranges(&args[0])? }))
+        });
+        registry.register("Gather", indexing_axis, |args: &[OpArg]| {
+            expect_args("Gather", args, 1)?;
+            Ok(Box::new(Gather { axis: args[0].as_axis()? }))
+        });
+        registry.register("Scatter", indexing_axis, |args: &[OpArg]| {
+            expect_args("Scatter", args, 1)?;
+            Ok(Box::new(Scatter { axis: args[0].as_axis()? }))
+        });
+        registry.register("ScatterAdd", indexing_axis, |args: &[OpArg]| {
+            expect_args("ScatterAdd", args, 1)?;
+            Ok(Box::new(ScatterAdd { axis: args[0].as_axis()? }))
+        });
+        registry
+    }
+}
@@ -19,10 +19,20 @@ impl Operator for TensorShape {
         Ok(Vec::new())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(None)
+    }
+
     fn clone_box(&self) -> Box<Operator> {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(format!("TensorShape({})", self.axis))
+    }
+
     fn get_args(&self) -> Option<Box<Any>> {
         Some(Box::new((self.axis)))
     }
@@ -40,6 +50,7 @@ impl Operator for TensorShape {
             differentiable: false,
             scalar_output: true,
             shape_operator: true,
+            copy_operator: false,
             fixed_output_type: Some(FundamentalType::UnsignedInt),
         };
         &TENSOR_SHAPE
@@ -99,6 +110,7 @@ impl Operator for TensorShape {
 //            differentiable: false,
 //            scalar_output: true,
 //            shape_operator: true,
+//            copy_operator: false,
 //            fixed_output_type: Some(FundamentalType::UnsignedInt),
 //        };
 //        &TENSOR_SHAPE
@@ -2,8 +2,10 @@ use ops::interface::*;
 use primitives::*;
 use graph::*;
 use errors::*;
+use backend::BackendPrecisions;
 use api::*;
 use std::any::Any;
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub struct Update {}
@@ -15,6 +17,13 @@ impl Operator for Update {
         unimplemented!()
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // Updates are never used by other expressions
+        Ok(None)
+    }
+
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
@@ -50,6 +59,10 @@ impl Operator for Update {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        None
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static UPDATE: OperatorMetaData = OperatorMetaData{
             name: "Update",
@@ -63,10 +76,16 @@ impl Operator for Update {
             differentiable: false,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &UPDATE
     }
+
+    /// A copy of the new value without any operations
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        default::get_memory_cost(g, x, precisions)
+    }
 }
 
 
@@ -86,6 +105,15 @@ impl Operator for Cast {
         }
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        match dx[0] {
+            Some(d) => Ok(Some(ids::cast(g, d, self.data_type)?)),
+            None => Ok(None)
+        }
+    }
+
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
@@ -109,6 +137,10 @@ impl Operator for Cast {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Cast({})", self.data_type))
+    }
+
     fn get_args(&self) -> Option<Box<Any>> {
         Some(Box::new((self.data_type)))
     }
@@ -126,6 +158,7 @@ impl Operator for Cast {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: true,
             fixed_output_type: None,
         };
         &CAST
@@ -153,6 +186,20 @@ impl Operator for Broadcast {
         }
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // The shape arguments are not differentiable
+        match dx[0] {
+            Some(d) => {
+                let mut args = g.get_node(x)?.ancestors.clone();
+                args[0] = d;
+                Ok(Some(g.apply_op(Box::new(self.clone()), args)?))
+            },
+            None => Ok(None)
+        }
+    }
+
     fn verify_args(&self, g: &mut Graph, args: Vec<usize>) -> Result<Vec<usize>> {
         let meta = self.get_meta();
         let args = default::verify_args(meta, g, args)?;
@@ -183,6 +230,10 @@ impl Operator for Broadcast {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Broadcast{:?}", self.axes))
+    }
+
     fn get_args(&self) -> Option<Box<Any>> {
         Some(Box::new((self.axes.clone())))
     }
@@ -200,6 +251,7 @@ impl Operator for Broadcast {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: false,
             fixed_output_type: None,
         };
         &BROADCAST
@@ -218,6 +270,12 @@ impl Operator for Broadcast {
         }
         shapes
     }
+
+    /// Broadcasts are computed on the fly by the operators using them
+    #[allow(unused_variables, unused_mut)]
+    fn get_cost(&self, g: &Graph, x: usize, precisions: &BackendPrecisions) -> OperatorCost {
+        OperatorCost::default()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -231,10 +289,21 @@ impl Operator for MakeConstant {
         Ok(Vec::new())
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        // No gradients are passed trough this operator
+        Ok(None)
+    }
+
     fn clone_box(&self) -> Box<Operator> {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
     fn get_meta(&self) -> &OperatorMetaData {
         static MAKE_CONSTANT: OperatorMetaData = OperatorMetaData{
             name: "MakeConstant",
@@ -248,12 +317,148 @@ impl Operator for MakeConstant {
             differentiable: false,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: true,
             fixed_output_type: None,
         };
         &MAKE_CONSTANT
     }
 }
 
+/// A user supplied reverse rule. It receives the graph, the wrapped expression and
+/// the derivative with respect to it and returns the derivatives of the wrapped
+/// expression or of any of its ancestors.
+pub type GradientRule = Rc<Fn(&mut Graph, usize, usize) -> Result<Vec<(usize, usize)>>>;
+
+/// Passes its argument unchanged, but replaces its reverse mode derivative with
+/// a user supplied rule. The rule is not used in forward mode.
+///
+/// As the rule is a closure it can not be exported, thus the operator is not part of
+/// the `OperatorRegistry` and must be removed, e.g. by `AlgebraicSimplification`,
+/// before exporting the graph.
+#[derive(Clone)]
+pub struct CustomGradient {
+    pub rule: GradientRule
+}
+
+impl ::std::fmt::Debug for CustomGradient {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(fmt, "CustomGradient {{ .. }}")
+    }
+}
+
+impl Operator for CustomGradient {
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let ancestor = g.get_node(x)?.ancestors[0];
+        let derivatives = (self.rule)(g, ancestor, dx)?;
+        // Derivatives can be sent only backwards, to the expressions the rule can see
+        let allowed = g.get_ancestors(&vec![ancestor]);
+        for &(a, _) in &derivatives {
+            if a >= allowed.len() || !allowed[a] {
+                return Err(ErrorKind::InvalidArguments(
+                    String::new() + self.get_meta().name, vec![x, a],
+                    "The custom gradient rule returned a derivative of an expression, \
+                    which is not the wrapped expression or one of its ancestors.".into()).into())
+            }
+        }
+        Ok(derivatives.into_iter().filter(|&(a, _)| flow_tree[a]).collect())
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(dx[0])
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        // Rules can not be compared, so every expression is unique
+        None
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static CUSTOM_GRADIENT: OperatorMetaData = OperatorMetaData{
+            name: "CustomGradient",
+            arity: Arity::Unary,
+            num_outputs: 1,
+            differential_parents: 1,
+            ordered_parents: true,
+            elementwise: true,
+            type_preserving: true,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: true,
+            fixed_output_type: None,
+        };
+        &CUSTOM_GRADIENT
+    }
+}
+
+/// Passes its first argument unchanged and starts the recomputation of a checkpointed
+/// segment during the backward pass. The second argument is a derivative, which only
+/// orders the recomputation after it and separates the copies from the forward pass.
+#[derive(Debug, Clone)]
+pub struct Rematerialize {}
+
+impl Operator for Rematerialize {
+    #[allow(unused_variables, unused_mut)]
+    fn reverse_diff(&self, g: &mut Graph, x: usize, dx: usize, flow_tree: &Vec<bool>)
+                    -> Result<Vec<(usize, usize)>> {
+        let ancestor = g.get_node(x)?.ancestors[0];
+        if flow_tree[ancestor] {
+            Ok(vec![(ancestor, dx)])
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        Ok(dx[0])
+    }
+
+    fn clone_box(&self) -> Box<Operator> {
+        Box::new(self.clone())
+    }
+
+    fn get_key(&self) -> Option<String> {
+        Some(self.get_meta().name.into())
+    }
+
+    fn get_is_input_dependent(&self, g: &Graph, args: &Vec<usize>) -> bool {
+        g.get_node(args[0]).unwrap().is_input_dependent
+    }
+
+    fn get_is_differentiable(&self, g: &Graph, args: &Vec<usize>) -> bool {
+        g.get_node(args[0]).unwrap().is_differentiable
+    }
+
+    fn get_meta(&self) -> &OperatorMetaData {
+        static REMATERIALIZE: OperatorMetaData = OperatorMetaData{
+            name: "Rematerialize",
+            arity: Arity::Binary,
+            num_outputs: 1,
+            differential_parents: 1,
+            ordered_parents: true,
+            elementwise: true,
+            type_preserving: true,
+            reduction: false,
+            differentiable: true,
+            scalar_output: false,
+            shape_operator: false,
+            copy_operator: true,
+            fixed_output_type: None,
+        };
+        &REMATERIALIZE
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Reorder {
     pub order: [Axis; 4],
@@ -288,10 +493,23 @@ impl Operator for Reorder {
         }
     }
 
+    #[allow(unused_variables, unused_mut)]
+    fn forward_diff(&self, g: &mut Graph, x: usize, dx: &Vec<Option<usize>>)
+                    -> Result<Option<usize>> {
+        match dx[0] {
+            Some(d) => Ok(Some(ids::reorder(g, d, Some(self.order))?)),
+            None => Ok(None)
+        }
+    }
+
     fn clone_box(&self) -> Box<Operator> {
         Box::new(self.clone())
     }
 
+    fn get_key(&self) -> Option<String> {
+        Some(format!("Reorder{:?}", self.order))
+    }
+
     fn get_args(&self) -> Option<Box<Any>> {
         Some(Box::new((self.order.clone())))
     }
@@ -309,6 +527,7 @@ impl Operator for Reorder {
             differentiable: true,
             scalar_output: false,
             shape_operator: false,
+            copy_operator: true,
             fixed_output_type: None,
         };
         &REORDER
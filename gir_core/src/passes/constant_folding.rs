@@ -0,0 +1,127 @@
+use graph::*;
+use primitives::*;
+use ops::*;
+use errors::*;
+use passes::Pass;
+use passes::rewrite::rebuild;
+
+use std::collections::HashMap;
+
+/// Evaluates every scalar expression whose ancestors are all compile-time
+/// constants and replaces it with a single `Scalar`.
+///
+/// The constants are the `Scalar`s, the scalar `Basis` tensors and the
+/// expressions with a constant symbolic integer value, such as a `TensorShape`
+/// of a concrete dimension.
+/// Expressions with a symbolic value are never replaced themselves, as
+/// operators like `Broadcast` depend on it, but their value is used for
+/// folding their children. Complex expressions are not folded.
+#[derive(Debug, Clone, Default)]
+pub struct ConstantFolding {}
+
+impl Pass for ConstantFolding {
+    fn name(&self) -> &str {
+        "ConstantFolding"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        let mut folded = 0;
+        let (new_gf, _) = rebuild(gf, |graph, node, ancestors| {
+            let meta = node.op.get_meta();
+            if meta.arity == Arity::Nullary || node.sym_int.is_some() ||
+                node.data_type == FundamentalType::Complex || node.shape.order() != 0 {
+                return Ok(None)
+            }
+            let mut values = Vec::with_capacity(ancestors.len());
+            for &a in ancestors {
+                match constant_value(graph.get_node(a)?) {
+                    Some(v) => values.push(v),
+                    None => return Ok(None)
+                }
+            }
+            match fold_scalar(&*node.op, node.data_type, &values) {
+                Some(value) => {
+                    let op = Scalar {
+                        value: value,
+                        data_type: node.data_type
+                    };
+                    let mut data = op.apply_null();
+                    data.grad_level = node.grad_level;
+                    folded += 1;
+                    Ok(Some(graph.add_node(data)?))
+                },
+                None => Ok(None)
+            }
+        })?;
+        if folded > 0 {
+            debug!(gf.graph.log, "[passes] Folded {} constant expressions.", folded);
+            *gf = new_gf;
+        }
+        Ok(folded > 0)
+    }
+}
+
+/// The compile-time value of the expression, if it is a constant
+pub fn constant_value(node: &ExprData) -> Option<f64> {
+    if node.op.get_meta().name == "Scalar" {
+        let (value, _) = *node.op.get_args().unwrap().downcast::<(f64, FundamentalType)>().unwrap();
+        Some(value)
+    } else if node.op.get_meta().name == "Basis" && node.shape.order() == 0 {
+        Some(1.0)
+    } else {
+        match node.sym_int {
+            Some(ref value) if value.is_constant() =>
+                value.eval(&HashMap::new()).ok().map(|v| v as f64),
+            _ => None
+        }
+    }
+}
+
+/// Converts the value to the one representable by the data type
+pub fn convert_value(value: f64, data_type: FundamentalType) -> f64 {
+    match data_type {
+        FundamentalType::Boolean => if value != 0.0 { 1.0 } else { 0.0 },
+        FundamentalType::UnsignedInt | FundamentalType::SignedInt => value.trunc(),
+        FundamentalType::Float | FundamentalType::Complex => value
+    }
+}
+
+/// The value of a boolean
+fn truth(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+/// Evaluates the operator on scalar arguments, returns `None` if the
+/// operator is not supported.
+pub fn fold_scalar(op: &Operator, data_type: FundamentalType, args: &[f64]) -> Option<f64> {
+    let value = match op.get_meta().name {
+        "Add" => args.iter().fold(0.0, |acc, &x| acc + x),
+        "Mul" | "MatMul" => args.iter().fold(1.0, |acc, &x| acc * x),
+        "Neg" => -args[0],
+        "Div" if data_type == FundamentalType::Float => 1.0 / args[0],
+        "Pow" => args[0].powf(args[1]),
+        "Maximum" => args[0].max(args[1]),
+        "Minimum" => args[0].min(args[1]),
+        "Atan2" => args[0].atan2(args[1]),
+        "Clamp" => args[0].max(args[1]).min(args[2]),
+        "Gt" => truth(args[0] > args[1]),
+        "Ge" => truth(args[0] >= args[1]),
+        "Lt" => truth(args[0] < args[1]),
+        "Le" => truth(args[0] <= args[1]),
+        "Equal" => truth(args[0] == args[1]),
+        "Ne" => truth(args[0] != args[1]),
+        "And" => truth(args[0] != 0.0 && args[1] != 0.0),
+        "Or" => truth(args[0] != 0.0 || args[1] != 0.0),
+        "Not" => truth(args[0] == 0.0),
+        "Xor" => truth((args[0] != 0.0) != (args[1] != 0.0)),
+        "Select" => if args[0] != 0.0 { args[1] } else { args[2] },
+        "Cast" | "MakeConstant" | "Sum" | "Reorder" | "Broadcast" => args[0],
+        "Max" | "Min" | "Prod" | "Mean" | "LogSumExp" | "Any" | "All" => args[0],
+        "Variance" | "ArgMax" | "ArgMin" => 0.0,
+        name => match args.get(0).and_then(|&a| unary_value(name, a)) {
+            Some(value) => value,
+            None => return None
+        }
+    };
+    Some(convert_value(value, data_type))
+}
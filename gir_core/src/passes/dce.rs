@@ -0,0 +1,26 @@
+use graph::*;
+use errors::*;
+use passes::Pass;
+use passes::rewrite::{rebuild, live_mask};
+
+/// Removes all expressions which are not needed for the outputs or the
+/// updates of the function, including `Cleared` updates. The rewrite passes
+/// leave behind the expressions they have replaced and rely on this pass to
+/// remove them.
+#[derive(Debug, Clone, Default)]
+pub struct DeadCodeElimination {}
+
+impl Pass for DeadCodeElimination {
+    fn name(&self) -> &str {
+        "DeadCodeElimination"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        if live_mask(gf).iter().all(|&x| x) {
+            return Ok(false)
+        }
+        let (new_gf, _) = rebuild(gf, |_, _, _| Ok(None))?;
+        *gf = new_gf;
+        Ok(true)
+    }
+}
@@ -0,0 +1,178 @@
+use primitives::*;
+use graph::*;
+use ops::*;
+use errors::*;
+use api::ids;
+use passes::Pass;
+use passes::rewrite::rebuild;
+
+/// Rewrites every `Einsum` into `Reorder`, `MatMul`, `Mul` and `Sum` for backends
+/// without a native implementation. The arguments are contracted from left to right.
+/// Each pair is multiplied with a batched `MatMul`, when each of the labels of only
+/// the first, only the second and those summed over is at most one, otherwise the
+/// two are multiplied elementwise and summed.
+///
+/// Fails if any intermediate product would need more than four axes.
+#[derive(Debug, Clone, Default)]
+pub struct EinsumLowering {}
+
+impl Pass for EinsumLowering {
+    fn name(&self) -> &str {
+        "EinsumLowering"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        if !gf.graph.order.iter().any(|&x| gf.graph.nodes[x].op.get_meta().name == "Einsum") {
+            return Ok(false)
+        }
+        let (new_gf, _) = rebuild(gf, |graph, node, ancestors| {
+            if node.op.get_meta().name == "Einsum" {
+                let (inputs, output) = *node.op.get_args().unwrap()
+                    .downcast::<(Vec<Vec<char>>, Vec<char>)>().unwrap();
+                let op = Einsum { inputs: inputs, output: output };
+                Ok(Some(lower_einsum(graph, &op, ancestors)?))
+            } else {
+                Ok(None)
+            }
+        })?;
+        *gf = new_gf;
+        Ok(true)
+    }
+}
+
+/// The label of each axis of a tensor, where `None` marks an axis of size one
+type Layout = [Option<char>; 4];
+
+fn layout(labels: &[char]) -> Layout {
+    let mut layout = [None; 4];
+    for (k, &c) in labels.iter().enumerate() {
+        layout[k] = Some(c);
+    }
+    layout
+}
+
+fn labels(layout: &Layout) -> Vec<char> {
+    layout.iter().filter_map(|&c| c).collect()
+}
+
+/// Rewrites the contraction into `Reorder`, `MatMul`, `Mul` and `Sum`
+pub fn lower_einsum(graph: &mut Graph, op: &Einsum, args: &[usize]) -> Result<usize> {
+    let mut operands: Vec<(usize, Layout)> = args.iter().cloned()
+        .zip(op.inputs.iter().map(|x| layout(x))).collect();
+    while operands.len() > 1 {
+        let (a, a_layout) = operands.remove(0);
+        let (b, b_layout) = operands.remove(0);
+        let mut needed = op.output.clone();
+        for &(_, ref l) in &operands {
+            needed.extend(labels(l));
+        }
+        let product = contract_pair(graph, (a, a_layout), (b, b_layout), &needed)?;
+        operands.insert(0, product);
+    }
+    let (x, x_layout) = operands[0];
+    let (x, x_layout) = sum_out(graph, x, x_layout, &op.output)?;
+    arrange(graph, x, &x_layout, &layout(&op.output))
+}
+
+/// Sums over the labels which are not kept
+fn sum_out(graph: &mut Graph, x: usize, layout: Layout, keep: &[char]) -> Result<(usize, Layout)> {
+    let mut axes = [false; 4];
+    let mut new_layout = layout;
+    for k in 0..4 {
+        if let Some(c) = layout[k] {
+            if !keep.contains(&c) {
+                axes[k] = true;
+                new_layout[k] = None;
+            }
+        }
+    }
+    if axes.iter().any(|&x| x) {
+        Ok((ids::sum(graph, x, axes)?, new_layout))
+    } else {
+        Ok((x, layout))
+    }
+}
+
+/// Reorders the tensor to the target layout with the same labels
+fn arrange(graph: &mut Graph, x: usize, layout: &Layout, target: &Layout) -> Result<usize> {
+    let mut used = [false; 4];
+    let mut order = [Axis::Axis0; 4];
+    for k in 0..4 {
+        let source = (0..4).find(|&j| !used[j] && layout[j] == target[k]).unwrap();
+        used[source] = true;
+        order[k] = Axis::iter().nth(source).cloned().unwrap();
+    }
+    if order == [Axis::Axis0, Axis::Axis1, Axis::Axis2, Axis::Axis3] {
+        Ok(x)
+    } else {
+        ids::reorder(graph, x, Some(order))
+    }
+}
+
+fn contract_pair(graph: &mut Graph, a: (usize, Layout), b: (usize, Layout), needed: &[char])
+                 -> Result<(usize, Layout)> {
+    // Sum first over the labels of a single tensor which are not needed
+    let a_labels = labels(&a.1);
+    let b_labels = labels(&b.1);
+    let mut keep_a = needed.to_vec();
+    keep_a.extend(b_labels.iter().cloned());
+    let mut keep_b = needed.to_vec();
+    keep_b.extend(a_labels.iter().cloned());
+    let (a, a_layout) = sum_out(graph, a.0, a.1, &keep_a)?;
+    let (b, b_layout) = sum_out(graph, b.0, b.1, &keep_b)?;
+    let a_labels = labels(&a_layout);
+    let b_labels = labels(&b_layout);
+    let batch: Vec<char> = a_labels.iter().cloned()
+        .filter(|c| b_labels.contains(c) && needed.contains(c)).collect();
+    let contracted: Vec<char> = a_labels.iter().cloned()
+        .filter(|c| b_labels.contains(c) && !needed.contains(c)).collect();
+    let left: Vec<char> = a_labels.iter().cloned().filter(|c| !b_labels.contains(c)).collect();
+    let right: Vec<char> = b_labels.iter().cloned().filter(|c| !a_labels.contains(c)).collect();
+    if left.len() <= 1 && right.len() <= 1 && contracted.len() <= 1 && batch.len() <= 2 {
+        let batch0 = batch.get(0).cloned();
+        let batch1 = batch.get(1).cloned();
+        let k = contracted.get(0).cloned();
+        let m = left.get(0).cloned();
+        let n = right.get(0).cloned();
+        let a = arrange(graph, a, &a_layout, &[m, k, batch0, batch1])?;
+        let b = arrange(graph, b, &b_layout, &[k, n, batch0, batch1])?;
+        Ok((ids::mat_mul(graph, a, b)?, [m, n, batch0, batch1]))
+    } else {
+        let mut all = a_labels.clone();
+        all.extend(right.iter().cloned());
+        if all.len() > 4 {
+            return Err(ErrorKind::Msg(format!("Can not lower the contraction of the labels \
+            {:?} and {:?}, as their product has more than 4 axes.", a_labels, b_labels)).into())
+        }
+        let product_layout = layout(&all);
+        let mut a_target = product_layout;
+        let mut b_target = product_layout;
+        for k in 0..4 {
+            if let Some(c) = product_layout[k] {
+                if !a_labels.contains(&c) {
+                    a_target[k] = None;
+                }
+                if !b_labels.contains(&c) {
+                    b_target[k] = None;
+                }
+            }
+        }
+        let a = arrange(graph, a, &a_layout, &a_target)?;
+        let b = arrange(graph, b, &b_layout, &b_target)?;
+        // Broadcast each to the axes of the other
+        let mut a_shape = [None; 4];
+        let mut b_shape = [None; 4];
+        for (k, &axis) in Axis::iter().enumerate() {
+            if a_target[k].is_none() && b_target[k].is_some() {
+                a_shape[k] = Some(ids::dim(graph, b, axis)?);
+            }
+            if b_target[k].is_none() && a_target[k].is_some() {
+                b_shape[k] = Some(ids::dim(graph, a, axis)?);
+            }
+        }
+        let a = ids::broadcast(graph, a, a_shape)?;
+        let b = ids::broadcast(graph, b, b_shape)?;
+        let product = ids::mul(graph, vec![a, b])?;
+        sum_out(graph, product, product_layout, needed)
+    }
+}
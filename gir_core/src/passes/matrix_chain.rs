@@ -0,0 +1,37 @@
+use graph::*;
+use errors::*;
+use api::ids;
+use passes::Pass;
+use passes::rewrite::rebuild;
+
+/// Replaces every `MatMul` of more than two matrices with binary products in the
+/// order with the least number of operations. Symbolic dimensions are compared
+/// using the size hints of the graph.
+#[derive(Debug, Clone, Default)]
+pub struct MatrixChainOrdering {}
+
+impl Pass for MatrixChainOrdering {
+    fn name(&self) -> &str {
+        "MatrixChainOrdering"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        let chains = gf.graph.order.iter()
+            .filter(|&&x| gf.graph.nodes[x].op.get_meta().name == "MatMul" &&
+                gf.graph.nodes[x].ancestors.len() > 2)
+            .count();
+        if chains == 0 {
+            return Ok(false)
+        }
+        let (new_gf, _) = rebuild(gf, |graph, node, ancestors| {
+            if node.op.get_meta().name == "MatMul" && ancestors.len() > 2 {
+                Ok(Some(ids::mat_mul_chain(graph, ancestors.to_vec())?))
+            } else {
+                Ok(None)
+            }
+        })?;
+        debug!(gf.graph.log, "[passes] Ordered {} matrix chains.", chains);
+        *gf = new_gf;
+        Ok(true)
+    }
+}
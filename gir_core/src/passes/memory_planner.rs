@@ -0,0 +1,335 @@
+use primitives::*;
+use graph::*;
+use errors::*;
+use ops::Rematerialize;
+use passes::Pass;
+use backend::{AbstractMemoryMap, build_memory_map, live_ranges};
+
+use std::collections::{HashMap, HashSet};
+
+/// The memory available to a function, measured in number of elements
+#[derive(Debug, Clone)]
+pub struct MemoryBudget {
+    /// The maximum number of elements kept in memory at the same time
+    pub limit: SymInt,
+    /// Values of the symbolic integers used to evaluate the limit and the sizes
+    /// of the expressions
+    pub values: HashMap<String, i64>
+}
+
+impl MemoryBudget {
+    pub fn new(limit: SymInt, values: HashMap<String, i64>) -> Self {
+        MemoryBudget {
+            limit: limit,
+            values: values
+        }
+    }
+}
+
+/// The decisions of the `MemoryPlanner`
+#[derive(Debug, Clone)]
+pub struct MemoryPlan {
+    /// The order in which the expressions are computed, same as the order of the graph
+    pub schedule: Vec<usize>,
+    /// The memory map of the planned function
+    pub memory_map: AbstractMemoryMap,
+    /// The total size of the expressions kept in memory at the peak of the schedule
+    pub peak_memory: SymInt,
+    /// Pairs of expressions and their copies, which are recomputed rather than stored
+    pub recomputed: Vec<(usize, usize)>,
+    /// Expressions, which are computed just before their first use rather than stored
+    pub deferred: Vec<usize>
+}
+
+/// Changes the function such that the expressions kept in memory at the same time fit
+/// in the budget. At each step the peak of the schedule is found and one of the expressions
+/// kept over it, without being used, is either computed later or recomputed for its later
+/// uses, together with any of its arguments not in memory at the peak. The expressions are
+/// tried in the order of the memory they free at the peak, until one reduces the peak of
+/// the whole schedule or the number of positions reaching it.
+///
+/// If the budget can not be met the smallest peak found is used and a warning is logged.
+#[derive(Debug, Clone)]
+pub struct MemoryPlanner {
+    pub budget: MemoryBudget,
+    /// The plan of the last run
+    pub plan: Option<MemoryPlan>
+}
+
+impl MemoryPlanner {
+    pub fn new(budget: MemoryBudget) -> Self {
+        MemoryPlanner {
+            budget: budget,
+            plan: None
+        }
+    }
+}
+
+impl Pass for MemoryPlanner {
+    fn name(&self) -> &str {
+        "MemoryPlanner"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        let plan = plan_memory(gf, &self.budget)?;
+        let changed = !plan.recomputed.is_empty() || !plan.deferred.is_empty();
+        self.plan = Some(plan);
+        Ok(changed)
+    }
+}
+
+/// Runs the `MemoryPlanner` on the function and returns the plan
+pub fn plan_memory(gf: &mut GraphFunction, budget: &MemoryBudget) -> Result<MemoryPlan> {
+    let limit = eval_size(&budget.limit, &budget.values)?;
+    let mut recomputed = Vec::new();
+    let mut deferred = Vec::new();
+    loop {
+        let (step, peak, count) = find_peak(gf, &budget.values)?;
+        if peak <= limit {
+            break
+        }
+        let mut improved = false;
+        for candidate in candidates(gf, &budget.values, step)? {
+            let mut new_gf = gf.clone();
+            let copy = store_later(&mut new_gf, candidate, step)?;
+            let (_, new_peak, new_count) = find_peak(&new_gf, &budget.values)?;
+            if (new_peak, new_count) < (peak, count) {
+                debug!(gf.graph.log, "[planner] Reducing the peak memory of {} elements at {} \
+                by not storing {}.", peak, step, candidate);
+                *gf = new_gf;
+                match copy {
+                    Some(c) => recomputed.push((candidate, c)),
+                    None => deferred.push(candidate)
+                }
+                improved = true;
+                break
+            }
+        }
+        if !improved {
+            warn!(gf.graph.log, "[planner] The peak memory of {} elements does \
+            not fit in the budget of {} elements.", peak, limit);
+            break
+        }
+    }
+    let (step, _, _) = find_peak(gf, &budget.values)?;
+    let mut peak_memory: SymInt = 0.into();
+    for (id, range) in live_ranges(gf).into_iter().enumerate() {
+        if let Some((start, end)) = range {
+            if start <= step && step <= end {
+                peak_memory += &gf.graph.nodes[id].shape.elements();
+            }
+        }
+    }
+    Ok(MemoryPlan {
+        schedule: gf.graph.order.clone(),
+        memory_map: build_memory_map(gf),
+        peak_memory: peak_memory,
+        recomputed: recomputed,
+        deferred: deferred
+    })
+}
+
+/// Evaluates the size of an expression with the values of the symbolic integers
+pub fn eval_size(sym_int: &SymInt, values: &HashMap<String, i64>) -> Result<i64> {
+    sym_int.eval(values).map_err(|_| ErrorKind::Msg(format!(
+        "Could not evaluate the size {} with the values {:?}.",
+        sym_int.to_code(&|x: String| x), values)).into())
+}
+
+/// Returns the sizes of all expressions, which are zero if they do not occupy memory
+pub fn sizes(gf: &GraphFunction, values: &HashMap<String, i64>) -> Result<Vec<i64>> {
+    let mut sizes = vec![0; gf.graph.nodes.len()];
+    for (id, range) in live_ranges(gf).into_iter().enumerate() {
+        if range.is_some() {
+            sizes[id] = eval_size(&gf.graph.nodes[id].shape.elements(), values)?;
+        }
+    }
+    Ok(sizes)
+}
+
+/// Returns the first position in the order with the largest memory, the memory itself
+/// and the number of positions with the same memory
+pub fn find_peak(gf: &GraphFunction, values: &HashMap<String, i64>) -> Result<(usize, i64, usize)> {
+    let sizes = sizes(gf, values)?;
+    let mut change = vec![0; gf.graph.order.len() + 1];
+    for (id, range) in live_ranges(gf).into_iter().enumerate() {
+        if let Some((start, end)) = range {
+            change[start] += sizes[id];
+            change[end + 1] -= sizes[id];
+        }
+    }
+    let mut memory = 0;
+    let mut peak = (0, 0, 0);
+    for (step, &c) in change.iter().take(gf.graph.order.len()).enumerate() {
+        memory += c;
+        if memory > peak.1 {
+            peak = (step, memory, 1);
+        } else if memory == peak.1 {
+            peak.2 += 1;
+        }
+    }
+    Ok(peak)
+}
+
+/// Whether the expression can be computed again from its arguments
+fn is_recomputable(gf: &GraphFunction, x: usize) -> bool {
+    let ref node = gf.graph.nodes[x];
+    match node.op.get_meta().name {
+        "Input" | "Parameter" | "Update" | "Cleared" => false,
+        _ => !node.ancestors.is_empty() && !gf.outputs.contains(&x)
+    }
+}
+
+/// The argument of **x** which is passed trough a `Rematerialize` in its recomputation
+fn rematerialized_argument(graph: &Graph, x: usize) -> Option<usize> {
+    graph.nodes[x].ancestors.iter().position(|&a| {
+        let ref node = graph.nodes[a];
+        node.sym_int.is_none() && !node.op.get_meta().shape_operator
+    })
+}
+
+/// The arguments needed to recompute **x**, where the dependency of a `Rematerialize` is
+/// replaced by the step of the recomputation
+fn arguments(graph: &Graph, x: usize) -> &[usize] {
+    let ref node = graph.nodes[x];
+    if node.op.get_meta().name == "Rematerialize" {
+        &node.ancestors[..1]
+    } else {
+        &node.ancestors
+    }
+}
+
+/// Returns the memory added at the step by recomputing **x** after it, or `None` if that
+/// is not possible. The arguments which are not in memory at the step are recomputed
+/// as well, unless they are not recomputable, in which case they are kept until then.
+fn recomputation_cost<F>(gf: &GraphFunction, x: usize, sizes: &[i64], is_live: &F,
+                         visited: &mut HashSet<usize>) -> Option<i64>
+    where F: Fn(usize) -> bool {
+    let mut cost = 0;
+    let mut copied = false;
+    for &a in arguments(&gf.graph, x) {
+        if is_live(a) || sizes[a] == 0 || !visited.insert(a) {
+            continue
+        } else if is_recomputable(gf, a) {
+            cost += recomputation_cost(gf, a, sizes, is_live, visited)?;
+            copied = true;
+        } else {
+            cost += sizes[a];
+        }
+    }
+    if copied || rematerialized_argument(&gf.graph, x).is_some() {
+        Some(cost)
+    } else {
+        None
+    }
+}
+
+/// Returns the expressions kept over the step, which removal reduces the memory at
+/// the step, ordered by the reduction
+fn candidates(gf: &GraphFunction, values: &HashMap<String, i64>, step: usize)
+              -> Result<Vec<usize>> {
+    let ref graph = gf.graph;
+    let sizes = sizes(gf, values)?;
+    let ranges = live_ranges(gf);
+    let is_live = |id: usize| ranges[id].map(|(s, e)| s <= step && step <= e).unwrap_or(false);
+    let current = graph.order[step];
+    let mut candidates = Vec::new();
+    for &v in &graph.order[..step] {
+        if !is_recomputable(gf, v) || !is_live(v) || graph.nodes[v].children.contains(&current) {
+            continue
+        }
+        if let Some(cost) = recomputation_cost(gf, v, &sizes, &is_live, &mut HashSet::new()) {
+            if sizes[v] > cost {
+                candidates.push((v, sizes[v] - cost));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.1.cmp(&a.1));
+    Ok(candidates.into_iter().map(|(v, _)| v).collect())
+}
+
+/// Moves the expression `id` just before `limit` in the order, unless it is already before it
+fn place_before(order: &mut Vec<usize>, id: usize, limit: usize) {
+    let position = order.iter().position(|&x| x == id).unwrap();
+    let limit_position = order.iter().position(|&x| x == limit).unwrap();
+    if position > limit_position {
+        order.remove(position);
+        order.insert(limit_position, id);
+    }
+}
+
+/// Changes the function such that **x** is not kept over the step. If **x** is used only after
+/// the step it is computed just before its first use, otherwise it is recomputed for the later
+/// uses and the copy is returned.
+fn store_later(gf: &mut GraphFunction, x: usize, step: usize) -> Result<Option<usize>> {
+    let ranges = live_ranges(gf);
+    let position: HashMap<usize, usize> = gf.graph.order.iter().enumerate()
+        .map(|(i, &id)| (id, i)).collect();
+    let mut later: Vec<usize> = gf.graph.nodes[x].children.iter().cloned()
+        .filter(|c| position[c] > step).collect();
+    later.sort_by_key(|c| position[c]);
+    let first = match later.first() {
+        Some(&first) => first,
+        None => return Err(ErrorKind::Msg(format!("The expression {} is not used after \
+        the step {}.", x, step)).into())
+    };
+    if later.len() == gf.graph.nodes[x].children.len() {
+        let ref mut order = gf.graph.order;
+        order.retain(|&id| id != x);
+        let first_position = order.iter().position(|&id| id == first).unwrap();
+        order.insert(first_position, x);
+        return Ok(None)
+    }
+    let recompute: Vec<bool> = (0..gf.graph.nodes.len()).map(|id| {
+        let live = ranges[id].map(|(s, e)| s <= step && step <= e).unwrap_or(true);
+        !live && is_recomputable(gf, id)
+    }).collect();
+    let copy = copy_after(&mut gf.graph, x, step, first, &recompute, &mut HashMap::new())?;
+    let ref mut graph = gf.graph;
+    for c in later {
+        for a in graph.nodes[c].ancestors.iter_mut().filter(|a| **a == x) {
+            *a = copy;
+        }
+        graph.nodes[x].children.remove(&c);
+        graph.nodes[copy].children.insert(c);
+    }
+    graph.rebuild_expr_map();
+    Ok(Some(copy))
+}
+
+/// Adds a copy of **x**, computed after the step and before `first`, together with
+/// the copies of the arguments marked for recomputation
+fn copy_after(graph: &mut Graph, x: usize, step: usize, first: usize, recompute: &[bool],
+              copies: &mut HashMap<usize, usize>) -> Result<usize> {
+    if let Some(&c) = copies.get(&x) {
+        return Ok(c)
+    }
+    let ancestors = arguments(graph, x).to_vec();
+    let mut args = Vec::with_capacity(ancestors.len());
+    for &a in &ancestors {
+        if recompute[a] {
+            args.push(copy_after(graph, a, step, first, recompute, copies)?);
+        } else {
+            args.push(a);
+        }
+    }
+    let dependency = graph.order[step];
+    if graph.nodes[x].op.get_meta().name == "Rematerialize" {
+        // Copies of copies depend only on the new step
+        let copy = graph.apply_op(Box::new(Rematerialize {}), vec![args[0], dependency])?;
+        place_before(&mut graph.order, copy, first);
+        copies.insert(x, copy);
+        return Ok(copy)
+    } else if args == ancestors {
+        // The copy must not be merged with the original
+        let k = rematerialized_argument(graph, x).unwrap();
+        args[k] = graph.apply_op(Box::new(Rematerialize {}), vec![args[k], dependency])?;
+        place_before(&mut graph.order, args[k], first);
+    }
+    let op = graph.nodes[x].op.clone();
+    let copy = graph.apply_op(op, args)?;
+    place_before(&mut graph.order, copy, first);
+    graph.nodes[copy].name = format!("{}|rm[{}]", graph.nodes[x].name, x);
+    copies.insert(x, copy);
+    Ok(copy)
+}
@@ -0,0 +1,178 @@
+use graph::*;
+use errors::*;
+
+use std::time::{Duration, Instant};
+
+pub mod rewrite;
+pub mod verify;
+pub mod dce;
+pub mod constant_folding;
+pub mod simplify;
+pub mod matrix_chain;
+pub mod lowering;
+pub mod memory_planner;
+pub mod scheduling;
+
+pub use self::rewrite::*;
+pub use self::verify::*;
+pub use self::dce::*;
+pub use self::constant_folding::*;
+pub use self::simplify::*;
+pub use self::matrix_chain::*;
+pub use self::lowering::*;
+pub use self::memory_planner::*;
+pub use self::scheduling::*;
+
+/// An analysis or a transformation of a `GraphFunction`.
+pub trait Pass {
+    fn name(&self) -> &str;
+
+    /// Runs the pass on the function and returns whether it was changed.
+    /// Analyses should never change the function and can keep their results
+    /// in the pass itself.
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool>;
+}
+
+/// Predefined optimization pipelines
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd, Eq, Ord)]
+pub enum OptimizationLevel {
+    /// No optimizations
+    O0 = 0,
+    /// Cheap optimizations which are run only once
+    O1 = 1,
+    /// All optimizations, repeated until none of them changes the function
+    O2 = 2
+}
+
+impl ::std::fmt::Display for OptimizationLevel {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            OptimizationLevel::O0 => write!(fmt, "O0"),
+            OptimizationLevel::O1 => write!(fmt, "O1"),
+            OptimizationLevel::O2 => write!(fmt, "O2"),
+        }
+    }
+}
+
+/// Information about a single execution of a pass
+#[derive(Clone, Debug)]
+pub struct PassStatistics {
+    pub name: String,
+    pub duration: Duration,
+    pub nodes_before: usize,
+    pub nodes_after: usize,
+    pub changed: bool
+}
+
+/// Runs an ordered sequence of passes over a `GraphFunction`.
+///
+/// Every pass is logged and timed through the graph's logger. When `verify`
+/// is set, which is the default in debug builds, the function is verified
+/// before the first pass and after every pass which changed it. The whole
+/// sequence is repeated while any pass changes the function, but at most
+/// `max_iterations` times.
+pub struct PassManager {
+    pub passes: Vec<Box<Pass>>,
+    pub verify: bool,
+    pub max_iterations: usize,
+    pub statistics: Vec<PassStatistics>
+}
+
+impl Default for PassManager {
+    fn default() -> Self {
+        PassManager {
+            passes: Vec::new(),
+            verify: cfg!(debug_assertions),
+            max_iterations: 1,
+            statistics: Vec::new()
+        }
+    }
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        PassManager::default()
+    }
+
+    /// The default pipeline for the optimization level
+    pub fn with_level(level: OptimizationLevel) -> Self {
+        let mut manager = PassManager::new();
+        match level {
+            OptimizationLevel::O0 => {},
+            OptimizationLevel::O1 => {
+                manager.add(ConstantFolding::default());
+                manager.add(MatrixChainOrdering::default());
+                manager.add(DeadCodeElimination::default());
+            },
+            OptimizationLevel::O2 => {
+                manager.add(ConstantFolding::default());
+                manager.add(AlgebraicSimplification::default());
+                manager.add(MatrixChainOrdering::default());
+                manager.add(DeadCodeElimination::default());
+                manager.max_iterations = 10;
+            }
+        }
+        manager
+    }
+
+    pub fn add<P: Pass + 'static>(&mut self, pass: P) -> &mut Self {
+        self.passes.push(Box::new(pass));
+        self
+    }
+
+    pub fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        let log = gf.graph.log.clone();
+        if self.verify {
+            if let Err(err) = verify_function(gf) {
+                error!(log, "[passes] Invalid function before any pass: {}", err);
+                return Err(err)
+            }
+        }
+        let mut changed_any = false;
+        for iteration in 0..self.max_iterations {
+            let mut changed = false;
+            for pass in self.passes.iter_mut() {
+                let name = pass.name().to_string();
+                let nodes_before = gf.graph.nodes.len();
+                let start = Instant::now();
+                let result = pass.run(gf);
+                let duration = start.elapsed();
+                let pass_changed = match result {
+                    Ok(v) => v,
+                    Err(err) => {
+                        error!(log, "[passes] Pass {} failed: {}", name, err);
+                        return Err(err)
+                    }
+                };
+                debug!(log, "[passes] Iteration {}: {} took {}.{:>09}s, nodes {} -> {}.",
+                       iteration, name, duration.as_secs(), duration.subsec_nanos(),
+                       nodes_before, gf.graph.nodes.len());
+                if self.verify && pass_changed {
+                    if let Err(err) = verify_function(gf) {
+                        error!(log, "[passes] Invalid function after pass {}: {}", name, err);
+                        return Err(ErrorKind::Msg(format!("The pass {} produced an invalid \
+                        function: {}", name, err)).into())
+                    }
+                }
+                self.statistics.push(PassStatistics {
+                    name: name,
+                    duration: duration,
+                    nodes_before: nodes_before,
+                    nodes_after: gf.graph.nodes.len(),
+                    changed: pass_changed
+                });
+                changed = changed || pass_changed;
+            }
+            changed_any = changed_any || changed;
+            if !changed {
+                break
+            }
+        }
+        Ok(changed_any)
+    }
+}
+
+/// Optimizes the function with the default pipeline of the level
+pub fn optimize(gf: &mut GraphFunction, level: OptimizationLevel) -> Result<bool> {
+    PassManager::with_level(level).run(gf)
+}
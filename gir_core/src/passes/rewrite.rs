@@ -0,0 +1,79 @@
+use graph::*;
+use primitives::*;
+use errors::*;
+use api::ids;
+
+use std::collections::HashMap;
+
+/// Rebuilds the function by replaying all of its live expressions in order
+/// into a new graph.
+///
+/// For every expression, other than `Update`s, the `rewrite` is called with
+/// the new graph, the original expression and its ancestors already mapped
+/// into the new graph. It can return the id of a replacement, which it has
+/// added to the new graph, or `None` in which case the expression is copied.
+/// Copies keep their name and gradient level, while checkpoints move to the copy
+/// or the replacement. The inputs of the function are always kept, even if
+/// nothing depends on them anymore.
+///
+/// Returns the new function and the mapping from the old to the new ids.
+pub fn rebuild<F>(gf: &GraphFunction, mut rewrite: F)
+                  -> Result<(GraphFunction, HashMap<usize, usize>)>
+    where F: FnMut(&mut Graph, &ExprData, &[usize]) -> Result<Option<usize>> {
+    let ref old = gf.graph;
+    let mask = live_mask(gf);
+    let mut graph = Graph::new(old.log.clone());
+    graph.props = old.props.clone();
+    graph.grad_level = old.grad_level;
+    let mut mapping: HashMap<usize, usize> = HashMap::new();
+    for &id in old.order.iter().filter(|&&x| mask[x]) {
+        let node = &old.nodes[id];
+        let mut ancestors = Vec::with_capacity(node.ancestors.len());
+        for a in &node.ancestors {
+            ancestors.push(*mapping.get(a).ok_or(ErrorKind::Msg(
+                format!("[passes] The ancestor {} of {} was not rebuilt.", a, id)))?);
+        }
+        graph.scope = node.scope.clone();
+        let new_id = if node.op.get_meta().name == "Update" {
+            ids::update(&mut graph, ancestors[0], ancestors[1])?
+        } else if let Some(new_id) = rewrite(&mut graph, node, &ancestors)? {
+            new_id
+        } else {
+            let mut data = if node.op.get_meta().arity == Arity::Nullary {
+                node.op.apply_null()
+            } else {
+                node.op.apply(&mut graph, ancestors)?
+            };
+            data.name = node.name.clone();
+            data.grad_level = node.grad_level;
+            graph.add_node(data)?
+        };
+        mapping.insert(id, new_id);
+    }
+    graph.scope = old.scope.clone();
+    for c in &old.checkpoints {
+        if let Some(&new_id) = mapping.get(c) {
+            graph.checkpoints.insert(new_id);
+        }
+    }
+    let inputs = gf.inputs.iter().map(|x| mapping[x]).collect();
+    let outputs = gf.outputs.iter().map(|x| mapping[x]).collect();
+    Ok((GraphFunction::from_graph(graph, inputs, outputs, gf.name.clone()), mapping))
+}
+
+/// Marks all expressions needed for the outputs and updates, as well as the
+/// inputs of the function.
+pub fn live_mask(gf: &GraphFunction) -> Vec<bool> {
+    let ref graph = gf.graph;
+    let mut leafs = gf.outputs.clone();
+    if let Some(updates) = graph.op_map.get("Update") {
+        for &u in updates.iter().filter(|&&u| graph.nodes[u].op.get_meta().name == "Update") {
+            leafs.push(u);
+        }
+    }
+    let mut mask = graph.get_ancestors(&leafs);
+    for &i in &gf.inputs {
+        mask[i] = true;
+    }
+    mask
+}
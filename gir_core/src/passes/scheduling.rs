@@ -0,0 +1,130 @@
+use graph::*;
+use errors::*;
+use passes::Pass;
+use passes::memory_planner::{find_peak, sizes};
+
+use std::collections::HashMap;
+
+/// Changes the order of the graph, which is the schedule used by the backends, to
+/// reduce the memory kept at the same time. The expressions are scheduled greedily,
+/// at each step choosing among those with all arguments computed the one which
+/// increases the memory the least, counting the arguments it is the last use of
+/// as freed. Ties are broken by the current order and the updates are kept last.
+/// The sizes of the expressions are evaluated with the values of the symbolic
+/// integers given as hints.
+///
+/// The new order is used only if it has a smaller peak memory than the current one.
+#[derive(Debug, Clone)]
+pub struct MemoryScheduler {
+    /// Values of the symbolic integers used to evaluate the sizes of the expressions
+    pub values: HashMap<String, i64>,
+    /// The peak memory of the schedule from the last run
+    pub peak_memory: Option<i64>
+}
+
+impl MemoryScheduler {
+    pub fn new(values: HashMap<String, i64>) -> Self {
+        MemoryScheduler {
+            values: values,
+            peak_memory: None
+        }
+    }
+}
+
+impl Pass for MemoryScheduler {
+    fn name(&self) -> &str {
+        "MemoryScheduler"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        let order = gf.graph.order.clone();
+        self.peak_memory = Some(schedule_memory(gf, &self.values)?);
+        Ok(gf.graph.order != order)
+    }
+}
+
+/// Runs the `MemoryScheduler` on the function and returns the peak memory of the schedule
+pub fn schedule_memory(gf: &mut GraphFunction, values: &HashMap<String, i64>) -> Result<i64> {
+    let (_, peak, _) = find_peak(gf, values)?;
+    let order = greedy_order(gf, values)?;
+    let old_order = ::std::mem::replace(&mut gf.graph.order, order);
+    let (_, new_peak, _) = find_peak(gf, values)?;
+    if new_peak < peak {
+        debug!(gf.graph.log, "[scheduler] Reduced the peak memory from {} to {} elements.",
+               peak, new_peak);
+        Ok(new_peak)
+    } else {
+        gf.graph.order = old_order;
+        Ok(peak)
+    }
+}
+
+/// Returns the expressions in memory whose values are read by the expression.
+/// Broadcasts are computed on the fly, thus their arguments are read instead.
+fn reads(graph: &Graph, x: usize, sizes: &[i64], result: &mut Vec<usize>) {
+    for &a in &graph.nodes[x].ancestors {
+        if graph.nodes[a].op.get_meta().name == "Broadcast" {
+            reads(graph, a, sizes, result);
+        } else if sizes[a] > 0 && !result.contains(&a) {
+            result.push(a);
+        }
+    }
+}
+
+fn greedy_order(gf: &GraphFunction, values: &HashMap<String, i64>) -> Result<Vec<usize>> {
+    let ref graph = gf.graph;
+    let sizes = sizes(gf, values)?;
+    let mut position = vec![None; graph.nodes.len()];
+    for (i, &id) in graph.order.iter().enumerate() {
+        position[id] = Some(i);
+    }
+    let mut read = vec![Vec::new(); graph.nodes.len()];
+    // The number of unscheduled expressions reading each value
+    let mut readers = vec![0; graph.nodes.len()];
+    // The number of unscheduled arguments of each expression
+    let mut waiting = vec![0; graph.nodes.len()];
+    for &x in &graph.order {
+        reads(graph, x, &sizes, &mut read[x]);
+        for &a in &read[x] {
+            readers[a] += 1;
+        }
+        let mut ancestors = graph.nodes[x].ancestors.clone();
+        ancestors.sort();
+        ancestors.dedup();
+        waiting[x] = ancestors.len();
+    }
+    let mut ready: Vec<usize> = graph.order.iter().cloned()
+        .filter(|&x| waiting[x] == 0).collect();
+    let mut order = Vec::with_capacity(graph.order.len());
+    while !ready.is_empty() {
+        let only_updates = ready.iter().all(|&x| graph.nodes[x].op.get_meta().name == "Update");
+        let (index, &x) = ready.iter().enumerate()
+            .filter(|&(_, &x)| only_updates || graph.nodes[x].op.get_meta().name != "Update")
+            .min_by_key(|&(_, &x)| {
+                // The inputs are kept from the start
+                let allocated = if gf.inputs.contains(&x) { 0 } else { sizes[x] };
+                let freed: i64 = read[x].iter()
+                    .filter(|&&a| readers[a] == 1 && !gf.outputs.contains(&a))
+                    .map(|&a| sizes[a]).sum();
+                (allocated - freed, position[x])
+            }).unwrap();
+        ready.swap_remove(index);
+        order.push(x);
+        for &a in &read[x] {
+            readers[a] -= 1;
+        }
+        for &c in &graph.nodes[x].children {
+            if position[c].is_some() {
+                waiting[c] -= 1;
+                if waiting[c] == 0 {
+                    ready.push(c);
+                }
+            }
+        }
+    }
+    if order.len() != graph.order.len() {
+        return Err(ErrorKind::Msg("The order of the graph contains expressions with \
+        arguments missing from it.".into()).into())
+    }
+    Ok(order)
+}
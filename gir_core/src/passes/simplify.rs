@@ -0,0 +1,125 @@
+use graph::*;
+use primitives::*;
+use ops::*;
+use errors::*;
+use passes::Pass;
+use passes::rewrite::rebuild;
+use passes::constant_folding::constant_value;
+
+/// Canonicalises and simplifies common patterns of the arithmetic, shape and
+/// reduction operators, which appear mostly in differentiated graphs:
+///
+/// * `Neg(Neg(x))` and `Div(Div(x))` become `x`
+/// * zeros are removed from `Add` and ones from `Mul`
+/// * nested `Reorder`s are combined and identity `Reorder`s removed
+/// * `Broadcast`s and `Cast`s which do not change the expression are removed
+/// * `CustomGradient`s are removed, as they matter only for differentiation
+/// * nested `Sum`s are combined and `Sum`s over unit axes removed
+///
+/// An expression is only ever replaced by one of the same shape and type.
+#[derive(Debug, Clone, Default)]
+pub struct AlgebraicSimplification {}
+
+impl Pass for AlgebraicSimplification {
+    fn name(&self) -> &str {
+        "AlgebraicSimplification"
+    }
+
+    fn run(&mut self, gf: &mut GraphFunction) -> Result<bool> {
+        let mut simplified = 0;
+        let (new_gf, _) = rebuild(gf, |graph, node, ancestors| {
+            let result = simplify(graph, node, ancestors)?;
+            if result.is_some() {
+                simplified += 1;
+            }
+            Ok(result)
+        })?;
+        if simplified > 0 {
+            debug!(gf.graph.log, "[passes] Simplified {} expressions.", simplified);
+            *gf = new_gf;
+        }
+        Ok(simplified > 0)
+    }
+}
+
+/// Whether the expression can replace the original node
+fn same_shape_and_type(graph: &Graph, id: usize, node: &ExprData) -> bool {
+    let ref data = graph.nodes[id];
+    data.shape == node.shape && data.data_type == node.data_type
+}
+
+/// Whether the expression is the constant, possibly broadcasted
+fn is_constant(graph: &Graph, id: usize, value: f64) -> bool {
+    let mut id = id;
+    while graph.nodes[id].op.get_meta().name == "Broadcast" {
+        id = graph.nodes[id].ancestors[0];
+    }
+    constant_value(&graph.nodes[id]) == Some(value)
+}
+
+/// The single ancestor of the expression, if it is computed by the operator
+fn unwrap_op<'a>(graph: &'a Graph, id: usize, name: &str) -> Option<&'a ExprData> {
+    let ref data = graph.nodes[id];
+    if data.op.get_meta().name == name && data.ancestors.len() == 1 {
+        Some(data)
+    } else {
+        None
+    }
+}
+
+/// Returns the replacement of the expression, if it can be simplified
+fn simplify(graph: &mut Graph, node: &ExprData, ancestors: &[usize]) -> Result<Option<usize>> {
+    let replacement = match node.op.get_meta().name {
+        "Neg" | "Div" => {
+            unwrap_op(graph, ancestors[0], node.op.get_meta().name).map(|x| x.ancestors[0])
+        },
+        "Add" | "Mul" => {
+            let neutral = if node.op.get_meta().name == "Add" { 0.0 } else { 1.0 };
+            let remaining: Vec<usize> = ancestors.iter().cloned()
+                .filter(|&x| !is_constant(graph, x, neutral)).collect();
+            if remaining.len() == ancestors.len() || remaining.is_empty() {
+                None
+            } else if remaining.len() == 1 {
+                Some(remaining[0])
+            } else {
+                Some(graph.apply_op(node.op.clone(), remaining)?)
+            }
+        },
+        "Reorder" => {
+            let order = *node.op.get_args().unwrap().downcast::<[Axis; 4]>().unwrap();
+            let (arg, order) = match unwrap_op(graph, ancestors[0], "Reorder") {
+                Some(inner) => {
+                    let inner_order = *inner.op.get_args().unwrap()
+                        .downcast::<[Axis; 4]>().unwrap();
+                    (inner.ancestors[0], [inner_order[order[0] as usize],
+                        inner_order[order[1] as usize],
+                        inner_order[order[2] as usize],
+                        inner_order[order[3] as usize]])
+                },
+                None => (ancestors[0], order)
+            };
+            if order == [Axis::Axis0, Axis::Axis1, Axis::Axis2, Axis::Axis3] {
+                Some(arg)
+            } else if arg != ancestors[0] {
+                Some(graph.apply_op(Box::new(Reorder { order: order }), vec![arg])?)
+            } else {
+                None
+            }
+        },
+        "Sum" => {
+            let axes = *node.op.get_args().unwrap().downcast::<[bool; 4]>().unwrap();
+            match unwrap_op(graph, ancestors[0], "Sum").map(|inner| (inner.ancestors[0],
+                *inner.op.get_args().unwrap().downcast::<[bool; 4]>().unwrap())) {
+                Some((arg, inner_axes)) => {
+                    let axes = [axes[0] || inner_axes[0], axes[1] || inner_axes[1],
+                        axes[2] || inner_axes[2], axes[3] || inner_axes[3]];
+                    Some(graph.apply_op(Box::new(Sum { axes: axes }), vec![arg])?)
+                },
+                None => Some(ancestors[0])
+            }
+        },
+        "Broadcast" | "Cast" | "CustomGradient" => Some(ancestors[0]),
+        _ => None
+    };
+    Ok(replacement.and_then(|id| if same_shape_and_type(graph, id, node) { Some(id) } else { None }))
+}
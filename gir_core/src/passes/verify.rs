@@ -0,0 +1,28 @@
+use graph::*;
+use errors::*;
+
+/// Checks the consistency of a function, which is used by the `PassManager`
+/// between passes. Next to `Graph::verify` this checks that the inputs,
+/// outputs and parameters of the function refer to valid expressions.
+pub fn verify_function(gf: &GraphFunction) -> Result<()> {
+    let ref graph = gf.graph;
+    let fail = |msg: String| -> Result<()> {
+        Err(ErrorKind::Msg(format!("[verify] Function '{}': {}", gf.name, msg)).into())
+    };
+    for &id in gf.inputs.iter().chain(gf.outputs.iter()).chain(gf.parameters.values()) {
+        if id >= graph.nodes.len() {
+            return fail(format!("The expression {} does not exist.", id))
+        }
+    }
+    for &id in &gf.inputs {
+        if graph.nodes[id].op.get_meta().name != "Input" {
+            return fail(format!("The input {} is not an Input expression.", id))
+        }
+    }
+    for (name, &id) in &gf.parameters {
+        if graph.nodes[id].op.get_meta().name != "Parameter" {
+            return fail(format!("The parameter '{}' is not a Parameter expression.", name))
+        }
+    }
+    graph.verify()
+}
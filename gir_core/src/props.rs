@@ -1,5 +1,6 @@
 use primitives::*;
 use std::path::PathBuf;
+use std::collections::HashMap;
 
 /// Collection of policies used in the graph
 #[derive(Clone, Copy, Debug, Default)]
@@ -26,11 +27,22 @@ pub trait LoadFromSystem: Default {
     }
 }
 
+/// The value assumed for symbolic integers without a size hint
+pub const DEFAULT_SIZE_HINT: i64 = 100;
+
 #[derive(Clone, Debug)]
 pub struct GraphProperties {
     pub http_proxy: Option<String>,
     pub scope_delimiter: String,
     pub policies: GraphPolicies,
+    /// Scopes whose expressions are recomputed during the backward pass of
+    /// `reverse_diff` instead of being kept from the forward pass. A trailing `*`
+    /// matches any suffix, e.g. `layer*` checkpoints every `layer1`, `layer2`, etc.
+    pub checkpoint_scopes: Vec<String>,
+    /// Expected values of the symbolic integers, used when the best of several
+    /// alternatives depends on them, e.g. the order of a chain of matrix products.
+    /// Symbolic integers without a hint are assumed to be `DEFAULT_SIZE_HINT`.
+    pub size_hints: HashMap<String, i64>,
     pub default_work_dir: PathBuf
 }
 
@@ -41,6 +53,8 @@ impl Default for GraphProperties {
             http_proxy: None,
             scope_delimiter: "::".into(),
             policies: GraphPolicies::default(),
+            checkpoint_scopes: Vec::new(),
+            size_hints: HashMap::new(),
             default_work_dir: PathBuf::from("~/.gir")
         }
     }
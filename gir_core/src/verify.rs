@@ -0,0 +1,178 @@
+use primitives::*;
+use graph::*;
+use errors::*;
+
+/// A single inconsistency of a `Graph` found by `Graph::verify`
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphIssue {
+    /// The id stored in the expression differs from its position in the graph
+    WrongId { index: usize, id: usize },
+    /// The expression refers to an expression which does not exist
+    InvalidReference { id: usize, reference: usize },
+    /// The expression is missing from the order
+    NotInOrder(usize),
+    /// The expression is repeated in the order or does not exist
+    InvalidOrderEntry(usize),
+    /// The ancestor is not before the expression in the order
+    NotTopological { id: usize, ancestor: usize },
+    /// The ancestor does not have the expression as a child
+    MissingChild { id: usize, ancestor: usize },
+    /// The child does not have the expression as an ancestor
+    MissingAncestor { id: usize, child: usize },
+    /// The `op_map` does not list the expression under its operator
+    NotInOpMap { id: usize, op: String },
+    /// The `op_map` lists the expression under a different operator
+    WrongOpMapEntry { id: usize, op: String },
+    /// The `expr_map` entry does not correspond to the expression
+    WrongExprMapEntry(usize),
+    /// The shape differs from the one computed by the operator
+    WrongShape { id: usize, stored: Shape, computed: Shape },
+    /// The data type differs from the one computed by the operator
+    WrongDataType { id: usize, stored: FundamentalType, computed: FundamentalType },
+    /// The `Update` does not target a `Parameter`
+    UpdateOfNonParameter { id: usize, target: usize },
+    /// The `Update` is used by another expression
+    UpdateConsumed { id: usize, child: usize },
+}
+
+impl ::std::fmt::Display for GraphIssue {
+    fn fmt(&self, fmt: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            GraphIssue::WrongId { index, id } =>
+                write!(fmt, "The expression at {} has the id {}.", index, id),
+            GraphIssue::InvalidReference { id, reference } =>
+                write!(fmt, "The expression {} refers to the invalid expression {}.", id, reference),
+            GraphIssue::NotInOrder(id) =>
+                write!(fmt, "The expression {} is missing from the order.", id),
+            GraphIssue::InvalidOrderEntry(id) =>
+                write!(fmt, "The expression {} is repeated or invalid in the order.", id),
+            GraphIssue::NotTopological { id, ancestor } =>
+                write!(fmt, "The ancestor {} of {} is not before it in the order.", ancestor, id),
+            GraphIssue::MissingChild { id, ancestor } =>
+                write!(fmt, "The expression {} is not a child of its ancestor {}.", id, ancestor),
+            GraphIssue::MissingAncestor { id, child } =>
+                write!(fmt, "The expression {} is not an ancestor of its child {}.", id, child),
+            GraphIssue::NotInOpMap { id, ref op } =>
+                write!(fmt, "The expression {} is missing from the op_map under {}.", id, op),
+            GraphIssue::WrongOpMapEntry { id, ref op } =>
+                write!(fmt, "The expression {} is listed in the op_map under {}.", id, op),
+            GraphIssue::WrongExprMapEntry(id) =>
+                write!(fmt, "The expr_map entry of {} does not match the expression.", id),
+            GraphIssue::WrongShape { id, ref stored, ref computed } =>
+                write!(fmt, "The expression {} has shape {}, but its operator gives {}.",
+                       id, stored, computed),
+            GraphIssue::WrongDataType { id, stored, computed } =>
+                write!(fmt, "The expression {} has type {}, but its operator gives {}.",
+                       id, stored, computed),
+            GraphIssue::UpdateOfNonParameter { id, target } =>
+                write!(fmt, "The update {} targets {}, which is not a parameter.", id, target),
+            GraphIssue::UpdateConsumed { id, child } =>
+                write!(fmt, "The update {} is used by the expression {}.", id, child),
+        }
+    }
+}
+
+impl Graph {
+    /// Checks that the graph is consistent, returns `ErrorKind::InvalidGraph`
+    /// with all of the issues found otherwise.
+    pub fn verify(&self) -> Result<()> {
+        let issues = self.find_issues();
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(ErrorKind::InvalidGraph(issues).into())
+        }
+    }
+
+    /// Returns all inconsistencies of the graph
+    pub fn find_issues(&self) -> Vec<GraphIssue> {
+        let n = self.nodes.len();
+        let mut issues = Vec::new();
+        // The order must contain every expression exactly once
+        let mut position = vec![None; n];
+        for (i, &id) in self.order.iter().enumerate() {
+            if id >= n || position[id].is_some() {
+                issues.push(GraphIssue::InvalidOrderEntry(id));
+            } else {
+                position[id] = Some(i);
+            }
+        }
+        for (id, node) in self.nodes.iter().enumerate() {
+            if node.id != id {
+                issues.push(GraphIssue::WrongId { index: id, id: node.id });
+            }
+            if position[id].is_none() {
+                issues.push(GraphIssue::NotInOrder(id));
+            }
+            let mut valid = true;
+            for &a in &node.ancestors {
+                if a >= n {
+                    issues.push(GraphIssue::InvalidReference { id: id, reference: a });
+                    valid = false;
+                    continue
+                }
+                if position[a].is_none() || position[a] >= position[id] {
+                    issues.push(GraphIssue::NotTopological { id: id, ancestor: a });
+                }
+                if !self.nodes[a].children.contains(&id) {
+                    issues.push(GraphIssue::MissingChild { id: id, ancestor: a });
+                }
+            }
+            for &c in &node.children {
+                if c >= n {
+                    issues.push(GraphIssue::InvalidReference { id: id, reference: c });
+                } else if !self.nodes[c].ancestors.contains(&id) {
+                    issues.push(GraphIssue::MissingAncestor { id: id, child: c });
+                }
+            }
+            let name = node.op.get_meta().name;
+            if !self.op_map.get(name).map(|v| v.contains(&id)).unwrap_or(false) {
+                issues.push(GraphIssue::NotInOpMap { id: id, op: name.into() });
+            }
+            if name == "Update" && valid {
+                if node.ancestors.len() != 2 ||
+                    self.nodes[node.ancestors[0]].op.get_meta().name != "Parameter" {
+                    let target = node.ancestors.get(0).cloned().unwrap_or(id);
+                    issues.push(GraphIssue::UpdateOfNonParameter { id: id, target: target });
+                }
+            }
+            if name == "Update" {
+                for &c in node.children.iter().filter(|&&c| c < n) {
+                    issues.push(GraphIssue::UpdateConsumed { id: id, child: c });
+                }
+            }
+            // Recompute the shape and the type
+            if valid {
+                let shape = node.op.get_shape(self, &node.ancestors);
+                if shape != node.shape {
+                    issues.push(GraphIssue::WrongShape {
+                        id: id,
+                        stored: node.shape.clone(),
+                        computed: shape
+                    });
+                }
+                let data_type = node.op.get_data_type(self, &node.ancestors);
+                if data_type != node.data_type {
+                    issues.push(GraphIssue::WrongDataType {
+                        id: id,
+                        stored: node.data_type,
+                        computed: data_type
+                    });
+                }
+            }
+        }
+        for (op, ids) in &self.op_map {
+            for &id in ids {
+                if id >= n || self.nodes[id].op.get_meta().name != op {
+                    issues.push(GraphIssue::WrongOpMapEntry { id: id, op: op.clone() });
+                }
+            }
+        }
+        for (key, &id) in &self.expr_map {
+            if id >= n || Graph::expr_key(&self.nodes[id]).as_ref() != Some(key) {
+                issues.push(GraphIssue::WrongExprMapEntry(id));
+            }
+        }
+        issues
+    }
+}
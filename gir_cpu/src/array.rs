@@ -0,0 +1,417 @@
+/// A dense tensor stored in host memory.
+///
+/// The layout is column major (the first axis is contiguous), the same as
+/// the one used by Arrayfire, and all values are kept as `f64` regardless
+/// of the `FundamentalType` of the expression they represent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuArray {
+    pub dims: [usize; 4],
+    pub mem: Vec<f64>
+}
+
+/// A borrowed view of a tensor, either from a `CpuArray` or from the
+/// memory buffer of a `CpuFunction`.
+#[derive(Debug, Clone, Copy)]
+pub struct ArrayView<'a> {
+    pub dims: [usize; 4],
+    pub mem: &'a [f64]
+}
+
+pub fn elements(dims: &[usize; 4]) -> usize {
+    dims[0] * dims[1] * dims[2] * dims[3]
+}
+
+pub fn linear_index(dims: &[usize; 4], index: &[usize; 4]) -> usize {
+    index[0] + dims[0] * (index[1] + dims[1] * (index[2] + dims[2] * index[3]))
+}
+
+pub fn unravel_index(dims: &[usize; 4], mut linear: usize) -> [usize; 4] {
+    let mut index = [0; 4];
+    for i in 0..4 {
+        index[i] = linear % dims[i];
+        linear /= dims[i];
+    }
+    index
+}
+
+impl CpuArray {
+    pub fn new(dims: [usize; 4], mem: Vec<f64>) -> Result<Self, String> {
+        if elements(&dims) != mem.len() {
+            Err(format!("The dimensions {:?} require {} elements, but {} were given.",
+                        dims, elements(&dims), mem.len()))
+        } else {
+            Ok(CpuArray {
+                dims: dims,
+                mem: mem
+            })
+        }
+    }
+
+    pub fn constant(value: f64, dims: [usize; 4]) -> Self {
+        CpuArray {
+            dims: dims,
+            mem: vec![value; elements(&dims)]
+        }
+    }
+
+    pub fn scalar(value: f64) -> Self {
+        CpuArray::constant(value, [1, 1, 1, 1])
+    }
+
+    pub fn elements(&self) -> usize {
+        self.mem.len()
+    }
+
+    pub fn view(&self) -> ArrayView {
+        ArrayView {
+            dims: self.dims,
+            mem: &self.mem
+        }
+    }
+}
+
+impl<'a> ArrayView<'a> {
+    pub fn to_array(&self) -> CpuArray {
+        CpuArray {
+            dims: self.dims,
+            mem: self.mem.to_vec()
+        }
+    }
+
+    /// Returns the element at the index, treating any unit axis as broadcasted.
+    pub fn at(&self, index: &[usize; 4]) -> f64 {
+        let mut i = [0; 4];
+        for k in 0..4 {
+            i[k] = if self.dims[k] == 1 { 0 } else { index[k] };
+        }
+        self.mem[linear_index(&self.dims, &i)]
+    }
+}
+
+/// Applies `f` elementwise.
+pub fn map<F: Fn(f64) -> f64>(x: ArrayView, f: F) -> CpuArray {
+    CpuArray {
+        dims: x.dims,
+        mem: x.mem.iter().map(|&v| f(v)).collect()
+    }
+}
+
+/// Applies `f` elementwise, broadcasting any unit axes of the two arguments.
+pub fn zip_with<F: Fn(f64, f64) -> f64>(x: ArrayView, y: ArrayView, f: F) -> Result<CpuArray, String> {
+    if x.dims == y.dims {
+        return Ok(CpuArray {
+            dims: x.dims,
+            mem: x.mem.iter().zip(y.mem.iter()).map(|(&a, &b)| f(a, b)).collect()
+        })
+    }
+    let mut dims = [1; 4];
+    for k in 0..4 {
+        dims[k] = if x.dims[k] == y.dims[k] || y.dims[k] == 1 {
+            x.dims[k]
+        } else if x.dims[k] == 1 {
+            y.dims[k]
+        } else {
+            return Err(format!("Can not broadcast dimensions {:?} and {:?}.", x.dims, y.dims))
+        };
+    }
+    let mem = (0..elements(&dims)).map(|i| {
+        let index = unravel_index(&dims, i);
+        f(x.at(&index), y.at(&index))
+    }).collect();
+    Ok(CpuArray {
+        dims: dims,
+        mem: mem
+    })
+}
+
+/// Chooses elementwise from `x` where `cond` is non zero and from `y` otherwise,
+/// broadcasting any unit axes of the three arguments.
+pub fn select(cond: ArrayView, x: ArrayView, y: ArrayView) -> Result<CpuArray, String> {
+    let mut dims = [1; 4];
+    for k in 0..4 {
+        for &d in &[cond.dims[k], x.dims[k], y.dims[k]] {
+            if d != 1 {
+                if dims[k] != 1 && dims[k] != d {
+                    return Err(format!("Can not broadcast dimensions {:?}, {:?} and {:?}.",
+                                       cond.dims, x.dims, y.dims))
+                }
+                dims[k] = d;
+            }
+        }
+    }
+    let mem = (0..elements(&dims)).map(|i| {
+        let index = unravel_index(&dims, i);
+        if cond.at(&index) != 0.0 { x.at(&index) } else { y.at(&index) }
+    }).collect();
+    Ok(CpuArray {
+        dims: dims,
+        mem: mem
+    })
+}
+
+/// Folds `f` over all of the arguments elementwise.
+pub fn fold_with<F: Fn(f64, f64) -> f64>(args: &[ArrayView], f: F) -> Result<CpuArray, String> {
+    let mut result = args[0].to_array();
+    for arg in args.iter().skip(1) {
+        result = zip_with(result.view(), *arg, &f)?;
+    }
+    Ok(result)
+}
+
+/// Reduces along each axis marked in `axes` using `f`, starting from `init`.
+pub fn reduce<F: Fn(f64, f64) -> f64>(x: ArrayView, axes: &[bool; 4], init: f64, f: F) -> CpuArray {
+    let mut dims = x.dims;
+    for k in 0..4 {
+        if axes[k] {
+            dims[k] = 1;
+        }
+    }
+    let mut result = CpuArray::constant(init, dims);
+    for i in 0..x.mem.len() {
+        let mut index = unravel_index(&x.dims, i);
+        for k in 0..4 {
+            if axes[k] {
+                index[k] = 0;
+            }
+        }
+        let j = linear_index(&dims, &index);
+        result.mem[j] = f(result.mem[j], x.mem[i]);
+    }
+    result
+}
+
+/// The mean along each axis marked in `axes`.
+pub fn mean(x: ArrayView, axes: &[bool; 4]) -> CpuArray {
+    let sum = reduce(x, axes, 0.0, |a, b| a + b);
+    let n = (x.mem.len() / sum.mem.len()) as f64;
+    map(sum.view(), |a| a / n)
+}
+
+/// `log(sum(exp(x)))` along each axis marked in `axes`, after subtracting the maximum.
+pub fn log_sum_exp(x: ArrayView, axes: &[bool; 4]) -> CpuArray {
+    let max = reduce(x, axes, ::std::f64::NEG_INFINITY, f64::max);
+    let exp = zip_with(x, max.view(), |a, m| (a - m).exp()).unwrap();
+    let sum = reduce(exp.view(), axes, 0.0, |a, b| a + b);
+    zip_with(sum.view(), max.view(), |s, m| s.ln() + m).unwrap()
+}
+
+/// The population variance along each axis marked in `axes`.
+pub fn variance(x: ArrayView, axes: &[bool; 4]) -> CpuArray {
+    let mean = mean(x, axes);
+    let squares = zip_with(x, mean.view(), |a, m| (a - m) * (a - m)).unwrap();
+    self::mean(squares.view(), axes)
+}
+
+/// The linear index within the axes marked in `axes`, with the first axis changing
+/// fastest, of the first element for which `better` holds against all others.
+pub fn arg_reduce<F: Fn(f64, f64) -> bool>(x: ArrayView, axes: &[bool; 4], better: F) -> CpuArray {
+    let best = reduce(x, axes, ::std::f64::NAN, |a, b| if a.is_nan() || better(b, a) { b } else { a });
+    let mut result = CpuArray::constant(::std::f64::NAN, best.dims);
+    for i in 0..x.mem.len() {
+        let index = unravel_index(&x.dims, i);
+        let mut reduced_index = [0; 4];
+        let mut reduced = 0;
+        let mut stride = 1;
+        for k in 0..4 {
+            if axes[k] {
+                reduced += index[k] * stride;
+                stride *= x.dims[k];
+            } else {
+                reduced_index[k] = index[k];
+            }
+        }
+        let j = linear_index(&best.dims, &reduced_index);
+        if result.mem[j].is_nan() && x.mem[i] == best.mem[j] {
+            result.mem[j] = reduced as f64;
+        }
+    }
+    result
+}
+
+/// Expands all unit axes of `x` to the dimensions given.
+pub fn broadcast(x: ArrayView, dims: [usize; 4]) -> CpuArray {
+    let mem = (0..elements(&dims)).map(|i| x.at(&unravel_index(&dims, i))).collect();
+    CpuArray {
+        dims: dims,
+        mem: mem
+    }
+}
+
+/// Permutes the axes such that axis `k` of the result is axis `order[k]` of `x`.
+pub fn reorder(x: ArrayView, order: &[usize; 4]) -> CpuArray {
+    let dims = [x.dims[order[0]], x.dims[order[1]], x.dims[order[2]], x.dims[order[3]]];
+    let mut result = CpuArray::constant(0.0, dims);
+    for i in 0..result.mem.len() {
+        let index = unravel_index(&dims, i);
+        let mut source = [0; 4];
+        for k in 0..4 {
+            source[order[k]] = index[k];
+        }
+        result.mem[i] = x.mem[linear_index(&x.dims, &source)];
+    }
+    result
+}
+
+/// Takes the elements `start, start + step, ...` before `stop` along each axis,
+/// given as `(start, stop, step)`.
+pub fn slice(x: ArrayView, ranges: &[(usize, usize, usize); 4]) -> CpuArray {
+    let mut dims = [1; 4];
+    for k in 0..4 {
+        let (start, stop, step) = ranges[k];
+        dims[k] = (stop - start + step - 1) / step;
+    }
+    let mut result = CpuArray::constant(0.0, dims);
+    for i in 0..result.mem.len() {
+        let mut index = unravel_index(&dims, i);
+        for k in 0..4 {
+            index[k] = ranges[k].0 + index[k] * ranges[k].2;
+        }
+        result.mem[i] = x.mem[linear_index(&x.dims, &index)];
+    }
+    result
+}
+
+/// Adds `update` to the elements of `base` selected as in `slice`.
+pub fn slice_add(base: ArrayView, update: ArrayView, ranges: &[(usize, usize, usize); 4]) -> CpuArray {
+    let mut result = base.to_array();
+    for i in 0..update.mem.len() {
+        let mut index = unravel_index(&update.dims, i);
+        for k in 0..4 {
+            index[k] = ranges[k].0 + index[k] * ranges[k].2;
+        }
+        result.mem[linear_index(&base.dims, &index)] += update.mem[i];
+    }
+    result
+}
+
+/// Converts the values of an integer vector to indices smaller than `bound`.
+fn to_indices(indices: ArrayView, bound: usize) -> Result<Vec<usize>, String> {
+    indices.mem.iter().map(|&v| {
+        if v < 0.0 || v as usize >= bound {
+            Err(format!("The index {} is out of bounds for an axis of size {}.", v, bound))
+        } else {
+            Ok(v as usize)
+        }
+    }).collect()
+}
+
+/// Takes the subtensors of `x` at each of the `indices` along the `axis`.
+pub fn gather(x: ArrayView, indices: ArrayView, axis: usize) -> Result<CpuArray, String> {
+    let indices = to_indices(indices, x.dims[axis])?;
+    let mut dims = x.dims;
+    dims[axis] = indices.len();
+    let mut result = CpuArray::constant(0.0, dims);
+    for i in 0..result.mem.len() {
+        let mut index = unravel_index(&dims, i);
+        index[axis] = indices[index[axis]];
+        result.mem[i] = x.mem[linear_index(&x.dims, &index)];
+    }
+    Ok(result)
+}
+
+/// Writes the subtensors of `updates` into `base` at each of the `indices` along
+/// the `axis`, adding them to the existing values if `accumulate` is set.
+pub fn scatter(base: ArrayView, indices: ArrayView, updates: ArrayView, axis: usize, accumulate: bool)
+               -> Result<CpuArray, String> {
+    let indices = to_indices(indices, base.dims[axis])?;
+    let mut result = base.to_array();
+    for i in 0..updates.mem.len() {
+        let mut index = unravel_index(&updates.dims, i);
+        index[axis] = indices[index[axis]];
+        let j = linear_index(&base.dims, &index);
+        if accumulate {
+            result.mem[j] += updates.mem[i];
+        } else {
+            result.mem[j] = updates.mem[i];
+        }
+    }
+    Ok(result)
+}
+
+/// Standard matrix product of two matrices, batched over the last two axes where
+/// an axis of size one is broadcasted.
+pub fn matmul(x: ArrayView, y: ArrayView) -> Result<CpuArray, String> {
+    let (m, k, n) = (x.dims[0], x.dims[1], y.dims[1]);
+    let mut batch = [1; 2];
+    for b in 0..2 {
+        let (xb, yb) = (x.dims[b + 2], y.dims[b + 2]);
+        if xb != yb && xb != 1 && yb != 1 {
+            return Err(format!("Can not multiply matrices of dimensions {:?} and {:?}.",
+                               x.dims, y.dims))
+        }
+        batch[b] = ::std::cmp::max(xb, yb);
+    }
+    if y.dims[0] != k {
+        return Err(format!("Can not multiply matrices of dimensions {:?} and {:?}.",
+                           x.dims, y.dims))
+    }
+    // The batch axes are broadcasted
+    let offset = |dims: &[usize; 4], p: usize, q: usize| {
+        let p = if dims[2] == 1 { 0 } else { p };
+        let q = if dims[3] == 1 { 0 } else { q };
+        dims[0] * dims[1] * (p + dims[2] * q)
+    };
+    let mut result = CpuArray::constant(0.0, [m, n, batch[0], batch[1]]);
+    for q in 0..batch[1] {
+        for p in 0..batch[0] {
+            let (xo, yo) = (offset(&x.dims, p, q), offset(&y.dims, p, q));
+            let ro = m * n * (p + batch[0] * q);
+            for j in 0..n {
+                for l in 0..k {
+                    let y_lj = y.mem[yo + l + k * j];
+                    if y_lj != 0.0 {
+                        for i in 0..m {
+                            result.mem[ro + i + m * j] += x.mem[xo + i + m * l] * y_lj;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Contracts the arrays, whose axes are labeled by `inputs`, into an array whose
+/// axes are labeled by `output`, by summing the products over all missing labels.
+pub fn einsum(args: &[ArrayView], inputs: &[Vec<char>], output: &[char]) -> CpuArray {
+    let mut labels: Vec<(char, usize)> = Vec::new();
+    for (x, labels_x) in args.iter().zip(inputs) {
+        for (k, &c) in labels_x.iter().enumerate() {
+            if !labels.iter().any(|&(l, _)| l == c) {
+                labels.push((c, x.dims[k]));
+            }
+        }
+    }
+    let position = |c: char| labels.iter().position(|&(l, _)| l == c).unwrap();
+    let mut dims = [1; 4];
+    for (k, &c) in output.iter().enumerate() {
+        dims[k] = labels[position(c)].1;
+    }
+    let mut result = CpuArray::constant(0.0, dims);
+    let total: usize = labels.iter().map(|&(_, d)| d).product();
+    let mut values = vec![0; labels.len()];
+    for _ in 0..total {
+        let mut product = 1.0;
+        for (x, labels_x) in args.iter().zip(inputs) {
+            let mut index = [0; 4];
+            for (k, &c) in labels_x.iter().enumerate() {
+                index[k] = values[position(c)];
+            }
+            product *= x.mem[linear_index(&x.dims, &index)];
+        }
+        let mut index = [0; 4];
+        for (k, &c) in output.iter().enumerate() {
+            index[k] = values[position(c)];
+        }
+        result.mem[linear_index(&dims, &index)] += product;
+        // Advance to the next combination of the labels
+        for (v, &(_, d)) in values.iter_mut().zip(labels.iter()) {
+            *v += 1;
+            if *v < d {
+                break
+            }
+            *v = 0;
+        }
+    }
+    result
+}
@@ -0,0 +1,91 @@
+use gir_core::graph::*;
+use gir_core::backend::*;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::{Ref, RefCell};
+use std::io;
+use super::array::CpuArray;
+use super::function::CpuFunction;
+
+
+/// A reference backend which executes every operator on the host with plain `f64`s.
+#[derive(Debug, Clone)]
+pub struct CpuBackend {
+    pub parameters: Rc<RefCell<HashMap<String, CpuArray>>>,
+    pub precisions: BackendPrecisions
+}
+
+impl Default for CpuBackend {
+    fn default() -> Self {
+        CpuBackend {
+            parameters: Rc::new(RefCell::new(HashMap::new())),
+            precisions: BackendPrecisions::default()
+        }
+    }
+}
+
+impl CpuBackend {
+    pub fn get_param_value(&self, name: &str) -> Ref<CpuArray> {
+        Ref::map(self.parameters.borrow(), |x| x.get(name).unwrap())
+    }
+
+    pub fn set_param_value(&mut self, name: &str, value: CpuArray) -> Result<(), String> {
+        if let Some(v) = self.parameters.borrow().get(name) {
+            if v.dims != value.dims {
+                return Err(format!("The parameter {} has shape {:?}, \
+                but {:?} was passed to set_param_value.", name, v.dims, value.dims))
+            }
+        }
+        self.parameters.borrow_mut().insert(name.into(), value);
+        Ok(())
+    }
+}
+
+impl Backend<CpuFunction> for CpuBackend {
+    fn make_function(&self, gf: GraphFunction) -> CpuFunction {
+        let sym_input_shapes = gf.inputs.iter()
+            .map(|&id| gf.graph.nodes[id].shape.clone()).collect();
+        let memory_map = build_memory_map(&gf);
+        CpuFunction {
+            initialized: false,
+            precisions: self.precisions,
+            gf: gf,
+            parameters: self.parameters.clone(),
+            memory_map: memory_map,
+            sym_input_shapes: sym_input_shapes,
+            last_shapes: Vec::new(),
+            last_deduced: HashMap::new(),
+            buffer: Vec::new(),
+            buffer_map: HashMap::new(),
+            dims: Vec::new(),
+            values: HashMap::new()
+        }
+    }
+
+    fn get_precisions(&self) -> &BackendPrecisions {
+        &self.precisions
+    }
+    fn set_precisions(&mut self, precisions: BackendPrecisions) {
+        self.precisions = precisions;
+    }
+    fn info(&self, f: &mut io::Write) -> io::Result<()> {
+        writeln!(f, "CPU Backend Information:")?;
+        writeln!(f, "==================================================")?;
+        writeln!(f, "Device:\n\
+            {t}Name: Host\n\
+            {t}Storage precision: f64\n\
+            {t}Parameters: {}",
+                 self.parameters.borrow().len(), t = "\t")?;
+        writeln!(f, "==================================================")
+    }
+
+    fn general_info(&self, f: &mut io::Write) -> io::Result<()> {
+        writeln!(f, "CPU Backend General Information:")?;
+        writeln!(f, "==================================================")?;
+        writeln!(f, "Platform[0]:\n\
+            {t}Name: Host\n\
+            {t}Total Device Count: 1", t = "\t")?;
+        writeln!(f, "==================================================")
+    }
+}
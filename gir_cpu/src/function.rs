@@ -0,0 +1,338 @@
+use gir_core::primitives::*;
+use gir_core::graph::*;
+use gir_core::backend::*;
+use gir_core::errors::*;
+use gir_core::ops::{unary_value, SliceRange};
+
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::cell::RefCell;
+
+use array::*;
+
+#[derive(Debug, Clone)]
+pub struct CpuFunction {
+    pub initialized: bool,
+    pub precisions: BackendPrecisions,
+    pub gf: GraphFunction,
+    pub parameters: Rc<RefCell<HashMap<String, CpuArray>>>,
+    pub memory_map: AbstractMemoryMap,
+    pub sym_input_shapes: Vec<Shape>,
+    pub last_shapes: Vec<[usize; 4]>,
+    pub last_deduced: HashMap<String, i64>,
+    // Single buffer holding all nodes from the memory map
+    pub buffer: Vec<f64>,
+    // Maps node id to (offset, size) in the buffer
+    pub buffer_map: HashMap<usize, (usize, usize)>,
+    // The concrete dimensions of every node
+    pub dims: Vec<[usize; 4]>,
+    // Values of nodes which are not part of the memory map
+    pub values: HashMap<usize, CpuArray>,
+}
+
+fn eval_sym_int(sym_int: &SymInt, deduced: &HashMap<String, i64>) -> Result<i64> {
+    match sym_int.eval(deduced) {
+        Ok(v) => Ok(v),
+        Err(_) => Err(ErrorKind::Msg(format!("Could not evaluate the symbolic integer {} \
+        with the deduced values {:?}.", sym_int.to_code(&|x: String| x), deduced)).into())
+    }
+}
+
+/// Booleans are stored as 0 and 1
+fn truth(value: bool) -> f64 {
+    if value { 1.0 } else { 0.0 }
+}
+
+impl CpuFunction {
+    /// Evaluates the memory map and all shapes for the last deduced values
+    /// and allocates the buffer accordingly.
+    fn allocate(&mut self) -> Result<()> {
+        let mut dims = Vec::with_capacity(self.gf.graph.nodes.len());
+        for node in &self.gf.graph.nodes {
+            let mut d = [1; 4];
+            for &axis in Axis::iter() {
+                d[axis as usize] = eval_sym_int(node.shape.get(axis), &self.last_deduced)? as usize;
+            }
+            dims.push(d);
+        }
+        let mut size = 0;
+        let mut buffer_map = HashMap::new();
+        for (&id, &(ref offset, ref n)) in self.memory_map.abstract_map.iter() {
+            let offset = eval_sym_int(offset, &self.last_deduced)? as usize;
+            let n = eval_sym_int(n, &self.last_deduced)? as usize;
+            size = ::std::cmp::max(size, offset + n);
+            buffer_map.insert(id, (offset, n));
+        }
+        self.dims = dims;
+        self.buffer_map = buffer_map;
+        self.buffer = vec![0.0; size];
+        self.values.clear();
+        self.initialized = true;
+        Ok(())
+    }
+
+    pub fn view(&self, id: usize) -> Result<ArrayView> {
+        if let Some(&(offset, n)) = self.buffer_map.get(&id) {
+            Ok(ArrayView {
+                dims: self.dims[id],
+                mem: &self.buffer[offset..offset + n]
+            })
+        } else if let Some(v) = self.values.get(&id) {
+            Ok(v.view())
+        } else {
+            Err(ErrorKind::Msg(format!("The value of node {} has not been computed.", id)).into())
+        }
+    }
+
+    fn store(&mut self, id: usize, value: CpuArray) -> Result<()> {
+        if value.dims != self.dims[id] {
+            return Err(ErrorKind::Msg(format!("The node {} was computed with dimensions {:?}, \
+            but expected {:?}.", id, value.dims, self.dims[id])).into())
+        }
+        if let Some(&(offset, n)) = self.buffer_map.get(&id) {
+            self.buffer[offset..offset + n].copy_from_slice(&value.mem);
+        } else {
+            self.values.insert(id, value);
+        }
+        Ok(())
+    }
+
+    pub fn internal_eval(&mut self, inputs: &[&CpuArray]) -> Result<()> {
+        for (&id, input) in self.gf.inputs.clone().iter().zip(inputs) {
+            self.store(id, (*input).clone())?;
+        }
+        let parameters = self.parameters.clone();
+        for (name, &id) in self.gf.parameters.iter() {
+            match parameters.borrow().get(name) {
+                Some(v) => {
+                    if v.dims != self.dims[id] {
+                        return Err(ErrorKind::Msg(format!("The parameter {} has dimensions {:?}, \
+                        but expected {:?}.", name, v.dims, self.dims[id])).into())
+                    }
+                    self.values.insert(id, v.clone());
+                },
+                None => return Err(ErrorKind::Msg(format!("The parameter {} has not been \
+                initialized.", name)).into())
+            }
+        }
+        let order = self.gf.graph.order.clone();
+        for &id in &order {
+            self.compute_node(id)?;
+        }
+        Ok(())
+    }
+}
+
+impl CompiledFunction<CpuArray, CpuArray> for CpuFunction {
+    fn eval(&mut self, inputs: &[&CpuArray]) -> Result<Vec<CpuArray>> {
+        // Check correct number of inputs are provided
+        if inputs.len() != self.gf.inputs.len() {
+            return Err(ErrorKind::Msg(format!("Incorrect number of inputs. \
+            Expected: {}, actual: {}.", self.gf.inputs.len(), inputs.len())).into());
+        }
+        let input_shapes: Vec<[usize; 4]> = inputs.iter().map(|x| x.dims).collect();
+        // Check shapes are correct and if they have changed
+        match verify_shapes(&input_shapes, &self.last_shapes, &self.sym_input_shapes)? {
+            Some(deduced) => {
+                self.last_shapes = input_shapes;
+                self.last_deduced = deduced;
+                self.allocate()?;
+            },
+            None => if !self.initialized {
+                self.allocate()?;
+            }
+        }
+        self.internal_eval(inputs)?;
+        let mut result = Vec::new();
+        for &i in &self.gf.outputs {
+            result.push(self.view(i)?.to_array());
+        }
+        Ok(result)
+    }
+
+    fn initialized(&self) -> bool {
+        self.initialized
+    }
+
+    fn free_memory(&mut self) {
+        self.buffer = Vec::new();
+        self.buffer_map.clear();
+        self.values.clear();
+        self.initialized = false;
+    }
+}
+
+impl CpuFunction {
+    fn compute_node(&mut self, id: usize) -> Result<()> {
+        let result = {
+            let ref node = self.gf.graph.nodes[id];
+            let args = node.ancestors.iter()
+                .map(|&a| self.view(a))
+                .collect::<Result<Vec<ArrayView>>>();
+            match node.op.get_meta().name {
+                "Input" | "Parameter" | "Cleared" => return Ok(()),
+                "Scalar" => {
+                    let (value, _) = *node.op.get_args().unwrap()
+                        .downcast::<(f64, FundamentalType)>().unwrap();
+                    CpuArray::scalar(value)
+                },
+                "Basis" => {
+                    let (index, _, _) = *node.op.get_args().unwrap()
+                        .downcast::<(usize, Shape, FundamentalType)>().unwrap();
+                    let mut result = CpuArray::constant(0.0, self.dims[id]);
+                    if index >= result.mem.len() {
+                        return Err(ErrorKind::Msg(format!("The basis index {} is out of bounds \
+                        for the shape {:?}.", index, self.dims[id])).into())
+                    }
+                    result.mem[index] = 1.0;
+                    result
+                },
+                "SymIntInput" => {
+                    let value = eval_sym_int(node.sym_int.as_ref().unwrap(), &self.last_deduced)?;
+                    CpuArray::scalar(value as f64)
+                },
+                "TensorShape" => {
+                    let axis = *node.op.get_args().unwrap()
+                        .downcast::<Axis>().unwrap();
+                    CpuArray::scalar(self.dims[node.ancestors[0]][axis as usize] as f64)
+                },
+                "Add" => fold_with(&args?, |a, b| a + b)?,
+                "Mul" => fold_with(&args?, |a, b| a * b)?,
+                "Neg" => map(args?[0], |a| -a),
+                "Div" => map(args?[0], |a| 1.0 / a),
+                "Pow" => {
+                    let args = args?;
+                    zip_with(args[0], args[1], |a, b| a.powf(b))?
+                },
+                "Maximum" => fold_with(&args?, |a, b| a.max(b))?,
+                "Minimum" => fold_with(&args?, |a, b| a.min(b))?,
+                "Atan2" => {
+                    let args = args?;
+                    zip_with(args[0], args[1], |a, b| a.atan2(b))?
+                },
+                "Clamp" => {
+                    let args = args?;
+                    let low = zip_with(args[0], args[1], |a, b| a.max(b))?;
+                    zip_with(low.view(), args[2], |a, b| a.min(b))?
+                },
+                "Tanh" | "Exp" | "Log" | "Log1p" | "Expm1" | "Sqrt" | "Rsqrt" | "Square" |
+                "Sigmoid" | "Softplus" | "Relu" | "Abs" | "Sign" | "Sin" | "Cos" | "Tan" |
+                "Sinh" | "Cosh" | "Erf" => {
+                    let name = node.op.get_meta().name;
+                    map(args?[0], |a| unary_value(name, a).unwrap())
+                },
+                "Gt" => fold_with(&args?, |a, b| truth(a > b))?,
+                "Ge" => fold_with(&args?, |a, b| truth(a >= b))?,
+                "Lt" => fold_with(&args?, |a, b| truth(a < b))?,
+                "Le" => fold_with(&args?, |a, b| truth(a <= b))?,
+                "Equal" => fold_with(&args?, |a, b| truth(a == b))?,
+                "Ne" => fold_with(&args?, |a, b| truth(a != b))?,
+                "And" => fold_with(&args?, |a, b| truth(a != 0.0 && b != 0.0))?,
+                "Or" => fold_with(&args?, |a, b| truth(a != 0.0 || b != 0.0))?,
+                "Xor" => fold_with(&args?, |a, b| truth((a != 0.0) != (b != 0.0)))?,
+                "Not" => map(args?[0], |a| truth(a == 0.0)),
+                "Select" => {
+                    let args = args?;
+                    select(args[0], args[1], args[2])?
+                },
+                "MakeConstant" | "CustomGradient" | "Rematerialize" => args?[0].to_array(),
+                "Cast" => {
+                    let data_type = *node.op.get_args().unwrap()
+                        .downcast::<FundamentalType>().unwrap();
+                    match data_type {
+                        FundamentalType::Boolean => map(args?[0], |a| if a != 0.0 { 1.0 } else { 0.0 }),
+                        FundamentalType::UnsignedInt | FundamentalType::SignedInt =>
+                            map(args?[0], |a| a.trunc()),
+                        FundamentalType::Float => args?[0].to_array(),
+                        FundamentalType::Complex => return Err(ErrorKind::Msg(
+                            "Complex tensors are not supported by the CPU backend.".into()).into())
+                    }
+                },
+                "Broadcast" => broadcast(args?[0], self.dims[id]),
+                "Reorder" => {
+                    let order = *node.op.get_args().unwrap()
+                        .downcast::<[Axis; 4]>().unwrap();
+                    reorder(args?[0], &[order[0] as usize, order[1] as usize,
+                        order[2] as usize, order[3] as usize])
+                },
+                "Sum" => {
+                    let axes = *node.op.get_args().unwrap()
+                        .downcast::<[bool; 4]>().unwrap();
+                    reduce(args?[0], &axes, 0.0, |a, b| a + b)
+                },
+                "Max" | "Min" | "Prod" | "Mean" | "LogSumExp" | "Variance" |
+                "ArgMax" | "ArgMin" | "Any" | "All" => {
+                    let axes = *node.op.get_args().unwrap()
+                        .downcast::<[bool; 4]>().unwrap();
+                    let arg = args?[0];
+                    match node.op.get_meta().name {
+                        "Max" => reduce(arg, &axes, ::std::f64::NEG_INFINITY, f64::max),
+                        "Min" => reduce(arg, &axes, ::std::f64::INFINITY, f64::min),
+                        "Prod" => reduce(arg, &axes, 1.0, |a, b| a * b),
+                        "Mean" => mean(arg, &axes),
+                        "LogSumExp" => log_sum_exp(arg, &axes),
+                        "Variance" => variance(arg, &axes),
+                        "ArgMax" => arg_reduce(arg, &axes, |a, b| a > b),
+                        "ArgMin" => arg_reduce(arg, &axes, |a, b| a < b),
+                        "Any" => reduce(arg, &axes, 0.0, |a, b| truth(a != 0.0 || b != 0.0)),
+                        _ => reduce(arg, &axes, 1.0, |a, b| truth(a != 0.0 && b != 0.0))
+                    }
+                },
+                "Slice" | "SliceAdd" => {
+                    let ranges = *node.op.get_args().unwrap()
+                        .downcast::<[Option<SliceRange>; 4]>().unwrap();
+                    let base = node.ancestors[0];
+                    let mut values = [(0, 0, 1); 4];
+                    for k in 0..4 {
+                        values[k] = match ranges[k] {
+                            Some(ref range) => (eval_sym_int(&range.start, &self.last_deduced)? as usize,
+                                                eval_sym_int(&range.stop, &self.last_deduced)? as usize,
+                                                range.step as usize),
+                            None => (0, self.dims[base][k], 1)
+                        };
+                    }
+                    let args = args?;
+                    if node.op.get_meta().name == "Slice" {
+                        slice(args[0], &values)
+                    } else {
+                        slice_add(args[0], args[1], &values)
+                    }
+                },
+                "Gather" | "Scatter" | "ScatterAdd" => {
+                    let axis = *node.op.get_args().unwrap()
+                        .downcast::<Axis>().unwrap() as usize;
+                    let args = args?;
+                    match node.op.get_meta().name {
+                        "Gather" => gather(args[0], args[1], axis)?,
+                        "Scatter" => scatter(args[0], args[1], args[2], axis, false)?,
+                        _ => scatter(args[0], args[1], args[2], axis, true)?
+                    }
+                },
+                "MatMul" => {
+                    let args = args?;
+                    let mut result = matmul(args[0], args[1])?;
+                    for arg in args.iter().skip(2) {
+                        result = matmul(result.view(), *arg)?;
+                    }
+                    result
+                },
+                "Einsum" => {
+                    let (inputs, output) = *node.op.get_args().unwrap()
+                        .downcast::<(Vec<Vec<char>>, Vec<char>)>().unwrap();
+                    einsum(&args?, &inputs, &output)
+                },
+                "Update" => {
+                    let name = self.gf.graph.nodes[node.ancestors[0]].name.clone();
+                    let upd = args?[1].to_array();
+                    self.parameters.borrow_mut().insert(name, upd);
+                    return Ok(())
+                },
+                name => {
+                    return Err(ErrorKind::Msg(format!("Operator {} not implemented \
+                    in the CPU backend.", name)).into())
+                }
+            }
+        };
+        self.store(id, result)
+    }
+}
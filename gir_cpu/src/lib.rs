@@ -0,0 +1,9 @@
+extern crate gir_core;
+
+pub mod array;
+pub mod backend;
+pub mod function;
+
+pub use self::array::*;
+pub use self::backend::*;
+pub use self::function::*;
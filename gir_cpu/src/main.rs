@@ -0,0 +1,69 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+use gir_core::{GraphFunction, Backend, CompiledFunction};
+use gir_core::errors::Result;
+use gir_core::passes::OptimizationLevel;
+use gir_cpu::CpuArray;
+use std::time::Instant;
+
+fn main() {
+    let f = gir_core::make_example_graph().unwrap();
+    compile_and_run_cpu(f).unwrap();
+}
+
+/// Simple deterministic generator, so that runs are reproducible
+pub struct Xorshift(u64);
+
+impl Xorshift {
+    pub fn uniform(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn array(&mut self, dims: [usize; 4], scale: f64, shift: f64) -> CpuArray {
+        let n = dims[0] * dims[1] * dims[2] * dims[3];
+        let mem = (0..n).map(|_| self.uniform() * scale + shift).collect();
+        CpuArray::new(dims, mem).unwrap()
+    }
+}
+
+pub fn initialize_params(backend: &mut gir_cpu::CpuBackend, rng: &mut Xorshift) -> Result<()> {
+    backend.set_param_value("alpha", CpuArray::scalar(0.001))?;
+    let sizes = [784, 1024, 512, 1024, 512, 1024, 10];
+    for i in 0..6 {
+        let w = rng.array([sizes[i + 1], sizes[i], 1, 1], 0.06, -0.03);
+        backend.set_param_value(&format!("{}::w", i + 1), w)?;
+        let b = CpuArray::constant(0.0, [sizes[i + 1], 1, 1, 1]);
+        backend.set_param_value(&format!("{}::b", i + 1), b)?;
+    }
+    Ok(())
+}
+
+#[allow(unused_variables, unused_mut)]
+pub fn compile_and_run_cpu(func: GraphFunction) -> Result<()> {
+    // Initialize backend
+    let mut backend = gir_cpu::CpuBackend::default();
+    backend.print_general_info().unwrap();
+    backend.print_info().unwrap();
+    // Initialize parameters
+    let mut rng = Xorshift(42);
+    initialize_params(&mut backend, &mut rng)?;
+    // Make inputs
+    let input = rng.array([784, 100, 1, 1], 1.0, 0.0);
+    let target = rng.array([10, 100, 1, 1], 2.0, -1.0);
+    let ins = &vec![&input, &target];
+    // Optimize and compile function
+    let mut f = backend.make_optimized_function(func, OptimizationLevel::O2)?;
+    // Run 10 iterations
+    let start = Instant::now();
+    for i in 0..10 {
+        let result = f.eval(ins)?.pop().unwrap();
+        println!("Iteration {}: {:.5e}", i, result.mem[0]);
+    }
+    let duration = start.elapsed();
+    println!("10 iterations took {}.{:>09}s", duration.as_secs(), duration.subsec_nanos());
+    Ok(())
+}
@@ -0,0 +1,19 @@
+#![allow(dead_code)]
+
+use gir_core::*;
+
+use std::collections::HashMap;
+
+/// A `Float` input with constant dimensions
+pub fn input(g: &GraphWrapper, dims: [usize; 4], name: &str) -> Expr {
+    let shape = Shape::tensor4_shape(dims[0].into(), dims[1].into(), dims[2].into(), dims[3].into());
+    let id = g.get_mut().input(FundamentalType::Float, shape, Some(name.into()));
+    g.as_expr(id).unwrap()
+}
+
+/// The values of the symbolic variables, with `n` as the only one
+pub fn values(n: i64) -> HashMap<String, i64> {
+    let mut values = HashMap::new();
+    values.insert("n".into(), n);
+    values
+}
@@ -0,0 +1,75 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::backend::BackendPrecisions;
+use gir_core::passes::*;
+use gir_cpu::CpuArray;
+use common::*;
+
+#[test]
+fn cost_report_per_scope() {
+    let g = GraphWrapper::default();
+    let x = f_var!(g, (8, "n"), "x");
+    let mut inputs = vec![x.clone()];
+    let mut h = x;
+    for i in 0..2 {
+        g.get_mut().scope.push(format!("layer{}", i));
+        let w = f_var!(g, (8, 8), "w");
+        h = api::tanh(api::mat_mul(&w, &h).unwrap()).unwrap();
+        g.get_mut().scope.pop();
+        inputs.push(w);
+    }
+    let gf = GraphFunction::new_from_expr(&inputs, &[h], false, &[], None).unwrap();
+    let report = gf.cost_report(&BackendPrecisions::default());
+
+    // 2 * 8 * 8 * n for the product and 8 * n for the tanh
+    for n in 1..4 {
+        let ref layer = report["layer0"];
+        assert_eq!(layer.flops.eval(&values(n)).unwrap(), 136 * n);
+        // Reading both arguments of the product and the argument of the tanh
+        assert_eq!(layer.bytes_read.eval(&values(n)).unwrap(), 4 * (64 + 16 * n));
+        assert_eq!(layer.bytes_written.eval(&values(n)).unwrap(), 4 * 16 * n);
+        assert_eq!(report["layer1"], report["layer0"]);
+        assert_eq!(report[""].flops.eval(&values(n)).unwrap(), 2 * 136 * n);
+    }
+}
+
+#[test]
+fn matrix_chain_ordering() {
+    let g = GraphWrapper::default();
+    let a = f_var!(g, (50, 5), "a");
+    let b = f_var!(g, (5, 100), "b");
+    let c = f_var!(g, (100, "n"), "c");
+    let y = api::mat_mul_n(&vec![&a, &b, &c]).unwrap();
+    let mut gf = GraphFunction::new_from_expr(&[a, b, c], &[y], false, &[], None).unwrap();
+    let precisions = BackendPrecisions::default();
+    // From left to right 2 * (50 * 5 * 100 + 50 * 100 * n)
+    let flops = gf.cost_report(&precisions)[""].flops.clone();
+    assert_eq!(flops.eval(&values(10)).unwrap(), 2 * (25000 + 5000 * 10));
+
+    let backend = gir_cpu::CpuBackend::default();
+    let inputs: Vec<CpuArray> = [[50, 5], [5, 100], [100, 3]].iter().enumerate().map(|(i, d)| {
+        let mem = (0..d[0] * d[1]).map(|j| ((i * 7 + j * 5) % 11) as f64 / 11.0 - 0.5).collect();
+        CpuArray::new([d[0], d[1], 1, 1], mem).unwrap()
+    }).collect();
+    let inputs: Vec<&CpuArray> = inputs.iter().collect();
+    let expected = backend.make_function(gf.clone()).eval(&inputs).unwrap();
+
+    assert!(MatrixChainOrdering::default().run(&mut gf).unwrap());
+    for &id in &gf.graph.order {
+        if gf.graph.nodes[id].op.get_meta().name == "MatMul" {
+            assert_eq!(gf.graph.nodes[id].ancestors.len(), 2);
+        }
+    }
+    // Multiplying b and c first gives 2 * (5 * 100 * n + 50 * 5 * n)
+    let flops = gf.cost_report(&precisions)[""].flops.clone();
+    assert_eq!(flops.eval(&values(10)).unwrap(), 2 * 750 * 10);
+    let actual = backend.make_function(gf).eval(&inputs).unwrap();
+    for (e, a) in expected[0].mem.iter().zip(actual[0].mem.iter()) {
+        assert!((e - a).abs() < 1e-12);
+    }
+}
@@ -0,0 +1,44 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use common::*;
+
+#[test]
+fn identical_expressions_are_merged() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [4, 3, 1, 1], "x");
+    let y = input(&g, [4, 3, 1, 1], "y");
+    assert_eq!(api::tanh(&x).unwrap().id, api::tanh(&x).unwrap().id);
+    assert_eq!((&x + &y).id, (&x + &y).id);
+    assert_eq!(api::sum(&x, Axis::Axis1).unwrap().id, api::sum(&x, Axis::Axis1).unwrap().id);
+    assert_eq!(api::transpose(&x).unwrap().id, api::transpose(&x).unwrap().id);
+    assert_eq!(api::slice_axis(&x, Axis::Axis0, 1, 3, 1).unwrap().id,
+               api::slice_axis(&x, Axis::Axis0, 1, 3, 1).unwrap().id);
+    assert_eq!(g.scalar(2.5, FundamentalType::Float).id, g.scalar(2.5, FundamentalType::Float).id);
+    assert_eq!(api::cast(&x, FundamentalType::SignedInt).unwrap().id,
+               api::cast(&x, FundamentalType::SignedInt).unwrap().id);
+}
+
+#[test]
+fn different_arguments_are_kept() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [4, 3, 1, 1], "x");
+    let y = input(&g, [4, 3, 1, 1], "y");
+    // Inputs are never merged, even with the same shape
+    assert!(x.id != y.id);
+    assert!(api::tanh(&x).unwrap().id != api::tanh(&y).unwrap().id);
+    assert!(api::sum(&x, Axis::Axis0).unwrap().id != api::sum(&x, Axis::Axis1).unwrap().id);
+    assert!(api::slice_axis(&x, Axis::Axis0, 1, 3, 1).unwrap().id !=
+            api::slice_axis(&x, Axis::Axis0, 0, 2, 1).unwrap().id);
+    assert!(api::reorder(&x, [Axis::Axis1, Axis::Axis0, Axis::Axis2, Axis::Axis3]).unwrap().id !=
+            api::reorder(&x, [Axis::Axis1, Axis::Axis2, Axis::Axis0, Axis::Axis3]).unwrap().id);
+    assert!(api::cast(&x, FundamentalType::SignedInt).unwrap().id !=
+            api::cast(&x, FundamentalType::UnsignedInt).unwrap().id);
+    // Scalars are compared by their bits, such that 0 and -0 differ
+    assert!(g.scalar(0.0, FundamentalType::Float).id != g.scalar(-0.0, FundamentalType::Float).id);
+    assert!(g.scalar(1.0, FundamentalType::Float).id != g.scalar(1.0, FundamentalType::SignedInt).id);
+}
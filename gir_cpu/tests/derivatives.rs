@@ -0,0 +1,738 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::ops::SliceRange;
+use gir_core::errors::Result;
+use gir_cpu::CpuArray;
+use common::*;
+
+const EPSILON: f64 = 1e-6;
+const TOLERANCE: f64 = 1e-5;
+
+/// Simple deterministic generator of values in [-0.5, 0.5)
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn array(&mut self, dims: [usize; 4]) -> CpuArray {
+        let n = dims[0] * dims[1] * dims[2] * dims[3];
+        let mem = (0..n).map(|_| {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+        }).collect();
+        CpuArray::new(dims, mem).unwrap()
+    }
+}
+
+fn assert_close(name: &str, expected: &CpuArray, actual: &CpuArray) {
+    // Independent derivatives are returned as a scalar zero
+    for i in 0..expected.mem.len() {
+        let value = if actual.mem.len() == 1 { actual.mem[0] } else { actual.mem[i] };
+        let scale = 1.0f64.max(expected.mem[i].abs());
+        assert!((expected.mem[i] - value).abs() < TOLERANCE * scale,
+                "{}: expected {:?}, actual {:?}", name, expected.mem, actual.mem);
+    }
+}
+
+/// Compares the Hessian-vector products of `f` to the finite differences of its gradients.
+/// The function is composed with `Tanh`, so that the second derivatives of linear
+/// operators are not trivially zero.
+fn check_second_derivatives<F>(name: &str, dims: &[[usize; 4]], f: F)
+    where F: Fn(&[Expr]) -> Result<Expr> {
+    let g = GraphWrapper::default();
+    let x: Vec<Expr> = dims.iter().enumerate()
+        .map(|(i, &d)| input(&g, d, &format!("x{}", i))).collect();
+    let v: Vec<Expr> = dims.iter().enumerate()
+        .map(|(i, &d)| input(&g, d, &format!("v{}", i))).collect();
+    let y = api::sum_all(api::tanh(f(&x).unwrap()).unwrap()).unwrap();
+    let grads = derivative::gradient(&y, &x).unwrap();
+    let hvp = derivative::hessian_vector_product(&y, &x, &v).unwrap();
+    let inputs: Vec<Expr> = x.iter().chain(v.iter()).cloned().collect();
+    let outputs: Vec<Expr> = grads.into_iter().chain(hvp.into_iter()).collect();
+    let gf = GraphFunction::new_from_expr(&inputs, &outputs, false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+
+    let mut rng = Xorshift(42);
+    let values: Vec<CpuArray> = dims.iter().chain(dims.iter()).map(|&d| rng.array(d)).collect();
+    let mut plus = values.clone();
+    let mut minus = values.clone();
+    for i in 0..dims.len() {
+        for j in 0..values[i].mem.len() {
+            plus[i].mem[j] += EPSILON * values[i + dims.len()].mem[j];
+            minus[i].mem[j] -= EPSILON * values[i + dims.len()].mem[j];
+        }
+    }
+    let result = func.eval(&values.iter().collect::<Vec<_>>()).unwrap();
+    let result_plus = func.eval(&plus.iter().collect::<Vec<_>>()).unwrap();
+    let result_minus = func.eval(&minus.iter().collect::<Vec<_>>()).unwrap();
+    for i in 0..dims.len() {
+        let mut expected = result_plus[i].clone();
+        for j in 0..expected.mem.len() {
+            let minus = if result_minus[i].mem.len() == 1 { result_minus[i].mem[0] } else { result_minus[i].mem[j] };
+            expected.mem[j] = (expected.mem[j] - minus) / (2.0 * EPSILON);
+        }
+        assert_close(&format!("{} - x{}", name, i), &expected, &result[i + dims.len()]);
+    }
+}
+
+#[test]
+fn second_derivative_add() {
+    check_second_derivatives("Add", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::add(&x[0], &x[1]));
+}
+
+#[test]
+fn second_derivative_neg() {
+    check_second_derivatives("Neg", &[[3, 2, 1, 1]], |x| api::neg(&x[0]));
+}
+
+#[test]
+fn second_derivative_mul() {
+    check_second_derivatives("Mul", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::mul(&x[0], &x[1]));
+    check_second_derivatives("Mul", &[[3, 2, 1, 1]], |x| api::mul(&x[0], &x[0]));
+    check_second_derivatives("Mul", &[[3, 2, 1, 1], [3, 2, 1, 1], [3, 2, 1, 1]],
+                             |x| api::mul_3(&x[0], &x[1], &x[2]));
+}
+
+#[test]
+fn second_derivative_div() {
+    check_second_derivatives("Div", &[[3, 2, 1, 1]], |x| {
+        let two = x[0].wrapper.scalar(2.0, FundamentalType::Float);
+        api::reciprocal(&x[0] + &two)
+    });
+    check_second_derivatives("Div", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let two = x[0].wrapper.scalar(2.0, FundamentalType::Float);
+        api::div(&x[0], &x[1] + &two)
+    });
+}
+
+#[test]
+fn second_derivative_pow() {
+    check_second_derivatives("Pow", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let one = x[0].wrapper.scalar(1.0, FundamentalType::Float);
+        api::pow(&x[0] * &x[0] + &one, &x[1])
+    });
+    check_second_derivatives("Pow", &[[3, 2, 1, 1], [1, 1, 1, 1]], |x| {
+        let one = x[0].wrapper.scalar(1.0, FundamentalType::Float);
+        api::pow(&x[0] * &x[0] + &one, &x[1])
+    });
+}
+
+#[test]
+fn second_derivative_maximum_minimum() {
+    check_second_derivatives("Maximum", &[[3, 2, 1, 1], [3, 2, 1, 1]],
+                             |x| Ok(api::maximum(&x[0], &x[1])? * &x[0]));
+    check_second_derivatives("Minimum", &[[3, 2, 1, 1], [3, 2, 1, 1]],
+                             |x| Ok(api::minimum(&x[0], &x[1])? * &x[1]));
+}
+
+#[test]
+fn second_derivative_atan2() {
+    check_second_derivatives("Atan2", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::atan2(&x[0], &x[1]));
+}
+
+#[test]
+fn second_derivative_clamp() {
+    check_second_derivatives("Clamp", &[[3, 2, 1, 1], [3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let low = -(&x[1] * &x[1]);
+        let high = &x[2] * &x[2];
+        Ok(api::clamp(&x[0], &low, &high)? * &x[0])
+    });
+}
+
+#[test]
+fn second_derivative_select() {
+    check_second_derivatives("Select", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let cond = api::gt(&x[0], &x[1])?;
+        api::select(&cond, &x[0] * &x[0], api::sin(&x[1])?)
+    });
+}
+
+#[test]
+fn second_derivative_tanh() {
+    check_second_derivatives("Tanh", &[[3, 2, 1, 1]], |x| api::tanh(&x[0]));
+}
+
+#[test]
+fn second_derivative_exp_log() {
+    check_second_derivatives("Exp", &[[3, 2, 1, 1]], |x| api::exp(&x[0]));
+    check_second_derivatives("Expm1", &[[3, 2, 1, 1]], |x| api::expm1(&x[0]));
+    check_second_derivatives("Log", &[[3, 2, 1, 1]], |x| {
+        let one = x[0].wrapper.scalar(1.0, FundamentalType::Float);
+        api::log(&x[0] * &x[0] + &one)
+    });
+    check_second_derivatives("Log1p", &[[3, 2, 1, 1]], |x| api::log1p(&x[0]));
+}
+
+#[test]
+fn second_derivative_powers() {
+    check_second_derivatives("Square", &[[3, 2, 1, 1]], |x| api::square(&x[0]));
+    check_second_derivatives("Sqrt", &[[3, 2, 1, 1]], |x| {
+        let one = x[0].wrapper.scalar(1.0, FundamentalType::Float);
+        api::sqrt(&x[0] * &x[0] + &one)
+    });
+    check_second_derivatives("Rsqrt", &[[3, 2, 1, 1]], |x| {
+        let one = x[0].wrapper.scalar(1.0, FundamentalType::Float);
+        api::rsqrt(&x[0] * &x[0] + &one)
+    });
+}
+
+#[test]
+fn second_derivative_activations() {
+    check_second_derivatives("Sigmoid", &[[3, 2, 1, 1]], |x| api::sigmoid(&x[0]));
+    check_second_derivatives("Softplus", &[[3, 2, 1, 1]], |x| api::softplus(&x[0]));
+    check_second_derivatives("Relu", &[[3, 2, 1, 1]], |x| api::relu(&x[0]));
+    check_second_derivatives("Abs", &[[3, 2, 1, 1]], |x| api::abs(&x[0]));
+    check_second_derivatives("Sign", &[[3, 2, 1, 1]], |x| api::mul(api::sign(&x[0])?, &x[0]));
+    check_second_derivatives("Erf", &[[3, 2, 1, 1]], |x| api::erf(&x[0]));
+}
+
+#[test]
+fn second_derivative_trigonometric() {
+    check_second_derivatives("Sin", &[[3, 2, 1, 1]], |x| api::sin(&x[0]));
+    check_second_derivatives("Cos", &[[3, 2, 1, 1]], |x| api::cos(&x[0]));
+    check_second_derivatives("Tan", &[[3, 2, 1, 1]], |x| api::tan(&x[0]));
+    check_second_derivatives("Sinh", &[[3, 2, 1, 1]], |x| api::sinh(&x[0]));
+    check_second_derivatives("Cosh", &[[3, 2, 1, 1]], |x| api::cosh(&x[0]));
+}
+
+#[test]
+fn second_derivative_mat_mul() {
+    check_second_derivatives("MatMul", &[[4, 3, 1, 1], [3, 2, 1, 1]], |x| api::mat_mul(&x[0], &x[1]));
+    check_second_derivatives("MatMul", &[[4, 3, 1, 1], [3, 2, 1, 1], [2, 5, 1, 1]],
+                             |x| api::mat_mul_n(&x.to_vec()));
+}
+
+#[test]
+fn second_derivative_batched_mat_mul() {
+    check_second_derivatives("MatMul", &[[4, 3, 2, 3], [3, 2, 2, 3]], |x| api::mat_mul(&x[0], &x[1]));
+    // Broadcasting over the batch axes
+    check_second_derivatives("MatMul", &[[4, 3, 2, 1], [3, 2, 1, 1]], |x| api::mat_mul(&x[0], &x[1]));
+    check_second_derivatives("MatMul", &[[4, 3, 1, 3], [3, 2, 2, 1], [2, 2, 1, 1]],
+                             |x| api::mat_mul_n(&x.to_vec()));
+}
+
+#[test]
+fn second_derivative_einsum() {
+    check_second_derivatives("Einsum", &[[4, 3, 1, 1], [3, 2, 1, 1]], |x| api::einsum("ij,jk->ik", &x.to_vec()));
+    check_second_derivatives("Einsum", &[[4, 3, 2, 1], [4, 2, 1, 1]], |x| api::einsum("ijb,ib->jb", &x.to_vec()));
+    // Labels summed over in a single argument
+    check_second_derivatives("Einsum", &[[4, 3, 1, 1], [3, 1, 1, 1]], |x| api::einsum("ij,j->", &x.to_vec()));
+    check_second_derivatives("Einsum", &[[4, 3, 2, 1], [2, 5, 1, 1], [5, 1, 1, 1]],
+                             |x| api::einsum("ijk,kl,l->ji", &x.to_vec()));
+}
+
+#[test]
+fn einsum_shapes() {
+    let g = GraphWrapper::default();
+    let a = input(&g, [4, 3, 2, 1], "a");
+    let b = input(&g, [3, 5, 1, 1], "b");
+    let ab = api::einsum("ijk,jl->kli", &vec![&a, &b]).unwrap();
+    assert_eq!(ab.wrapper.get().nodes[ab.id].shape, Shape::tensor3_shape(2.into(), 5.into(), 4.into()));
+    // The implicit output are the labels appearing once in alphabetical order
+    let ab = api::einsum("kji,jl", &vec![&a, &b]).unwrap();
+    assert_eq!(ab.wrapper.get().nodes[ab.id].shape, Shape::tensor3_shape(2.into(), 4.into(), 5.into()));
+    assert!(api::einsum("ijk,kl->il", &vec![&a, &b]).is_err());
+    assert!(api::einsum("ij,jl->il", &vec![&a, &b]).is_err());
+    assert!(api::einsum("ijk,jl->m", &vec![&a, &b]).is_err());
+}
+
+#[test]
+fn batched_mat_mul_shapes() {
+    let g = GraphWrapper::default();
+    let a = input(&g, [4, 3, 2, 1], "a");
+    let b = input(&g, [3, 5, 1, 6], "b");
+    let c = input(&g, [3, 5, 3, 1], "c");
+    let ab = api::mat_mul(&a, &b).unwrap();
+    assert_eq!(ab.wrapper.get().nodes[ab.id].shape, Shape::tensor4_shape(4.into(), 5.into(), 2.into(), 6.into()));
+    assert!(api::mat_mul(&a, &c).is_err());
+}
+
+#[test]
+fn second_derivative_sum() {
+    check_second_derivatives("Sum", &[[3, 2, 1, 1]], |x| api::sum(&x[0], Axis::Axis0));
+    check_second_derivatives("Sum", &[[3, 2, 1, 1]], |x| api::sum(&x[0], Axis::Axis1));
+}
+
+#[test]
+fn second_derivative_reductions() {
+    check_second_derivatives("Max", &[[3, 2, 1, 1]], |x| api::max(&x[0], Axis::Axis0));
+    check_second_derivatives("Min", &[[3, 2, 1, 1]], |x| api::min_axes(&x[0], [true, true, false, false]));
+    check_second_derivatives("Prod", &[[3, 2, 1, 1]], |x| api::prod(&x[0], Axis::Axis1));
+    check_second_derivatives("Mean", &[[3, 2, 1, 1]], |x| api::mean(&x[0], Axis::Axis0));
+    check_second_derivatives("Mean", &[[3, 2, 1, 1]], |x| api::mean_all(&x[0]));
+    check_second_derivatives("LogSumExp", &[[3, 2, 1, 1]], |x| api::log_sum_exp(&x[0], Axis::Axis0));
+    check_second_derivatives("Variance", &[[3, 2, 1, 1]], |x| api::variance(&x[0], Axis::Axis0));
+    check_second_derivatives("Std", &[[3, 2, 1, 1]], |x| api::std_all(&x[0]));
+}
+
+/// A constant integer vector with the given values
+fn index_vector(g: &GraphWrapper, values: &[usize]) -> Result<Expr> {
+    let shape = Shape::vector_shape(values.len().into());
+    let mut result = g.scalar(0.0, FundamentalType::UnsignedInt);
+    for (i, &v) in values.iter().enumerate() {
+        let basis = g.basis(i, shape.clone(), FundamentalType::UnsignedInt)?;
+        result = &result + &(&basis * &g.scalar(v as f64, FundamentalType::UnsignedInt));
+    }
+    Ok(result)
+}
+
+#[test]
+fn second_derivative_slice() {
+    check_second_derivatives("Slice", &[[4, 3, 1, 1]], |x| {
+        let s = api::slice_axis(&x[0], Axis::Axis0, 1, 3, 1)?;
+        api::mul(&s, api::slice_axis(&x[0], Axis::Axis0, 0, 4, 2)?)
+    });
+    check_second_derivatives("SliceStep", &[[4, 3, 1, 1]], |x| {
+        let s = api::slice_axis(&x[0], Axis::Axis1, 0, 3, 2)?;
+        api::mul(&s, &s)
+    });
+    check_second_derivatives("SliceAdd", &[[4, 3, 1, 1], [2, 3, 1, 1]], |x| {
+        let ranges = [Some(SliceRange::new(1.into(), 3.into(), 1)), None, None, None];
+        let y = api::slice_add(&x[0], &x[1] * &x[1], ranges)?;
+        api::mul(&y, &x[0])
+    });
+}
+
+#[test]
+fn second_derivative_gather_scatter() {
+    check_second_derivatives("Gather", &[[4, 3, 1, 1]], |x| {
+        let idx = index_vector(&x[0].wrapper, &[2, 0, 2])?;
+        let y = api::gather(&x[0], &idx, Axis::Axis0)?;
+        api::mul(&y, &y)
+    });
+    check_second_derivatives("Index", &[[4, 3, 1, 1]], |x| {
+        let idx = x[0].wrapper.scalar(1.0, FundamentalType::UnsignedInt);
+        let y = api::index(&x[0], &idx, Axis::Axis1)?;
+        api::mul(&y, &x[0])
+    });
+    check_second_derivatives("ScatterAdd", &[[4, 3, 1, 1], [3, 3, 1, 1]], |x| {
+        let idx = index_vector(&x[0].wrapper, &[3, 1, 3])?;
+        let y = api::scatter_add(&x[0], &idx, &x[1] * &x[1], Axis::Axis0)?;
+        api::mul(&y, &x[0])
+    });
+    check_second_derivatives("Scatter", &[[4, 3, 1, 1], [2, 3, 1, 1]], |x| {
+        let idx = index_vector(&x[0].wrapper, &[3, 0])?;
+        let y = api::scatter(&x[0], &idx, &x[1] * &x[1], Axis::Axis0)?;
+        api::mul(&y, &x[0])
+    });
+}
+
+#[test]
+fn second_derivative_broadcast() {
+    check_second_derivatives("Broadcast", &[[3, 1, 1, 1], [3, 2, 1, 1]],
+                             |x| api::add(api::broadcast_to(&x[0], &x[1])?, &x[1]));
+    check_second_derivatives("Broadcast", &[[1, 2, 1, 1], [3, 2, 1, 1]],
+                             |x| api::mul(&x[0], &x[1]));
+}
+
+#[test]
+fn second_derivative_reorder() {
+    check_second_derivatives("Reorder", &[[3, 2, 1, 1]], |x| api::transpose(&x[0]));
+    check_second_derivatives("Reorder", &[[3, 2, 4, 1]],
+                             |x| api::reorder(&x[0], [Axis::Axis2, Axis::Axis0, Axis::Axis1, Axis::Axis3]));
+}
+
+#[test]
+fn second_derivative_cast() {
+    check_second_derivatives("Cast", &[[3, 2, 1, 1]], |x| api::cast(&x[0], FundamentalType::Float));
+}
+
+#[test]
+fn second_derivative_make_constant() {
+    // Finite differences would also change the constant, so compare to the exact value
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    let v = input(&g, [3, 2, 1, 1], "v");
+    let c = api::make_constant(&x).unwrap();
+    let y = api::sum_all(api::tanh(&c * &x).unwrap()).unwrap();
+    let hvp = derivative::hessian_vector_product(&y, &vec![&x], &vec![&v]).unwrap();
+    let gf = GraphFunction::new_from_expr(&[x, v], &hvp, false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let mut rng = Xorshift(42);
+    let values = vec![rng.array([3, 2, 1, 1]), rng.array([3, 2, 1, 1])];
+    let result = func.eval(&values.iter().collect::<Vec<_>>()).unwrap();
+    let mut expected = values[0].clone();
+    for i in 0..expected.mem.len() {
+        let (c, v) = (values[0].mem[i], values[1].mem[i]);
+        let t = (c * c).tanh();
+        expected.mem[i] = -2.0 * t * (1.0 - t * t) * c * c * v;
+    }
+    assert_close("MakeConstant", &expected, &result[0]);
+}
+
+#[test]
+fn second_derivative_composite() {
+    check_second_derivatives("Composite", &[[4, 3, 1, 1], [4, 1, 1, 1], [3, 2, 1, 1]], |x| {
+        let h = api::tanh(api::mat_mul(&x[0], &x[2])? + &x[1])?;
+        api::mat_mul(api::transpose(&h)?, &h)
+    });
+}
+
+#[test]
+fn forward_diff_matches_reverse_diff() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [4, 3, 1, 1], "x");
+    let w = input(&g, [3, 2, 1, 1], "w");
+    let vx = input(&g, [4, 3, 1, 1], "vx");
+    let vw = input(&g, [3, 2, 1, 1], "vw");
+    let h = api::tanh(api::mat_mul(&x, &w).unwrap()).unwrap();
+    let y = api::sum_all(&h * &h).unwrap();
+    let jvp = derivative::jvp(&y, &vec![&x, &w], &vec![&vx, &vw]).unwrap();
+    let grads = derivative::gradient(&y, &vec![&x, &w]).unwrap();
+    let vjp = api::sum_all(&grads[0] * &vx).unwrap() + api::sum_all(&grads[1] * &vw).unwrap();
+    let gf = GraphFunction::new_from_expr(&[x, w, vx, vw], &[jvp, vjp], false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let mut rng = Xorshift(7);
+    let values = vec![rng.array([4, 3, 1, 1]), rng.array([3, 2, 1, 1]),
+                      rng.array([4, 3, 1, 1]), rng.array([3, 2, 1, 1])];
+    let result = func.eval(&values.iter().collect::<Vec<_>>()).unwrap();
+    assert_close("jvp", &result[1], &result[0]);
+}
+
+/// Compares the Jacobian-vector products of `f` to its finite differences
+fn check_forward_derivatives<F>(name: &str, dims: &[[usize; 4]], f: F)
+    where F: Fn(&[Expr]) -> Result<Expr> {
+    let g = GraphWrapper::default();
+    let x: Vec<Expr> = dims.iter().enumerate()
+        .map(|(i, &d)| input(&g, d, &format!("x{}", i))).collect();
+    let v: Vec<Expr> = dims.iter().enumerate()
+        .map(|(i, &d)| input(&g, d, &format!("v{}", i))).collect();
+    let y = f(&x).unwrap();
+    let jvp = derivative::jvp(&y, &x, &v).unwrap();
+    let inputs: Vec<Expr> = x.iter().chain(v.iter()).cloned().collect();
+    let gf = GraphFunction::new_from_expr(&inputs, &[y, jvp], false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+
+    let mut rng = Xorshift(13);
+    let values: Vec<CpuArray> = dims.iter().chain(dims.iter()).map(|&d| rng.array(d)).collect();
+    let mut plus = values.clone();
+    let mut minus = values.clone();
+    for i in 0..dims.len() {
+        for j in 0..values[i].mem.len() {
+            plus[i].mem[j] += EPSILON * values[i + dims.len()].mem[j];
+            minus[i].mem[j] -= EPSILON * values[i + dims.len()].mem[j];
+        }
+    }
+    let result = func.eval(&values.iter().collect::<Vec<_>>()).unwrap();
+    let result_plus = func.eval(&plus.iter().collect::<Vec<_>>()).unwrap();
+    let result_minus = func.eval(&minus.iter().collect::<Vec<_>>()).unwrap();
+    let mut expected = result_plus[0].clone();
+    for j in 0..expected.mem.len() {
+        expected.mem[j] = (expected.mem[j] - result_minus[0].mem[j]) / (2.0 * EPSILON);
+    }
+    assert_close(name, &expected, &result[1]);
+}
+
+#[test]
+fn forward_diff_arithmetic() {
+    check_forward_derivatives("Add", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::add(&x[0], &x[1]));
+    check_forward_derivatives("Neg", &[[3, 2, 1, 1]], |x| api::neg(&x[0]));
+    check_forward_derivatives("Mul", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::mul(&x[0], &x[1]));
+    check_forward_derivatives("Div", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let two = x[0].wrapper.scalar(2.0, FundamentalType::Float);
+        api::div(&x[0], &x[1] + &two)
+    });
+    check_forward_derivatives("Pow", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let one = x[0].wrapper.scalar(1.0, FundamentalType::Float);
+        api::pow(&x[0] * &x[0] + &one, &x[1])
+    });
+    check_forward_derivatives("Maximum", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::maximum(&x[0], &x[1]));
+    check_forward_derivatives("Minimum", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::minimum(&x[0], &x[1]));
+    check_forward_derivatives("Atan2", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| api::atan2(&x[0], &x[1]));
+    check_forward_derivatives("Select", &[[3, 2, 1, 1], [3, 2, 1, 1]], |x| {
+        let cond = api::gt(&x[0], &x[1])?;
+        api::select(&cond, &x[0] * &x[0], api::sin(&x[1])?)
+    });
+}
+
+#[test]
+fn forward_diff_nonlinear() {
+    check_forward_derivatives("Tanh", &[[3, 2, 1, 1]], |x| api::tanh(&x[0]));
+    check_forward_derivatives("Exp", &[[3, 2, 1, 1]], |x| api::exp(&x[0]));
+    check_forward_derivatives("Log1p", &[[3, 2, 1, 1]], |x| api::log1p(&x[0]));
+    check_forward_derivatives("Sigmoid", &[[3, 2, 1, 1]], |x| api::sigmoid(&x[0]));
+    check_forward_derivatives("Softplus", &[[3, 2, 1, 1]], |x| api::softplus(&x[0]));
+    check_forward_derivatives("Sin", &[[3, 2, 1, 1]], |x| api::sin(&x[0]));
+    check_forward_derivatives("Sign", &[[3, 2, 1, 1]], |x| api::mul(api::sign(&x[0])?, &x[0]));
+}
+
+#[test]
+fn forward_diff_linear_algebra() {
+    check_forward_derivatives("MatMul", &[[4, 3, 1, 1], [3, 2, 1, 1]], |x| api::mat_mul(&x[0], &x[1]));
+    check_forward_derivatives("MatMul", &[[4, 3, 2, 1], [3, 2, 1, 1]], |x| api::mat_mul(&x[0], &x[1]));
+    check_forward_derivatives("Einsum", &[[4, 3, 2, 1], [4, 2, 1, 1]], |x| api::einsum("ijb,ib->jb", &x.to_vec()));
+}
+
+#[test]
+fn forward_diff_reductions() {
+    check_forward_derivatives("Sum", &[[3, 2, 1, 1]], |x| api::sum(&x[0], Axis::Axis0));
+    check_forward_derivatives("Max", &[[3, 2, 1, 1]], |x| api::max(&x[0], Axis::Axis1));
+    check_forward_derivatives("Prod", &[[3, 2, 1, 1]], |x| api::prod(&x[0], Axis::Axis1));
+    check_forward_derivatives("LogSumExp", &[[3, 2, 1, 1]], |x| api::log_sum_exp(&x[0], Axis::Axis0));
+}
+
+#[test]
+fn forward_diff_indexing() {
+    check_forward_derivatives("Slice", &[[4, 3, 1, 1]], |x| api::slice_axis(&x[0], Axis::Axis0, 0, 4, 2));
+    check_forward_derivatives("SliceAdd", &[[4, 3, 1, 1], [2, 3, 1, 1]], |x| {
+        let ranges = [Some(SliceRange::new(1.into(), 3.into(), 1)), None, None, None];
+        api::slice_add(&x[0], &x[1] * &x[1], ranges)
+    });
+    check_forward_derivatives("Gather", &[[4, 3, 1, 1]], |x| {
+        let idx = index_vector(&x[0].wrapper, &[2, 0, 2])?;
+        api::gather(&x[0], &idx, Axis::Axis0)
+    });
+    check_forward_derivatives("ScatterAdd", &[[4, 3, 1, 1], [3, 3, 1, 1]], |x| {
+        let idx = index_vector(&x[0].wrapper, &[3, 1, 3])?;
+        api::scatter_add(&x[0], &idx, &x[1], Axis::Axis0)
+    });
+    check_forward_derivatives("Scatter", &[[4, 3, 1, 1], [2, 3, 1, 1]], |x| {
+        let idx = index_vector(&x[0].wrapper, &[3, 0])?;
+        api::scatter(&x[0], &idx, &x[1], Axis::Axis0)
+    });
+}
+
+#[test]
+fn forward_diff_shapes() {
+    check_forward_derivatives("Broadcast", &[[3, 1, 1, 1], [3, 2, 1, 1]],
+                              |x| api::add(api::broadcast_to(&x[0], &x[1])?, &x[1]));
+    check_forward_derivatives("Reorder", &[[3, 2, 4, 1]],
+                              |x| api::reorder(&x[0], [Axis::Axis2, Axis::Axis0, Axis::Axis1, Axis::Axis3]));
+    check_forward_derivatives("Cast", &[[3, 2, 1, 1]], |x| api::cast(&x[0], FundamentalType::Float));
+}
+
+#[test]
+fn forward_diff_invalid_arguments() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    let v = input(&g, [2, 3, 1, 1], "v");
+    let y = api::tanh(&x).unwrap();
+    // The tangents must match the parameters in number and shape
+    assert!(derivative::jvp(&y, &vec![&x], &Vec::<Expr>::new()).is_err());
+    assert!(derivative::jvp(&y, &vec![&x], &vec![&v]).is_err());
+    assert!(derivative::jvp(&y, &Vec::<Expr>::new(), &Vec::<Expr>::new()).is_err());
+}
+
+#[test]
+fn full_hessian() {
+    let g = GraphWrapper::default();
+    let a = input(&g, [1, 1, 1, 1], "a");
+    let b = input(&g, [1, 1, 1, 1], "b");
+    // f = a^2 b + tanh(a b)
+    let y = &a * &a * &b + api::tanh(&a * &b).unwrap();
+    let hessian = derivative::hessian(&y, &vec![&a, &b]).unwrap();
+    let outputs: Vec<Expr> = hessian.into_iter().flat_map(|row| row.into_iter()).collect();
+    let gf = GraphFunction::new_from_expr(&[a, b], &outputs, false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let (a, b) = (0.3, -0.7);
+    let result = func.eval(&[&CpuArray::scalar(a), &CpuArray::scalar(b)]).unwrap();
+    let t = (a * b as f64).tanh();
+    let d2 = -2.0 * t * (1.0 - t * t);
+    let expected = [2.0 * b + b * b * d2,
+        2.0 * a + (1.0 - t * t) + a * b * d2,
+        2.0 * a + (1.0 - t * t) + a * b * d2,
+        a * a * d2];
+    for (e, r) in expected.iter().zip(result.iter()) {
+        assert_close("hessian", &CpuArray::scalar(*e), r);
+    }
+}
+
+#[test]
+fn full_hessian_requires_scalars() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 1, 1, 1], "x");
+    let y = api::sum_all(&x * &x).unwrap();
+    assert!(derivative::hessian(&y, &vec![&x]).is_err());
+}
+
+/// Checks the Jacobian of `tanh(W x)` with respect to `x`, which is `diag(1 - tanh^2) W`
+fn check_jacobian(rows: usize, columns: usize) {
+    let g = GraphWrapper::default();
+    let w = input(&g, [rows, columns, 1, 1], "w");
+    let x = input(&g, [columns, 1, 1, 1], "x");
+    let y = api::tanh(api::mat_mul(&w, &x).unwrap()).unwrap();
+    let jacobian = derivative::jacobian(&y, &vec![&x]).unwrap();
+    let gf = GraphFunction::new_from_expr(&[w, x], &[y, jacobian[0].clone()], false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let mut rng = Xorshift(13);
+    let values = vec![rng.array([rows, columns, 1, 1]), rng.array([columns, 1, 1, 1])];
+    let result = func.eval(&values.iter().collect::<Vec<_>>()).unwrap();
+    let mut expected = values[0].clone();
+    for i in 0..rows {
+        for j in 0..columns {
+            let t = result[0].mem[i];
+            expected.mem[i + rows * j] = (1.0 - t * t) * values[0].mem[i + rows * j];
+        }
+    }
+    assert_eq!(result[1].dims, [rows, columns, 1, 1]);
+    assert_close("jacobian", &expected, &result[1]);
+}
+
+#[test]
+fn jacobian_reverse_mode() {
+    check_jacobian(2, 5);
+    check_jacobian(1, 3);
+}
+
+#[test]
+fn jacobian_forward_mode() {
+    check_jacobian(5, 2);
+    check_jacobian(3, 1);
+}
+
+/// The Jacobian of a row vector function of a row vector
+fn check_row_jacobian(rows: usize, columns: usize) {
+    let g = GraphWrapper::default();
+    let x = input(&g, [1, columns, 1, 1], "x");
+    let w = input(&g, [columns, rows, 1, 1], "w");
+    let y = api::tanh(api::mat_mul(&x, &w).unwrap()).unwrap();
+    let jacobian = derivative::jacobian(&y, &vec![&x]).unwrap();
+    let gf = GraphFunction::new_from_expr(&[x, w], &[y, jacobian[0].clone()], false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let mut rng = Xorshift(13);
+    let values = vec![rng.array([1, columns, 1, 1]), rng.array([columns, rows, 1, 1])];
+    let result = func.eval(&values.iter().collect::<Vec<_>>()).unwrap();
+    let mut expected = CpuArray::new([rows, columns, 1, 1], vec![0.0; rows * columns]).unwrap();
+    for i in 0..rows {
+        for j in 0..columns {
+            let t = result[0].mem[i];
+            expected.mem[i + rows * j] = (1.0 - t * t) * values[1].mem[j + columns * i];
+        }
+    }
+    assert_eq!(result[1].dims, [rows, columns, 1, 1]);
+    assert_close("jacobian", &expected, &result[1]);
+}
+
+#[test]
+fn jacobian_row_vectors() {
+    check_row_jacobian(2, 5);
+    check_row_jacobian(5, 2);
+    // Only tensors with a single non unit axis are vectors
+    let g = GraphWrapper::default();
+    let x = input(&g, [1, 3, 1, 1], "x");
+    let m = input(&g, [2, 3, 1, 1], "m");
+    assert!(derivative::jacobian(&(&m * &x), &vec![&x]).is_err());
+    let v = input(&g, [1, 1, 4, 1], "v");
+    assert!(derivative::jacobian(api::sum_all(&v).unwrap(), &vec![&v]).is_ok());
+}
+
+#[test]
+fn custom_gradient() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    // Gradient reversal
+    let reversed = api::custom_gradient(&x * &x, |g, a, dx| Ok(vec![(a, api::ids::neg(g, dx)?)])).unwrap();
+    // Straight-through estimator, which skips the Tanh
+    let x_id = x.id;
+    let straight = api::custom_gradient(api::tanh(&x).unwrap(), move |_, _, dx| Ok(vec![(x_id, dx)])).unwrap();
+    let grads = derivative::gradient(&api::sum_all(&reversed).unwrap(), &vec![&x]).unwrap();
+    let straight_grads = derivative::gradient(&api::sum_all(&straight).unwrap(), &vec![&x]).unwrap();
+    let gf = GraphFunction::new_from_expr(&[x], &[reversed, grads[0].clone(), straight_grads[0].clone()],
+                                          false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let value = Xorshift(5).array([3, 2, 1, 1]);
+    let result = func.eval(&[&value]).unwrap();
+    let mut squared = value.clone();
+    let mut expected = value.clone();
+    for i in 0..value.mem.len() {
+        squared.mem[i] = value.mem[i] * value.mem[i];
+        expected.mem[i] = -2.0 * value.mem[i];
+    }
+    assert_close("custom_gradient - value", &squared, &result[0]);
+    assert_close("custom_gradient - reversal", &expected, &result[1]);
+    assert_close("custom_gradient - straight-through", &CpuArray::constant(1.0, [3, 2, 1, 1]), &result[2]);
+}
+
+#[test]
+fn custom_gradient_rejects_descendants() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 1, 1, 1], "x");
+    let y = input(&g, [3, 1, 1, 1], "y");
+    let y_id = y.id;
+    let z = api::custom_gradient(&x, move |_, _, dx| Ok(vec![(y_id, dx)])).unwrap();
+    let f = api::sum_all(&z * &y).unwrap();
+    assert!(derivative::gradient(&f, &vec![&x, &y]).is_err());
+}
+
+/// Builds the gradients of a network with three `layer` scopes, either checkpointing
+/// all of them or only the expressions selected by `mark`
+fn layered_gradients<F>(scopes: &[&str], mark: F) -> (GraphWrapper, Vec<Expr>, Vec<Expr>)
+    where F: Fn(&Expr) {
+    let g = GraphWrapper::default();
+    g.get_mut().props.checkpoint_scopes = scopes.iter().map(|&s| s.into()).collect();
+    let x = input(&g, [4, 3, 1, 1], "x");
+    let mut inputs = vec![x.clone()];
+    let mut h = x;
+    for i in 1..4 {
+        g.get_mut().scope.push(format!("layer{}", i));
+        let w = input(&g, [4, 4, 1, 1], "w");
+        let linear = api::mat_mul(&w, &h).unwrap();
+        mark(&linear);
+        h = api::tanh(api::tanh(&linear).unwrap()).unwrap();
+        g.get_mut().scope.pop();
+        inputs.push(w);
+    }
+    let y = api::sum_all(&h).unwrap();
+    let grads = derivative::gradient(&y, &inputs).unwrap();
+    (g, inputs, grads)
+}
+
+fn eval_gradients(inputs: &[Expr], grads: &[Expr]) -> Vec<CpuArray> {
+    let gf = GraphFunction::new_from_expr(inputs, grads, false, &[], None).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let mut rng = Xorshift(11);
+    // The input of the network is followed by the weights of the layers
+    let values: Vec<CpuArray> = (0..inputs.len())
+        .map(|i| rng.array(if i == 0 { [4, 3, 1, 1] } else { [4, 4, 1, 1] })).collect();
+    func.eval(&values.iter().collect::<Vec<_>>()).unwrap()
+}
+
+#[test]
+fn checkpoint_scopes() {
+    let (_, inputs, grads) = layered_gradients(&[], |_| ());
+    let expected = eval_gradients(&inputs, &grads);
+    let (g, inputs, grads) = layered_gradients(&["layer*"], |_| ());
+    {
+        let graph = g.get();
+        assert!(!graph.op_map.get("Rematerialize").unwrap().is_empty());
+        // Only the outputs of the layers are used by the backward pass
+        for node in graph.nodes.iter().filter(|n| n.grad_level == 0 && !n.ancestors.is_empty()) {
+            let is_output = node.scope.is_empty() || node.children.iter()
+                .any(|&c| graph.nodes[c].grad_level == 0 && graph.nodes[c].scope != node.scope);
+            let used = node.children.iter().any(|&c| graph.nodes[c].grad_level > 0);
+            assert!(is_output || !used, "The expression {} is kept for the backward pass.", node.id);
+        }
+    }
+    for (i, actual) in eval_gradients(&inputs, &grads).iter().enumerate() {
+        assert_close(&format!("checkpoint_scopes - {}", i), &expected[i], actual);
+    }
+}
+
+#[test]
+fn checkpoint_expressions() {
+    let (_, inputs, grads) = layered_gradients(&[], |_| ());
+    let expected = eval_gradients(&inputs, &grads);
+    let (g, inputs, grads) = layered_gradients(&[], |e| api::checkpoint(e).unwrap());
+    for &id in g.get().checkpoints.iter() {
+        let graph = g.get();
+        assert!(graph.nodes[id].children.iter().all(|&c| graph.nodes[c].grad_level == 0),
+                "The expression {} is used by the backward pass.", id);
+    }
+    for (i, actual) in eval_gradients(&inputs, &grads).iter().enumerate() {
+        assert_close(&format!("checkpoint_expressions - {}", i), &expected[i], actual);
+    }
+}
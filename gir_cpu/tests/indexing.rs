@@ -0,0 +1,73 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::ops::SliceRange;
+use gir_cpu::CpuArray;
+
+fn indices(g: &GraphWrapper, length: Dim, name: &str) -> Expr {
+    g.input(FundamentalType::UnsignedInt, Shape::vector_shape(length), Some(name.into()))
+}
+
+#[test]
+fn symbolic_shapes() {
+    let g = GraphWrapper::default();
+    let x = f_var!(g, (5, "n"), "x");
+    let n: SymInt = Dim::from("n").into();
+    let mut n_minus_one = n.clone();
+    n_minus_one -= &1.into();
+    let mut n_minus_two = n_minus_one.clone();
+    n_minus_two -= &1.into();
+
+    let s = api::slice_axis(&x, Axis::Axis1, 1, n_minus_one.clone(), 1).unwrap();
+    assert_eq!(s.get().unwrap().shape, Shape(5.into(), n_minus_two.clone(), 1.into(), 1.into()));
+    let s = api::slice_axis(&x, Axis::Axis0, 0, 5, 2).unwrap();
+    assert_eq!(s.get().unwrap().shape, Shape(3.into(), n.clone(), 1.into(), 1.into()));
+    // The length with a step must be known and the range must be within the shape
+    assert!(api::slice_axis(&x, Axis::Axis1, 0, n.clone(), 2).is_err());
+    assert!(api::slice_axis(&x, Axis::Axis0, 1, 6, 1).is_err());
+    assert!(api::slice_axis(&x, Axis::Axis1, 0, n_minus_one.clone(), 1).is_ok());
+
+    let idx = indices(&g, "k".into(), "idx");
+    let k: SymInt = Dim::from("k").into();
+    let gathered = api::gather(&x, &idx, Axis::Axis0).unwrap();
+    assert_eq!(gathered.get().unwrap().shape, Shape(k.clone(), n.clone(), 1.into(), 1.into()));
+    let column = api::index(&x, g.scalar(0.0, FundamentalType::UnsignedInt), Axis::Axis1).unwrap();
+    assert_eq!(column.get().unwrap().shape, Shape(5.into(), 1.into(), 1.into(), 1.into()));
+    // Indices must be an integer vector and updates must match the gathered shape
+    assert!(api::gather(&x, &x, Axis::Axis0).is_err());
+    assert!(api::scatter_add(&x, &idx, &x, Axis::Axis0).is_err());
+    assert!(api::scatter_add(&x, &idx, &gathered, Axis::Axis0).is_ok());
+}
+
+#[test]
+fn indexing_operators() {
+    let g = GraphWrapper::default();
+    let x = f_var!(g, (4, 2), "x");
+    let u = f_var!(g, (3, 2), "u");
+    let idx = indices(&g, 3.into(), "idx");
+    let outputs = vec![
+        api::slice_axis(&x, Axis::Axis0, 1, 4, 2).unwrap(),
+        api::slice_add(&x, &api::slice_axis(&u, Axis::Axis0, 0, 2, 1).unwrap(),
+                       [Some(SliceRange::new(2.into(), 4.into(), 1)), None, None, None]).unwrap(),
+        api::gather(&x, &idx, Axis::Axis0).unwrap(),
+        api::scatter(&x, &idx, &u, Axis::Axis0).unwrap(),
+        api::scatter_add(&x, &idx, &u, Axis::Axis0).unwrap()];
+
+    let gf = GraphFunction::new_from_expr(&[x, u, idx], &outputs, false, &[], None).unwrap();
+    let mut func = gir_cpu::CpuBackend::default().make_function(gf);
+    let x = CpuArray::new([4, 2, 1, 1], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]).unwrap();
+    let u = CpuArray::new([3, 2, 1, 1], vec![10.0, 20.0, 30.0, 40.0, 50.0, 60.0]).unwrap();
+    let idx = CpuArray::new([3, 1, 1, 1], vec![3.0, 0.0, 2.0]).unwrap();
+    let result = func.eval(&[&x, &u, &idx]).unwrap();
+    assert_eq!(result[0].mem, vec![1.0, 3.0, 5.0, 7.0]);
+    assert_eq!(result[1].mem, vec![0.0, 1.0, 12.0, 23.0, 4.0, 5.0, 46.0, 57.0]);
+    assert_eq!(result[2].mem, vec![3.0, 0.0, 2.0, 7.0, 4.0, 6.0]);
+    assert_eq!(result[3].mem, vec![20.0, 1.0, 30.0, 10.0, 50.0, 5.0, 60.0, 40.0]);
+    assert_eq!(result[4].mem, vec![20.0, 1.0, 32.0, 13.0, 54.0, 5.0, 66.0, 47.0]);
+
+    // Indices outside of the axis are reported at evaluation
+    let idx = CpuArray::new([3, 1, 1, 1], vec![3.0, 4.0, 2.0]).unwrap();
+    assert!(func.eval(&[&x, &u, &idx]).is_err());
+}
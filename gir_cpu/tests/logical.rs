@@ -0,0 +1,108 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_cpu::CpuArray;
+use common::*;
+
+#[test]
+fn logical_operators() {
+    let g = GraphWrapper::default();
+    let a = input(&g, [4, 1, 1, 1], "a");
+    let b = input(&g, [4, 1, 1, 1], "b");
+    let gt = api::gt(&a, &b).unwrap();
+    let le = api::le(&a, &b).unwrap();
+    let eq = api::eq(&a, &b).unwrap();
+    let positive = api::gt(&a, g.scalar(0.0, FundamentalType::Float)).unwrap();
+    let outputs = vec![
+        gt.clone(), api::ge(&a, &b).unwrap(), api::lt(&a, &b).unwrap(), le.clone(),
+        eq.clone(), api::ne(&a, &b).unwrap(), &gt & &positive, &gt | &eq, &gt ^ &positive,
+        !&positive, api::select(&gt, &a, &b).unwrap()];
+    for out in outputs.iter().take(10) {
+        assert_eq!(out.get().unwrap().data_type, FundamentalType::Boolean);
+    }
+    assert_eq!(outputs[10].get().unwrap().data_type, FundamentalType::Float);
+    // Logical operators accept only booleans
+    assert!(api::and(&gt, &a).is_err());
+    assert!(api::select(&a, &a, &b).is_err());
+
+    let gf = GraphFunction::new_from_expr(&[a, b], &outputs, false, &[], None).unwrap();
+    let mut func = gir_cpu::CpuBackend::default().make_function(gf);
+    let a = CpuArray::new([4, 1, 1, 1], vec![1.0, -2.0, 3.0, -1.0]).unwrap();
+    let b = CpuArray::new([4, 1, 1, 1], vec![0.0, -2.0, 4.0, -3.0]).unwrap();
+    let result = func.eval(&[&a, &b]).unwrap();
+    let expected: Vec<Vec<f64>> = vec![
+        vec![1.0, 0.0, 0.0, 1.0],
+        vec![1.0, 1.0, 0.0, 1.0],
+        vec![0.0, 0.0, 1.0, 0.0],
+        vec![0.0, 1.0, 1.0, 0.0],
+        vec![0.0, 1.0, 0.0, 0.0],
+        vec![1.0, 0.0, 1.0, 1.0],
+        vec![1.0, 0.0, 0.0, 0.0],
+        vec![1.0, 1.0, 0.0, 1.0],
+        vec![0.0, 0.0, 1.0, 1.0],
+        vec![0.0, 1.0, 0.0, 1.0],
+        vec![1.0, -2.0, 4.0, -1.0]];
+    for (i, (e, r)) in expected.iter().zip(result.iter()).enumerate() {
+        assert_eq!(e, &r.mem, "output {}", i);
+    }
+}
+
+#[test]
+fn index_and_boolean_reductions() {
+    let g = GraphWrapper::default();
+    let a = input(&g, [3, 2, 1, 1], "a");
+    let positive = api::gt(&a, g.scalar(0.0, FundamentalType::Float)).unwrap();
+    let outputs = vec![
+        api::arg_max(&a, Axis::Axis0).unwrap(), api::arg_min_all(&a).unwrap(),
+        api::any(&positive, Axis::Axis0).unwrap(), api::all(&positive, Axis::Axis0).unwrap(),
+        api::log_sum_exp_all(&a).unwrap(), api::variance(&a, Axis::Axis1).unwrap()];
+    assert_eq!(outputs[0].get().unwrap().data_type, FundamentalType::UnsignedInt);
+    assert_eq!(outputs[2].get().unwrap().data_type, FundamentalType::Boolean);
+    assert!(api::any_all(&a).is_err());
+
+    let gf = GraphFunction::new_from_expr(&[a], &outputs, false, &[], None).unwrap();
+    let mut func = gir_cpu::CpuBackend::default().make_function(gf);
+    let a = CpuArray::new([3, 2, 1, 1], vec![1.0, 3.0, 3.0, -1.0, -2.0, -4.0]).unwrap();
+    let result = func.eval(&[&a]).unwrap();
+    assert_eq!(result[0].mem, vec![1.0, 0.0]);
+    assert_eq!(result[1].mem, vec![5.0]);
+    assert_eq!(result[2].mem, vec![1.0, 0.0]);
+    assert_eq!(result[3].mem, vec![1.0, 0.0]);
+    let lse = a.mem.iter().map(|v| v.exp()).sum::<f64>().ln();
+    assert!((result[4].mem[0] - lse).abs() < 1e-12);
+    assert_eq!(result[5].mem, vec![1.0, 6.25, 12.25]);
+}
+
+#[test]
+fn reductions_over_unit_axes() {
+    let g = GraphWrapper::default();
+    let a = input(&g, [3, 1, 1, 1], "a");
+    let s = input(&g, [1, 1, 1, 1], "s");
+    let outputs = vec![
+        api::variance(&a, Axis::Axis1).unwrap(), api::std(&a, Axis::Axis2).unwrap(),
+        api::arg_max(&a, Axis::Axis1).unwrap(), api::arg_min_all(&s).unwrap(),
+        api::variance_all(&s).unwrap(), api::mean(&a, Axis::Axis1).unwrap(), s.clone()];
+    // The variance and the indices are zeros rather than the argument
+    for output in &outputs[..5] {
+        assert!(output.id != a.id && output.id != s.id);
+    }
+    assert_eq!(outputs[2].get().unwrap().data_type, FundamentalType::UnsignedInt);
+    assert_eq!(outputs[3].get().unwrap().data_type, FundamentalType::UnsignedInt);
+    assert_eq!(outputs[5].id, a.id);
+
+    let gf = GraphFunction::new_from_expr(&[a, s], &outputs, false, &[], None).unwrap();
+    let mut func = gir_cpu::CpuBackend::default().make_function(gf);
+    let a = CpuArray::new([3, 1, 1, 1], vec![1.0, -3.0, 2.0]).unwrap();
+    let s = CpuArray::new([1, 1, 1, 1], vec![5.0]).unwrap();
+    let result = func.eval(&[&a, &s]).unwrap();
+    assert_eq!(result[0].mem, vec![0.0, 0.0, 0.0]);
+    assert_eq!(result[1].mem, vec![0.0, 0.0, 0.0]);
+    assert_eq!(result[2].mem, vec![0.0, 0.0, 0.0]);
+    assert_eq!(result[3].mem, vec![0.0]);
+    assert_eq!(result[4].mem, vec![0.0]);
+    assert_eq!(result[5].mem, a.mem);
+}
@@ -0,0 +1,52 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::passes::*;
+use gir_cpu::CpuArray;
+use common::*;
+
+/// Compares the native evaluation of the contraction to the lowered one
+fn check_lowering(spec: &str, dims: &[[usize; 4]]) {
+    let g = GraphWrapper::default();
+    let x: Vec<Expr> = dims.iter().enumerate()
+        .map(|(i, &d)| input(&g, d, &format!("x{}", i))).collect();
+    let y = api::einsum(spec, &x).unwrap();
+    let gf = GraphFunction::new_from_expr(&x, &[y], false, &[], None).unwrap();
+    let mut lowered = gf.clone();
+    assert!(EinsumLowering::default().run(&mut lowered).unwrap());
+    assert!(!lowered.graph.order.iter().any(|&i| lowered.graph.nodes[i].op.get_meta().name == "Einsum"));
+    lowered.graph.verify().unwrap();
+
+    let values: Vec<CpuArray> = dims.iter().enumerate().map(|(i, d)| {
+        let n = d[0] * d[1] * d[2] * d[3];
+        let mem = (0..n).map(|j| ((i * 13 + j * 7) % 17) as f64 / 17.0 - 0.5).collect();
+        CpuArray::new(*d, mem).unwrap()
+    }).collect();
+    let values: Vec<&CpuArray> = values.iter().collect();
+    let backend = gir_cpu::CpuBackend::default();
+    let expected = backend.make_function(gf).eval(&values).unwrap();
+    let actual = backend.make_function(lowered).eval(&values).unwrap();
+    assert_eq!(expected[0].dims, actual[0].dims, "{}", spec);
+    for (e, a) in expected[0].mem.iter().zip(actual[0].mem.iter()) {
+        assert!((e - a).abs() < 1e-12, "{}: expected {:?}, actual {:?}", spec, expected[0].mem, actual[0].mem);
+    }
+}
+
+#[test]
+fn einsum_lowering() {
+    check_lowering("ij,jk->ik", &[[4, 3, 1, 1], [3, 2, 1, 1]]);
+    check_lowering("ij->ji", &[[4, 3, 1, 1]]);
+    check_lowering("ijk->j", &[[4, 3, 2, 1]]);
+    check_lowering("ij,ij->i", &[[4, 3, 1, 1], [4, 3, 1, 1]]);
+    check_lowering("i,j->ij", &[[4, 1, 1, 1], [3, 1, 1, 1]]);
+    // Batched products and labels summed over in a single argument
+    check_lowering("bij,bjk->kib", &[[2, 4, 3, 1], [2, 3, 5, 1]]);
+    check_lowering("ijkl,jm->mi", &[[2, 3, 2, 2], [3, 4, 1, 1]]);
+    // Elementwise products of several axes
+    check_lowering("ijk,jkl->il", &[[2, 3, 2, 1], [3, 2, 4, 1]]);
+    check_lowering("ij,jk,kl->li", &[[2, 3, 1, 1], [3, 4, 1, 1], [4, 5, 1, 1]]);
+}
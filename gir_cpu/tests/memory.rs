@@ -0,0 +1,143 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::passes::*;
+use gir_cpu::CpuArray;
+use common::*;
+
+/// The gradients of a network with four layers on `n` samples
+fn network() -> GraphFunction {
+    let g = GraphWrapper::default();
+    let x = f_var!(g, (8, "n"), "x");
+    let mut inputs = vec![x.clone()];
+    let mut h = x;
+    for i in 0..4 {
+        let w = f_var!(g, (8, 8), format!("w{}", i));
+        h = api::tanh(api::mat_mul(&w, &h).unwrap()).unwrap();
+        inputs.push(w);
+    }
+    let y = api::sum_all(&h).unwrap();
+    let grads = derivative::gradient(&y, &inputs[1..].to_vec()).unwrap();
+    GraphFunction::new_from_expr(&inputs, &grads, false, &[], None).unwrap()
+}
+
+fn eval(gf: GraphFunction, n: usize) -> Vec<CpuArray> {
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let values: Vec<CpuArray> = (0..5).map(|i| {
+        let dims = if i == 0 { [8, n, 1, 1] } else { [8, 8, 1, 1] };
+        let mem = (0..dims[0] * dims[1]).map(|j| ((i * 31 + j * 17) % 13) as f64 / 13.0 - 0.5).collect();
+        CpuArray::new(dims, mem).unwrap()
+    }).collect();
+    func.eval(&values.iter().collect::<Vec<_>>()).unwrap()
+}
+
+#[test]
+fn memory_planner_meets_budget() {
+    let expected = eval(network(), 6);
+    let mut gf = network();
+    let unlimited = plan_memory(&mut gf, &MemoryBudget::new(1000000.into(), values(100))).unwrap();
+    assert!(unlimited.recomputed.is_empty() && unlimited.deferred.is_empty());
+    let peak = unlimited.peak_memory.eval(&values(100)).unwrap();
+
+    // Keeping one activation less than all of them
+    let limit = peak - 800;
+    let plan = plan_memory(&mut gf, &MemoryBudget::new(limit.into(), values(100))).unwrap();
+    assert!(!plan.recomputed.is_empty());
+    assert!(plan.peak_memory.eval(&values(100)).unwrap() <= limit);
+    assert_eq!(plan.schedule, gf.graph.order);
+    gf.graph.verify().unwrap();
+    for (e, a) in expected.iter().zip(eval(gf, 6).iter()) {
+        for (x, y) in e.mem.iter().zip(a.mem.iter()) {
+            assert!((x - y).abs() < 1e-12, "expected {:?}, actual {:?}", e.mem, a.mem);
+        }
+    }
+}
+
+#[test]
+fn memory_planner_best_effort() {
+    let mut gf = network();
+    let plan = plan_memory(&mut gf, &MemoryBudget::new(1.into(), values(100))).unwrap();
+    // The inputs alone do not fit, so the smallest peak found is returned
+    assert!(plan.peak_memory.eval(&values(100)).unwrap() > 1);
+    gf.graph.verify().unwrap();
+}
+
+#[test]
+fn memory_map_reuses_buffers() {
+    let gf = network();
+    let map = build_memory_map(&gf);
+    let ranges = live_ranges(&gf);
+    let total: i64 = map.abstract_map.keys()
+        .map(|&i| gf.graph.nodes[i].shape.elements().eval(&values(100)).unwrap()).sum();
+    assert!(map.abstract_size.2.eval(&values(100)).unwrap() < total);
+    // Expressions alive at the same time never share memory
+    let regions: Vec<(usize, i64, i64)> = map.abstract_map.iter()
+        .map(|(&i, &(ref offset, ref n))| (i, offset.eval(&values(100)).unwrap(),
+                                           n.eval(&values(100)).unwrap()))
+        .collect();
+    for &(i, o1, n1) in &regions {
+        for &(j, o2, n2) in &regions {
+            let (s1, e1) = ranges[i].unwrap();
+            let (s2, e2) = ranges[j].unwrap();
+            if i != j && o1 < o2 + n2 && o2 < o1 + n1 {
+                assert!(e1 <= s2 || e2 <= s1, "{} and {} overlap", i, j);
+            }
+        }
+    }
+}
+
+#[test]
+fn memory_map_skips_updates() {
+    let g = GraphWrapper::default();
+    let x = f_var!(g, (3, 2), "x");
+    let w = g.parameter(FundamentalType::Float, Shape::matrix_shape(3.into(), 2.into()), "w".into()).unwrap();
+    let y = api::tanh(&x).unwrap();
+    let new_w = &w * &y;
+    let gf = GraphFunction::new_from_expr(&[x], &[y], false, &[(w, new_w)], None).unwrap();
+    let update = gf.graph.op_map["Update"][0];
+    let map = build_memory_map(&gf);
+    assert!(!map.abstract_map.contains_key(&update));
+    assert!(live_ranges(&gf)[update].is_none());
+    // The new value is kept until the update
+    let new_value = gf.graph.nodes[update].ancestors[1];
+    let position = gf.graph.order.iter().position(|&i| i == update).unwrap();
+    assert_eq!(live_ranges(&gf)[new_value].unwrap().1, position);
+}
+
+#[test]
+fn memory_scheduler_reduces_peak() {
+    // All activations are created before any of them is summed
+    let g = GraphWrapper::default();
+    let x = f_var!(g, (8, "n"), "x");
+    let activations: Vec<Expr> = (0..4).map(|i| {
+        let c = g.scalar(i as f64 + 1.0, FundamentalType::Float);
+        api::tanh(&x * &c).unwrap()
+    }).collect();
+    let sums: Vec<Expr> = activations.iter().map(|a| api::sum_all(a).unwrap()).collect();
+    let y = sums.iter().skip(1).fold(sums[0].clone(), |y, s| y + s);
+    let mut gf = GraphFunction::new_from_expr(&[x], &[y], false, &[], None).unwrap();
+
+    let (_, peak, _) = find_peak(&gf, &values(100)).unwrap();
+    let order = gf.graph.order.clone();
+    let mut scheduler = MemoryScheduler::new(values(100));
+    assert!(scheduler.run(&mut gf).unwrap());
+    assert!(scheduler.peak_memory.unwrap() < peak);
+    let mut sorted = gf.graph.order.clone();
+    sorted.sort();
+    let mut expected = order.clone();
+    expected.sort();
+    assert_eq!(sorted, expected);
+    gf.graph.verify().unwrap();
+
+    let backend = gir_cpu::CpuBackend::default();
+    let mut func = backend.make_function(gf);
+    let input = CpuArray::new([8, 3, 1, 1], (0..24).map(|j| j as f64 / 24.0).collect()).unwrap();
+    let result = func.eval(&[&input]).unwrap();
+    let expected: f64 = (1..5).map(|i| input.mem.iter().map(|v| (v * i as f64).tanh()).sum::<f64>()).sum();
+    assert!((result[0].mem[0] - expected).abs() < 1e-12);
+}
@@ -0,0 +1,296 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::passes::*;
+use gir_cpu::CpuArray;
+use common::*;
+
+fn count(gf: &GraphFunction, op: &str) -> usize {
+    gf.graph.order.iter().filter(|&&i| gf.graph.nodes[i].op.get_meta().name == op).count()
+}
+
+#[test]
+fn dead_code_elimination() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    let unused = input(&g, [3, 2, 1, 1], "unused");
+    let w = g.parameter(FundamentalType::Float, Shape::matrix_shape(3.into(), 2.into()), "w".into()).unwrap();
+    let y = api::tanh(&x).unwrap();
+    let dead = api::exp(&x).unwrap();
+    api::sin(&dead).unwrap();
+    api::update(&w, &(&w * &y)).unwrap();
+    let graph = g.get().clone();
+    let mut gf = GraphFunction::from_graph(graph, vec![x.id, unused.id], vec![y.id], "f".into());
+    assert_eq!(count(&gf, "Exp"), 1);
+
+    assert!(DeadCodeElimination::default().run(&mut gf).unwrap());
+    gf.graph.verify().unwrap();
+    assert_eq!(count(&gf, "Exp"), 0);
+    assert_eq!(count(&gf, "Sin"), 0);
+    // The outputs, the updates and all of the inputs are kept
+    assert_eq!(gf.inputs.len(), 2);
+    assert_eq!(gf.graph.nodes[gf.outputs[0]].op.get_meta().name, "Tanh");
+    assert_eq!(count(&gf, "Update"), 1);
+    assert_eq!(count(&gf, "Mul"), 1);
+    assert_eq!(count(&gf, "Input"), 2);
+    // Nothing is left to remove
+    assert!(!DeadCodeElimination::default().run(&mut gf).unwrap());
+}
+
+#[test]
+fn rebuild_replaces_expressions() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    let y = api::sin(api::tanh(&x).unwrap()).unwrap();
+    let gf = GraphFunction::new_from_expr(&[x], &[y], false, &[], None).unwrap();
+    // Replaces every Tanh by a Sigmoid of the same argument
+    let (new_gf, mapping) = rebuild(&gf, |graph, node, ancestors| {
+        if node.op.get_meta().name == "Tanh" {
+            Ok(Some(gir_core::api::ids::sigmoid(graph, ancestors[0])?))
+        } else {
+            Ok(None)
+        }
+    }).unwrap();
+    new_gf.graph.verify().unwrap();
+    assert_eq!(mapping.len(), gf.graph.nodes.len());
+    assert_eq!(count(&new_gf, "Tanh"), 0);
+    assert_eq!(count(&new_gf, "Sigmoid"), 1);
+    let sin = new_gf.outputs[0];
+    assert_eq!(new_gf.graph.nodes[sin].op.get_meta().name, "Sin");
+    assert_eq!(new_gf.graph.nodes[new_gf.graph.nodes[sin].ancestors[0]].op.get_meta().name, "Sigmoid");
+}
+
+#[test]
+fn rebuild_keeps_checkpoints() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    let h = api::tanh(&x).unwrap();
+    api::checkpoint(&h).unwrap();
+    let y = api::sin(&h).unwrap();
+    let mut gf = GraphFunction::new_from_expr(&[x], &[y], false, &[], None).unwrap();
+    let tanh = gf.graph.nodes[gf.outputs[0]].ancestors[0];
+    assert!(gf.graph.checkpoints.contains(&tanh));
+    gf.graph.grad_level = 2;
+    let (new_gf, mapping) = rebuild(&gf, |_, _, _| Ok(None)).unwrap();
+    assert!(new_gf.graph.checkpoints.contains(&mapping[&tanh]));
+    assert_eq!(new_gf.graph.checkpoints.len(), 1);
+    assert_eq!(new_gf.graph.grad_level, 2);
+    // As well as through the optimization pipelines
+    let mut optimized = gf.clone();
+    PassManager::with_level(OptimizationLevel::O2).run(&mut optimized).unwrap();
+    let tanh = optimized.graph.nodes[optimized.outputs[0]].ancestors[0];
+    assert!(optimized.graph.checkpoints.contains(&tanh));
+}
+
+/// A function with constant subexpressions, algebraic identities and a matrix chain
+fn redundant_function() -> GraphFunction {
+    let g = GraphWrapper::default();
+    let a = input(&g, [4, 3, 1, 1], "a");
+    let b = input(&g, [3, 5, 1, 1], "b");
+    let c = input(&g, [5, 2, 1, 1], "c");
+    let one = g.scalar(1.0, FundamentalType::Float);
+    let zero = g.scalar(0.0, FundamentalType::Float);
+    let two = g.scalar(2.0, FundamentalType::Float);
+    let three = g.scalar(3.0, FundamentalType::Float);
+    let ab = api::mat_mul(&a, &b).unwrap();
+    let abc = api::mat_mul(&ab, &c).unwrap();
+    let scaled = &(&abc * &one) * &(&two * &three);
+    let y = -(-&(&scaled + &zero));
+    let z = api::tanh(&api::sum_all(&y).unwrap()).unwrap();
+    GraphFunction::new_from_expr(&[a, b, c], &[y, z], false, &[], None).unwrap()
+}
+
+#[test]
+fn optimization_levels_preserve_values() {
+    let gf = redundant_function();
+    let values: Vec<CpuArray> = [[4, 3, 1, 1], [3, 5, 1, 1], [5, 2, 1, 1]].iter().enumerate().map(|(i, d)| {
+        let n = d[0] * d[1];
+        let mem = (0..n).map(|j| ((i * 11 + j * 5) % 13) as f64 / 13.0 - 0.5).collect();
+        CpuArray::new(*d, mem).unwrap()
+    }).collect();
+    let values: Vec<&CpuArray> = values.iter().collect();
+    let backend = gir_cpu::CpuBackend::default();
+    let expected = backend.make_optimized_function(gf.clone(), OptimizationLevel::O0).unwrap()
+        .eval(&values).unwrap();
+    for &level in &[OptimizationLevel::O1, OptimizationLevel::O2] {
+        let mut optimized = gf.clone();
+        let mut manager = PassManager::with_level(level);
+        manager.verify = true;
+        assert!(manager.run(&mut optimized).unwrap());
+        assert!(optimized.graph.nodes.len() < gf.graph.nodes.len(), "{}", level);
+        assert!(manager.statistics.iter().any(|s| s.changed));
+        let actual = backend.make_function(optimized).eval(&values).unwrap();
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            assert_eq!(e.dims, a.dims);
+            for (u, v) in e.mem.iter().zip(a.mem.iter()) {
+                assert!((u - v).abs() < 1e-12, "{}: expected {:?}, actual {:?}", level, e.mem, a.mem);
+            }
+        }
+    }
+    // O0 does not change the function
+    let mut unchanged = gf.clone();
+    assert!(!optimize(&mut unchanged, OptimizationLevel::O0).unwrap());
+    assert_eq!(unchanged.graph.nodes.len(), gf.graph.nodes.len());
+}
+
+fn scalar_values(gf: &GraphFunction) -> Vec<f64> {
+    gf.graph.order.iter()
+        .filter_map(|&i| constant_value(&gf.graph.nodes[i]))
+        .collect()
+}
+
+#[test]
+fn constant_folding_of_scalars() {
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 2, 1, 1], "x");
+    let two = g.scalar(2.0, FundamentalType::Float);
+    let three = g.scalar(3.0, FundamentalType::Float);
+    let one = g.scalar(1.0, FundamentalType::Float);
+    let s = api::exp(&(&two * &three) + &one).unwrap();
+    let y = &x * &s;
+    let mut gf = GraphFunction::new_from_expr(&[x], &[y], false, &[], None).unwrap();
+    assert_eq!(count(&gf, "Exp"), 1);
+
+    assert!(ConstantFolding::default().run(&mut gf).unwrap());
+    DeadCodeElimination::default().run(&mut gf).unwrap();
+    gf.graph.verify().unwrap();
+    assert_eq!(count(&gf, "Exp"), 0);
+    assert_eq!(count(&gf, "Add"), 0);
+    assert!(scalar_values(&gf).contains(&7.0f64.exp()));
+    let out = gf.outputs[0];
+    assert_eq!(gf.graph.nodes[out].op.get_meta().name, "Mul");
+    // Nothing is left to fold
+    assert!(!ConstantFolding::default().run(&mut gf).unwrap());
+}
+
+#[test]
+fn constant_folding_keeps_inputs_and_tensors() {
+    let g = GraphWrapper::default();
+    // A scalar input is not a constant
+    let x = input(&g, [1, 1, 1, 1], "x");
+    let two = g.scalar(2.0, FundamentalType::Float);
+    let y = api::exp(&(&x * &two)).unwrap();
+    // A constant tensor with a shape is not folded
+    let basis = g.basis(1, Shape::vector_shape(3.into()), FundamentalType::Float).unwrap();
+    let z = api::sin(&basis).unwrap();
+    // Neither is the shape of a symbolic dimension
+    let v = g.input(FundamentalType::Float, Shape::vector_shape("n".into()), Some("v".into()));
+    let n = api::dim0(&v).unwrap();
+    let m = &n * &two;
+    let mut gf = GraphFunction::new_from_expr(&[x, v], &[y, z, m], false, &[], None).unwrap();
+    let nodes = gf.graph.nodes.len();
+    assert!(!ConstantFolding::default().run(&mut gf).unwrap());
+    assert_eq!(gf.graph.nodes.len(), nodes);
+    assert_eq!(count(&gf, "Exp"), 1);
+    assert_eq!(count(&gf, "Sin"), 1);
+    assert_eq!(count(&gf, "Mul"), 2);
+}
+
+/// Simplifies `f` and checks that `op` appears `remaining` times afterwards, and
+/// that the function computes the same values
+fn check_simplification<F>(dims: [usize; 4], op: &str, remaining: usize, f: F)
+    where F: Fn(&Expr) -> Expr {
+    let g = GraphWrapper::default();
+    let x = input(&g, dims, "x");
+    let y = f(&x);
+    let gf = GraphFunction::new_from_expr(&[x], &[y], false, &[], None).unwrap();
+    let mut simplified = gf.clone();
+    assert!(AlgebraicSimplification::default().run(&mut simplified).unwrap(), "{}", op);
+    DeadCodeElimination::default().run(&mut simplified).unwrap();
+    simplified.graph.verify().unwrap();
+    assert_eq!(count(&simplified, op), remaining, "{}", op);
+    assert!(count(&gf, op) > remaining, "{}", op);
+
+    let n = dims[0] * dims[1] * dims[2] * dims[3];
+    let value = CpuArray::new(dims, (0..n).map(|i| i as f64 / 7.0 + 0.25).collect()).unwrap();
+    let backend = gir_cpu::CpuBackend::default();
+    let expected = backend.make_function(gf).eval(&[&value]).unwrap();
+    let actual = backend.make_function(simplified).eval(&[&value]).unwrap();
+    assert_eq!(expected[0].dims, actual[0].dims, "{}", op);
+    for (e, a) in expected[0].mem.iter().zip(actual[0].mem.iter()) {
+        assert!((e - a).abs() < 1e-12, "{}: expected {:?}, actual {:?}", op, expected[0].mem, actual[0].mem);
+    }
+}
+
+#[test]
+fn simplify_double_negation() {
+    check_simplification([3, 2, 1, 1], "Neg", 0, |x| -(-x));
+}
+
+#[test]
+fn simplify_double_reciprocal() {
+    check_simplification([3, 2, 1, 1], "Div", 0, |x| {
+        api::reciprocal(api::reciprocal(x).unwrap()).unwrap()
+    });
+}
+
+#[test]
+fn simplify_add_zero() {
+    check_simplification([3, 2, 1, 1], "Add", 0, |x| {
+        x + &x.wrapper.scalar(0.0, FundamentalType::Float)
+    });
+    // Only the zero is removed from a sum of several terms
+    check_simplification([3, 2, 1, 1], "Add", 1, |x| {
+        let zero = x.wrapper.scalar(0.0, FundamentalType::Float);
+        api::add(&api::add(x, &zero).unwrap(), &api::tanh(x).unwrap()).unwrap()
+    });
+}
+
+#[test]
+fn simplify_mul_one() {
+    check_simplification([3, 2, 1, 1], "Mul", 0, |x| {
+        x * &x.wrapper.scalar(1.0, FundamentalType::Float)
+    });
+}
+
+#[test]
+fn simplify_reorder() {
+    // Transposing twice is the identity
+    check_simplification([3, 2, 1, 1], "Reorder", 0, |x| {
+        api::transpose(api::transpose(x).unwrap()).unwrap()
+    });
+    // Nested reorders are combined
+    check_simplification([4, 3, 2, 1], "Reorder", 1, |x| {
+        let y = api::reorder(x, [Axis::Axis1, Axis::Axis2, Axis::Axis0, Axis::Axis3]).unwrap();
+        api::reorder(&y, [Axis::Axis1, Axis::Axis0, Axis::Axis2, Axis::Axis3]).unwrap()
+    });
+}
+
+#[test]
+fn simplify_identity_broadcast_and_cast() {
+    // Broadcasting a unit axis to its own size
+    check_simplification([3, 1, 1, 1], "Broadcast", 0, |x| {
+        let d = api::dim1(x).unwrap();
+        let id = x.wrapper.get_mut().apply_op(Box::new(gir_core::ops::Broadcast {
+            axes: [false, true, false, false]
+        }), vec![x.id, d.id]).unwrap();
+        x.wrapper.as_expr(id).unwrap()
+    });
+    check_simplification([3, 2, 1, 1], "Cast", 0, |x| api::cast(x, FundamentalType::Float).unwrap());
+}
+
+#[test]
+fn simplify_custom_gradient() {
+    check_simplification([3, 2, 1, 1], "CustomGradient", 0, |x| {
+        let y = api::custom_gradient(x, |g, y, dy| Ok(vec![(g.nodes[y].ancestors[0], dy)])).unwrap();
+        api::tanh(&y).unwrap()
+    });
+}
+
+#[test]
+fn simplify_sum() {
+    // Nested sums are combined
+    check_simplification([3, 2, 1, 1], "Sum", 1, |x| {
+        api::sum(api::sum(x, Axis::Axis0).unwrap(), Axis::Axis1).unwrap()
+    });
+    // Sums over unit axes never make it into the graph
+    let g = GraphWrapper::default();
+    let x = input(&g, [3, 1, 1, 1], "x");
+    let id = g.get_mut().apply_op(Box::new(gir_core::ops::Sum { axes: [false, true, false, false] }),
+                                  vec![x.id]).unwrap();
+    assert_eq!(id, x.id);
+}
@@ -0,0 +1,197 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::errors::ErrorKind;
+use gir_core::export::{GraphExporter, TextExporter, BinaryExporter};
+use gir_core::import::{FunctionImporter, TextImporter, BinaryImporter};
+use gir_core::ops::{Operator, OperatorRegistry, OpArg, SliceRange, SymIntInput};
+
+/// A function with symbolic shapes and operators with arguments of every kind
+fn example_function() -> GraphFunction {
+    let g = GraphWrapper::default();
+    let n: SymInt = Dim::from("n").into();
+    let mut n_minus_one = n.clone();
+    n_minus_one -= &1.into();
+    let x = g.input(FundamentalType::Float, Shape::matrix_shape(8.into(), "n".into()), Some("x".into()));
+    let w = g.input(FundamentalType::Float, Shape::matrix_shape(4.into(), 8.into()), Some("w".into()));
+    let h = api::tanh(api::mat_mul(&w, &x).unwrap()).unwrap();
+    let h = api::slice(&h, [None, Some(SliceRange::new(1.into(), n_minus_one, 1)), None, None]).unwrap();
+    let h = &h * &g.scalar(-0.5, FundamentalType::Float);
+    let y = api::sum(api::transpose(&h).unwrap(), Axis::Axis0).unwrap();
+    let i = api::arg_max(api::cast(&h, FundamentalType::SignedInt).unwrap(), Axis::Axis1).unwrap();
+    let id = g.get_mut().add_node(SymIntInput { identifier: "n".into() }.apply_null()).unwrap();
+    let d = g.as_expr(id).unwrap();
+    GraphFunction::new_from_expr(&[x, w], &[y, i, d], false, &[], None).unwrap()
+}
+
+/// Checks that the graphs have the same expressions in the same order
+fn assert_same_graph(expected: &Graph, actual: &Graph) {
+    let registry = OperatorRegistry::default();
+    assert_eq!(expected.order.len(), actual.order.len());
+    for (&i, &j) in expected.order.iter().zip(actual.order.iter()) {
+        let (e, a) = (&expected.nodes[i], &actual.nodes[j]);
+        assert_eq!(e.op.get_meta().name, a.op.get_meta().name);
+        assert_eq!(registry.encode(&*e.op).unwrap(), registry.encode(&*a.op).unwrap());
+        assert_eq!(e.ancestors, a.ancestors);
+        assert_eq!(e.shape, a.shape);
+        assert_eq!(e.data_type, a.data_type);
+        assert_eq!(e.name, a.name);
+    }
+}
+
+#[test]
+fn text_round_trip() {
+    let gf = example_function();
+    gf.graph.verify().unwrap();
+    let mut text = Vec::new();
+    TextExporter::new().export_function(&mut text, &gf).unwrap();
+    let imported = TextImporter::new().import(&mut &text[..]).unwrap();
+    imported.graph.verify().unwrap();
+    assert_same_graph(&gf.graph, &imported.graph);
+    assert_eq!(gf.inputs, imported.inputs);
+    assert_eq!(gf.outputs, imported.outputs);
+    // The symbolic shapes are kept
+    let n: SymInt = Dim::from("n").into();
+    assert_eq!(imported.graph.nodes[imported.inputs[0]].shape.1, n);
+
+    // Exporting the imported function gives the same text
+    let mut text_again = Vec::new();
+    TextExporter::new().export_function(&mut text_again, &imported).unwrap();
+    assert_eq!(String::from_utf8(text).unwrap(), String::from_utf8(text_again).unwrap());
+}
+
+#[test]
+fn text_parse_errors() {
+    let importer = TextImporter::new();
+    let malformed = [
+        // Unterminated string
+        "function \"f() -> () {\n}",
+        // Missing body
+        "function \"f\"(%0) -> (%0)",
+        // Unknown data type
+        "function \"f\"(%0) -> (%0) {\n    %0 = Input<Float, (2,1,1,1)>() : Real (2,1,1,1)\n}",
+        // Use before the definition
+        "function \"f\"(%0) -> (%1) {\n    %1 = Tanh(%2)\n    %0 = Input<Float, (2,1,1,1)>()\n}",
+        // Unexpected character
+        "function \"f\"(%0) -> (%0) {\n    %0 = Input<Float, (2,1,1,1)>() $\n}",
+        // Wrong shape annotation
+        "function \"f\"(%0) -> (%0) {\n    %0 = Input<Float, (2,1,1,1)>() : Float (3,1,1,1)\n}",
+        // Trailing tokens
+        "function \"f\"(%0) -> (%0) {\n    %0 = Input<Float, (2,1,1,1)>()\n} }",
+    ];
+    for text in malformed.iter() {
+        match importer.parse_function(text) {
+            Err(e) => match *e.kind() {
+                ErrorKind::ParseError(line, _) => assert!(line >= 1, "{}", text),
+                ref kind => panic!("Expected a ParseError for {:?}, found {:?}.", text, kind)
+            },
+            Ok(_) => panic!("Expected a ParseError for {:?}.", text)
+        }
+    }
+}
+
+fn encode(gf: &GraphFunction) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    BinaryExporter::new().export_function(&mut bytes, gf).unwrap();
+    bytes
+}
+
+fn assert_decode_error(bytes: &[u8]) {
+    match BinaryImporter::new().decode_function(bytes) {
+        Err(e) => match *e.kind() {
+            ErrorKind::DecodeError(..) => {},
+            ref kind => panic!("Expected a DecodeError, found {:?}.", kind)
+        },
+        Ok(_) => panic!("Expected a DecodeError.")
+    }
+}
+
+#[test]
+fn binary_round_trip() {
+    let mut gf = example_function();
+    gf.graph.props.policies.implicit_broadcast = Policy::Raise;
+    gf.graph.props.policies.independent_derivative = Policy::Warn;
+    gf.graph.props.checkpoint_scopes = vec!["layer*".into(), "head".into()];
+    gf.graph.props.size_hints.insert("n".into(), 64);
+    gf.graph.props.size_hints.insert("batch".into(), 32);
+    gf.graph.checkpoints.insert(3);
+    let bytes = encode(&gf);
+    let decoded = BinaryImporter::new().decode_function(&bytes).unwrap();
+    decoded.graph.verify().unwrap();
+    assert_same_graph(&gf.graph, &decoded.graph);
+    assert_eq!(gf.graph.props.policies.implicit_broadcast, decoded.graph.props.policies.implicit_broadcast);
+    assert_eq!(gf.graph.props.policies.implicit_cast, decoded.graph.props.policies.implicit_cast);
+    assert_eq!(gf.graph.props.policies.downcast, decoded.graph.props.policies.downcast);
+    assert_eq!(gf.graph.props.policies.independent_derivative,
+               decoded.graph.props.policies.independent_derivative);
+    assert_eq!(gf.inputs, decoded.inputs);
+    assert_eq!(gf.outputs, decoded.outputs);
+    assert_eq!(gf.unique_symints, decoded.unique_symints);
+    assert_eq!(gf.graph.props.checkpoint_scopes, decoded.graph.props.checkpoint_scopes);
+    assert_eq!(gf.graph.props.size_hints, decoded.graph.props.size_hints);
+    assert_eq!(gf.graph.checkpoints, decoded.graph.checkpoints);
+    for (e, a) in gf.graph.nodes.iter().zip(decoded.graph.nodes.iter()) {
+        assert_eq!(e.children, a.children);
+        assert_eq!(e.sym_int, a.sym_int);
+    }
+    // Encoding the decoded function gives the same bytes
+    assert_eq!(bytes, encode(&decoded));
+}
+
+#[test]
+fn binary_decode_errors() {
+    let gf = example_function();
+    let bytes = encode(&gf);
+    // Every strict prefix is truncated
+    for i in 0..bytes.len() {
+        assert_decode_error(&bytes[..i]);
+    }
+    // Trailing bytes
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert_decode_error(&extended);
+    // Wrong magic and version
+    let mut corrupt = bytes.clone();
+    corrupt[0] = b'X';
+    assert_decode_error(&corrupt);
+    let mut corrupt = bytes.clone();
+    corrupt[4] += 1;
+    assert_decode_error(&corrupt);
+
+    // An id which differs from the position of the expression
+    let mut wrong_id = gf.clone();
+    wrong_id.graph.nodes[3].id = 4;
+    assert_decode_error(&encode(&wrong_id));
+    // An ancestor after the expression, which allows for cycles
+    let mut cycle = gf.clone();
+    cycle.graph.nodes[2].ancestors = vec![1, 3];
+    cycle.graph.nodes[3].children.insert(2);
+    assert_decode_error(&encode(&cycle));
+    // A checkpoint of an expression which does not exist
+    let mut checkpoint = gf.clone();
+    checkpoint.graph.checkpoints.insert(gf.graph.nodes.len());
+    assert_decode_error(&encode(&checkpoint));
+}
+
+#[test]
+fn custom_gradients_are_not_serialized() {
+    let g = GraphWrapper::default();
+    let x = g.input(FundamentalType::Float, Shape::vector_shape(3.into()), Some("x".into()));
+    let y = api::custom_gradient(api::tanh(&x).unwrap(), |g, y, dy| Ok(vec![(g.nodes[y].ancestors[0], dy)])).unwrap();
+    let gf = GraphFunction::new_from_expr(&[x], &[y], false, &[], None).unwrap();
+    // The rule can not be exported
+    assert!(TextExporter::new().export_function(&mut Vec::new(), &gf).is_err());
+    assert!(BinaryExporter::new().export_function(&mut Vec::new(), &gf).is_err());
+
+    // Nor can the operator be imported without its rule
+    let mut registry = OperatorRegistry::default();
+    registry.register("CustomGradient", |_: &Operator| Vec::new(), |_: &[OpArg]| unreachable!());
+    let mut text = Vec::new();
+    TextExporter::with_registry(registry.clone()).export_function(&mut text, &gf).unwrap();
+    assert!(TextImporter::new().import(&mut &text[..]).is_err());
+    let mut bytes = Vec::new();
+    BinaryExporter::with_registry(registry).export_function(&mut bytes, &gf).unwrap();
+    assert!(BinaryImporter::new().decode_function(&bytes).is_err());
+}
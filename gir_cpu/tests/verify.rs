@@ -0,0 +1,89 @@
+extern crate gir_core;
+extern crate gir_cpu;
+
+mod common;
+
+use gir_core::*;
+use gir_core::api;
+use gir_core::errors::ErrorKind;
+use common::*;
+
+/// A valid graph computing `tanh(x + y)`, with the ids of the sum and the tanh
+fn example_graph() -> (Graph, usize, usize) {
+    let g = GraphWrapper::default();
+    let x = input(&g, [4, 3, 1, 1], "x");
+    let y = input(&g, [4, 3, 1, 1], "y");
+    let s = &x + &y;
+    let t = api::tanh(&s).unwrap();
+    let graph = g.get().clone();
+    (graph, s.id, t.id)
+}
+
+fn assert_issues(graph: &Graph, expected: &[GraphIssue]) {
+    match graph.verify() {
+        Err(e) => match *e.kind() {
+            ErrorKind::InvalidGraph(ref issues) => {
+                for issue in expected {
+                    assert!(issues.contains(issue), "{} not found in {:?}", issue, issues);
+                }
+            },
+            ref kind => panic!("Expected an InvalidGraph, found {:?}.", kind)
+        },
+        Ok(_) => panic!("Expected the issues {:?}.", expected)
+    }
+}
+
+#[test]
+fn valid_graph() {
+    let (graph, _, _) = example_graph();
+    graph.verify().unwrap();
+    assert!(graph.find_issues().is_empty());
+}
+
+#[test]
+fn dangling_ancestor() {
+    let (mut graph, s, _) = example_graph();
+    graph.nodes[s].ancestors[1] = 42;
+    assert_issues(&graph, &[GraphIssue::InvalidReference { id: s, reference: 42 }]);
+
+    // An ancestor which does not list the expression as its child
+    let (mut graph, s, t) = example_graph();
+    graph.nodes[s].children.remove(&t);
+    assert_issues(&graph, &[GraphIssue::MissingChild { id: t, ancestor: s }]);
+}
+
+#[test]
+fn wrong_shape() {
+    let (mut graph, s, t) = example_graph();
+    let stored = Shape::vector_shape(5.into());
+    graph.nodes[t].shape = stored.clone();
+    assert_issues(&graph, &[GraphIssue::WrongShape {
+        id: t,
+        stored: stored,
+        computed: graph.nodes[s].shape.clone()
+    }]);
+}
+
+#[test]
+fn wrong_data_type() {
+    let (mut graph, _, t) = example_graph();
+    graph.nodes[t].data_type = FundamentalType::SignedInt;
+    assert_issues(&graph, &[GraphIssue::WrongDataType {
+        id: t,
+        stored: FundamentalType::SignedInt,
+        computed: FundamentalType::Float
+    }]);
+}
+
+#[test]
+fn wrong_order() {
+    let (mut graph, s, t) = example_graph();
+    let (i, j) = (graph.order.iter().position(|&x| x == s).unwrap(),
+                  graph.order.iter().position(|&x| x == t).unwrap());
+    graph.order.swap(i, j);
+    assert_issues(&graph, &[GraphIssue::NotTopological { id: t, ancestor: s }]);
+
+    let (mut graph, _, t) = example_graph();
+    graph.order.retain(|&x| x != t);
+    assert_issues(&graph, &[GraphIssue::NotInOrder(t)]);
+}